@@ -3,10 +3,29 @@
 //! This module provides integration with AgentDB for storing and querying
 //! jj operation history, enabling AI agents to learn from past operations.
 
-use crate::{JJError, JJOperation, Result};
+use crate::{JJError, JJOperation, JJWrapper, OperationType, Result, UNDONE_TAG};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::mcp::{MCPClient, MCPClientConfig};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Computes the reward (0.0 to 1.0) recorded for an operation
+pub type RewardFn = Arc<dyn Fn(&JJOperation) -> f64 + Send + Sync>;
+
+/// Default reward heuristic
+///
+/// Failed operations, and operations a later undo reversed (tagged by
+/// [`JJOperationLog::mark_undone_operations`](crate::JJOperationLog::mark_undone_operations)),
+/// earn nothing. Successful ones start at 1.0 and decay with duration,
+/// reaching 0.5 at ten seconds, so fast clean operations are preferred over
+/// slow ones that merely succeeded.
+pub fn default_reward(op: &JJOperation) -> f64 {
+    if !op.success || op.has_tag(UNDONE_TAG) {
+        return 0.0;
+    }
+    1.0 / (1.0 + op.duration_ms as f64 / 10_000.0)
+}
 
 /// Episode data structure for AgentDB storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,8 +57,18 @@ pub struct AgentDBEpisode {
 }
 
 impl AgentDBEpisode {
-    /// Create a new episode from a JJ operation
+    /// Create a new episode from a JJ operation, scored with [`default_reward`]
     pub fn from_operation(op: &JJOperation, session_id: String, agent_id: String) -> Self {
+        Self::from_operation_with_reward(op, session_id, agent_id, &default_reward)
+    }
+
+    /// Create a new episode from a JJ operation with a custom reward heuristic
+    pub fn from_operation_with_reward(
+        op: &JJOperation,
+        session_id: String,
+        agent_id: String,
+        reward: &dyn Fn(&JJOperation) -> f64,
+    ) -> Self {
         Self {
             session_id,
             task: op.command.clone(),
@@ -47,8 +76,8 @@ impl AgentDBEpisode {
             input: None,
             output: None,
             critique: None,
-            success: true,
-            reward: 1.0,
+            success: op.success,
+            reward: reward(op).clamp(0.0, 1.0),
             latency_ms: None,
             tokens_used: None,
             operation: Some(op.clone()),
@@ -59,6 +88,41 @@ impl AgentDBEpisode {
         }
     }
 
+    /// Create an episode whose `output` summarises the operation's diff
+    ///
+    /// For commit-like operations (commit, describe, squash) this runs one
+    /// `jj diff --stat -r` through `wrapper`, comparing the changed commit
+    /// with all of its parents, and records the totals, e.g.
+    /// `2 files changed, 7 insertions(+), 1 deletion(-)`. It reads the
+    /// repository's current state, so call it right after the operation.
+    /// Other operations, and diff failures, leave `output` unset.
+    pub async fn from_operation_with_context(
+        op: &JJOperation,
+        session_id: String,
+        agent_id: String,
+        wrapper: &JJWrapper,
+    ) -> Self {
+        Self::from_operation(op, session_id, agent_id).with_diff_context(wrapper).await
+    }
+
+    /// Fill `output` with the diff summary described in
+    /// [`from_operation_with_context`](Self::from_operation_with_context)
+    async fn with_diff_context(mut self, wrapper: &JJWrapper) -> Self {
+        let Some(op) = &self.operation else {
+            return self;
+        };
+        // `jj commit` and `jj squash` leave the changes in the parent of the new working copy
+        let revision = match op.get_operation_type() {
+            OperationType::Commit | OperationType::Squash => "@-",
+            OperationType::Describe => "@",
+            _ => return self,
+        };
+        if let Ok(summary) = wrapper.revision_summary(revision).await {
+            self.output = Some(summary.to_string());
+        }
+        self
+    }
+
     /// Set input context
     pub fn with_input(mut self, input: String) -> Self {
         self.input = Some(input);
@@ -101,8 +165,19 @@ pub struct AgentDBSync {
     /// MCP client for AgentDB communication (native only)
     #[cfg(not(target_arch = "wasm32"))]
     mcp_client: Option<MCPClient>,
+    /// Reward heuristic applied to synced operations
+    reward_fn: RewardFn,
+    /// Episodes stored without a remote backend, used for offline suggestions
+    local_episodes: Arc<Mutex<VecDeque<AgentDBEpisode>>>,
+    /// How many local episodes to keep; the oldest are dropped first
+    max_local_episodes: usize,
 }
 
+/// Default number of episodes kept without a remote backend
+///
+/// Matches the default `JJConfig::max_log_entries`.
+pub const DEFAULT_MAX_LOCAL_EPISODES: usize = 1000;
+
 impl AgentDBSync {
     /// Create a new AgentDB sync manager
     pub fn new(enabled: bool) -> Self {
@@ -111,6 +186,9 @@ impl AgentDBSync {
             api_url: None,
             #[cfg(not(target_arch = "wasm32"))]
             mcp_client: None,
+            reward_fn: Arc::new(default_reward),
+            local_episodes: Arc::default(),
+            max_local_episodes: DEFAULT_MAX_LOCAL_EPISODES,
         }
     }
 
@@ -127,6 +205,9 @@ impl AgentDBSync {
             enabled,
             api_url: None,
             mcp_client,
+            reward_fn: Arc::new(default_reward),
+            local_episodes: Arc::default(),
+            max_local_episodes: DEFAULT_MAX_LOCAL_EPISODES,
         })
     }
 
@@ -136,6 +217,34 @@ impl AgentDBSync {
         self
     }
 
+    /// Keep at most `max` episodes stored without a remote backend
+    ///
+    /// Offline suggestions only learn from this window of recent episodes.
+    /// Values below 1 are treated as 1.
+    pub fn with_max_local_episodes(mut self, max: usize) -> Self {
+        self.max_local_episodes = max.max(1);
+        self
+    }
+
+    /// Replace the reward heuristic used when syncing operations
+    pub fn with_reward_fn<F>(mut self, reward_fn: F) -> Self
+    where
+        F: Fn(&JJOperation) -> f64 + Send + Sync + 'static,
+    {
+        self.reward_fn = Arc::new(reward_fn);
+        self
+    }
+
+    /// Build the episode recorded for an operation
+    fn episode_for(&self, op: &JJOperation, session_id: &str, agent_id: &str) -> AgentDBEpisode {
+        AgentDBEpisode::from_operation_with_reward(
+            op,
+            session_id.to_string(),
+            agent_id.to_string(),
+            self.reward_fn.as_ref(),
+        )
+    }
+
     /// Sync a single operation to AgentDB
     pub async fn sync_operation(
         &self,
@@ -147,8 +256,26 @@ impl AgentDBSync {
             return Ok(());
         }
 
-        let episode =
-            AgentDBEpisode::from_operation(op, session_id.to_string(), agent_id.to_string());
+        let episode = self.episode_for(op, session_id, agent_id);
+        self.store_episode(&episode).await
+    }
+
+    /// Sync a single operation with a diff summary as its output
+    ///
+    /// Costs one extra jj call for commit-like operations; see
+    /// [`AgentDBEpisode::from_operation_with_context`].
+    pub async fn sync_operation_with_context(
+        &self,
+        op: &JJOperation,
+        session_id: &str,
+        agent_id: &str,
+        wrapper: &JJWrapper,
+    ) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let episode = self.episode_for(op, session_id, agent_id).with_diff_context(wrapper).await;
         self.store_episode(&episode).await
     }
 
@@ -163,7 +290,7 @@ impl AgentDBSync {
         {
             if let Some(client) = &self.mcp_client {
                 let episode_value = serde_json::to_value(episode)
-                    .map_err(|e| JJError::SerializationError(e.to_string()))?;
+                    .map_err(JJError::from)?;
 
                 client.store_pattern(episode_value).await?;
 
@@ -175,8 +302,15 @@ impl AgentDBSync {
         }
 
         // Fallback: Log to console/file
+        {
+            let mut local = self.local_episodes.lock().unwrap();
+            if local.len() >= self.max_local_episodes {
+                local.pop_front();
+            }
+            local.push_back(episode.clone());
+        }
         let episode_json = serde_json::to_string_pretty(episode)
-            .map_err(|e| JJError::SerializationError(e.to_string()))?;
+            .map_err(JJError::from)?;
 
         #[cfg(feature = "native")]
         {
@@ -251,6 +385,35 @@ impl AgentDBSync {
         Ok(vec![])
     }
 
+    /// Suggest the operation type that most often followed `recent_ops` in the past
+    ///
+    /// Past sessions come from AgentDB when an MCP client is configured and
+    /// from the episodes stored locally otherwise. Every place where a session
+    /// ran the same sequence of operation types counts a vote for the type that
+    /// came next. Returns `None` if the sequence was never seen followed by
+    /// anything.
+    pub async fn suggest_next_operation(
+        &self,
+        recent_ops: &[JJOperation],
+    ) -> Result<Option<OperationType>> {
+        if !self.enabled || recent_ops.is_empty() {
+            return Ok(None);
+        }
+
+        let sequence: Vec<&str> = recent_ops.iter().map(|op| op.operation_type.as_str()).collect();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let episodes = if self.mcp_client.is_some() {
+            self.query_similar_operations(&sequence.join(" "), 100).await?
+        } else {
+            self.local_episodes.lock().unwrap().iter().cloned().collect()
+        };
+        #[cfg(target_arch = "wasm32")]
+        let episodes: Vec<AgentDBEpisode> = self.local_episodes.lock().unwrap().iter().cloned().collect();
+
+        Ok(most_common_successor(&episodes, &sequence).map(|t| OperationType::from_string(&t)))
+    }
+
     /// Get statistics for operations related to a task
     pub async fn get_task_statistics(&self, task_pattern: &str) -> Result<TaskStatistics> {
         if !self.enabled {
@@ -287,6 +450,12 @@ impl AgentDBSync {
     }
 
     /// Batch sync multiple operations
+    ///
+    /// Operations reversed by a later `Undo` in the same batch are tagged with
+    /// [`UNDONE_TAG`] first, so the default reward gives them nothing.
+    /// With an API URL (and the `mcp` feature, which provides the HTTP client)
+    /// all episodes go out in a single POST to `{api_url}/episodes/batch`.
+    /// Otherwise each operation is stored individually.
     pub async fn batch_sync_operations(
         &self,
         operations: &[(JJOperation, String, String)], // (operation, session_id, agent_id)
@@ -295,6 +464,24 @@ impl AgentDBSync {
             return Ok(());
         }
 
+        let mut tagged: Vec<(JJOperation, String, String)> = operations.to_vec();
+        let ops: Vec<JJOperation> = tagged.iter().map(|(op, _, _)| op.clone()).collect();
+        for i in crate::operations::undone_indices(&ops) {
+            tagged[i].0.add_tag(UNDONE_TAG.to_string());
+        }
+        let operations = tagged.as_slice();
+
+        #[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
+        {
+            if let Some(api_url) = &self.api_url {
+                let episodes: Vec<AgentDBEpisode> = operations
+                    .iter()
+                    .map(|(op, session_id, agent_id)| self.episode_for(op, session_id, agent_id))
+                    .collect();
+                return self.post_episodes(api_url, &episodes).await;
+            }
+        }
+
         for (op, session_id, agent_id) in operations {
             self.sync_operation(op, session_id, agent_id).await?;
         }
@@ -302,12 +489,67 @@ impl AgentDBSync {
         Ok(())
     }
 
+    /// POST episodes to the AgentDB batch endpoint in one request
+    #[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
+    async fn post_episodes(&self, api_url: &str, episodes: &[AgentDBEpisode]) -> Result<()> {
+        if episodes.is_empty() {
+            return Ok(());
+        }
+
+        let url = format!("{}/episodes/batch", api_url.trim_end_matches('/'));
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&serde_json::json!({ "episodes": episodes }))
+            .send()
+            .await
+            .map_err(JJError::from)?;
+
+        if !response.status().is_success() {
+            return Err(JJError::IoError(format!(
+                "AgentDB batch sync to {} failed with status {}",
+                url,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Check if sync is enabled
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
 }
 
+/// Count which operation type follows `sequence` in each session's history
+fn most_common_successor(episodes: &[AgentDBEpisode], sequence: &[&str]) -> Option<String> {
+    let mut sessions: HashMap<&str, Vec<&AgentDBEpisode>> = HashMap::new();
+    for episode in episodes.iter().filter(|e| e.operation.is_some()) {
+        sessions.entry(&episode.session_id).or_default().push(episode);
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for history in sessions.values_mut() {
+        history.sort_by_key(|e| e.timestamp);
+        let types: Vec<&str> = history
+            .iter()
+            .filter_map(|e| e.operation.as_ref())
+            .map(|op| op.operation_type.as_str())
+            .collect();
+        for window in types.windows(sequence.len() + 1) {
+            if window[..sequence.len()] == *sequence {
+                *counts.entry(window[sequence.len()]).or_default() += 1;
+            }
+        }
+    }
+
+    // Ties go to the alphabetically first type so results are stable
+    counts
+        .into_iter()
+        .max_by(|(a, ca), (b, cb)| ca.cmp(cb).then_with(|| b.cmp(a)))
+        .map(|(t, _)| t.to_string())
+}
+
 /// Statistics for task operations
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TaskStatistics {
@@ -367,6 +609,118 @@ mod tests {
         assert_eq!(episode.reward, 1.0);
     }
 
+    #[test]
+    fn test_failed_operation_earns_no_reward() {
+        let op = JJOperation::builder()
+            .operation_type(OperationType::Rebase)
+            .command("jj rebase -d main".to_string())
+            .failed("conflict".to_string())
+            .build();
+
+        let episode =
+            AgentDBEpisode::from_operation(&op, "session-001".to_string(), "agent-001".to_string());
+
+        assert!(!episode.success);
+        assert_eq!(episode.reward, 0.0);
+    }
+
+    #[test]
+    fn test_reward_decays_with_duration() {
+        let fast = JJOperation::builder().duration_ms(100).build();
+        let slow = JJOperation::builder().duration_ms(10_000).build();
+
+        assert!(default_reward(&fast) > default_reward(&slow));
+        assert!((default_reward(&slow) - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_undone_operation_earns_no_reward() {
+        let log = crate::JJOperationLog::new(10);
+        log.add_operation(JJOperation::builder().operation_type(OperationType::Rebase).build());
+        log.add_operation(JJOperation::builder().operation_type(OperationType::Undo).build());
+        log.mark_undone_operations();
+
+        let rebase = &log.get_all()[0];
+        assert!(rebase.success);
+        assert_eq!(default_reward(rebase), 0.0);
+    }
+
+    #[test]
+    fn test_custom_reward_fn() {
+        let sync = AgentDBSync::new(true).with_reward_fn(|op| if op.success { 7.0 } else { 0.25 });
+        let op = JJOperation::builder().failed("boom".to_string()).build();
+
+        assert_eq!(sync.episode_for(&op, "s", "a").reward, 0.25);
+        // Rewards are clamped to the documented range
+        let op = JJOperation::builder().build();
+        assert_eq!(sync.episode_for(&op, "s", "a").reward, 1.0);
+    }
+
+    /// Executor answering every `jj diff` with a fixed `--stat` output
+    ///
+    /// `@-` is a merge, so `--from @--` fails the way jj does for a revset
+    /// naming several commits.
+    struct DiffStatExecutor {
+        calls: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl crate::native::CommandExecutor for DiffStatExecutor {
+        fn execute<'a>(
+            &'a self,
+            _jj_path: &'a str,
+            _repo_path: Option<&'a str>,
+            args: &'a [String],
+            _command_timeout: std::time::Duration,
+        ) -> crate::native::BoxFuture<'a, Result<String>> {
+            self.calls.lock().unwrap().push(args.to_vec());
+            if args.windows(2).any(|pair| pair[0] == "--from" && pair[1] == "@--") {
+                let stderr = "Error: Revset `@--` resolved to more than one revision\n";
+                return Box::pin(async move { Err(JJError::classify(stderr)) });
+            }
+            let output = if args.first().map(String::as_str) == Some("diff") {
+                "src/lib.rs | 6 +++++-\nsrc/new.rs | 2 ++\n2 files changed, 7 insertions(+), 1 deletion(-)\n"
+            } else {
+                ""
+            };
+            Box::pin(async move { Ok(output.to_string()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_episode_with_diff_context() {
+        let executor = Arc::new(DiffStatExecutor { calls: Mutex::default() });
+        let wrapper = JJWrapper::with_config_checked(crate::JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+        let commit = JJOperation::builder()
+            .operation_type(OperationType::Commit)
+            .command("jj commit -m fix".to_string())
+            .build();
+
+        let episode = AgentDBEpisode::from_operation_with_context(
+            &commit,
+            "session-001".to_string(),
+            "agent-001".to_string(),
+            &wrapper,
+        )
+        .await;
+
+        assert_eq!(
+            episode.output.as_deref(),
+            Some("2 files changed, 7 insertions(+), 1 deletion(-)")
+        );
+        // Diffing against all parents works even though `@-` is a merge
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["diff", "--stat", "-r", "@-"]);
+
+        // Non-commit operations don't trigger a diff
+        let fetch = JJOperation::builder().operation_type(OperationType::GitFetch).build();
+        let episode =
+            AgentDBEpisode::from_operation_with_context(&fetch, "s".to_string(), "a".to_string(), &wrapper)
+                .await;
+        assert!(episode.output.is_none());
+        assert_eq!(executor.calls.lock().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_episode_builder() {
         let op = JJOperation::builder()
@@ -432,4 +786,154 @@ mod tests {
         let result = sync.sync_operation(&op, "session-001", "agent-001").await;
         assert!(result.is_ok());
     }
+
+    #[cfg(feature = "mcp")]
+    #[tokio::test]
+    async fn test_batch_sync_single_request() {
+        use std::sync::{Arc, Mutex};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Minimal HTTP server recording each request line and body
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<(String, serde_json::Value)>>> = Arc::default();
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let (head_len, body_len) = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        let head = String::from_utf8_lossy(&buf[..pos]).to_lowercase();
+                        let len = head
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .map(|v| v.trim().parse::<usize>().unwrap())
+                            .unwrap_or(0);
+                        break (pos + 4, len);
+                    }
+                };
+                while buf.len() < head_len + body_len {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+                let request_line = String::from_utf8_lossy(&buf).lines().next().unwrap().to_string();
+                let body = serde_json::from_slice(&buf[head_len..head_len + body_len]).unwrap();
+                recorded.lock().unwrap().push((request_line, body));
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let operations: Vec<(JJOperation, String, String)> = (0..3)
+            .map(|i| {
+                let op = JJOperation::builder()
+                    .operation_id(format!("op-{}", i))
+                    .command(format!("jj describe -m step-{}", i))
+                    .build();
+                (op, "session-001".to_string(), "agent-001".to_string())
+            })
+            .collect();
+
+        let sync = AgentDBSync::new(true).with_api_url(url);
+        sync.batch_sync_operations(&operations).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].0.starts_with("POST /episodes/batch "));
+        let tasks: Vec<&str> = requests[0].1["episodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["task"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            tasks,
+            vec!["jj describe -m step-0", "jj describe -m step-1", "jj describe -m step-2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suggest_next_operation() {
+        let sync = AgentDBSync::new(true);
+        let sessions = [
+            ("s1", vec![OperationType::Rebase, OperationType::Resolve, OperationType::Push]),
+            ("s2", vec![OperationType::New, OperationType::Rebase, OperationType::Resolve, OperationType::Push]),
+            ("s3", vec![OperationType::Rebase, OperationType::Resolve, OperationType::Describe]),
+            ("s4", vec![OperationType::Rebase, OperationType::Push]),
+        ];
+        for (session, types) in &sessions {
+            for (i, op_type) in types.iter().enumerate() {
+                let op = JJOperation::builder().operation_type(*op_type).build();
+                let mut episode =
+                    AgentDBEpisode::from_operation(&op, session.to_string(), "agent".to_string());
+                episode.timestamp = i as i64;
+                sync.store_episode(&episode).await.unwrap();
+            }
+        }
+
+        let recent: Vec<JJOperation> = [OperationType::Rebase, OperationType::Resolve]
+            .into_iter()
+            .map(|t| JJOperation::builder().operation_type(t).build())
+            .collect();
+        assert_eq!(
+            sync.suggest_next_operation(&recent).await.unwrap(),
+            Some(OperationType::Push)
+        );
+
+        // A sequence never seen before gives no suggestion
+        let unseen = vec![JJOperation::builder().operation_type(OperationType::Split).build()];
+        assert_eq!(sync.suggest_next_operation(&unseen).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_local_episodes_are_capped() {
+        let sync = AgentDBSync::new(true).with_max_local_episodes(3);
+        let types = [
+            OperationType::New,
+            OperationType::Rebase,
+            OperationType::Describe,
+            OperationType::Push,
+            OperationType::Describe,
+        ];
+        for (i, op_type) in types.into_iter().enumerate() {
+            let op = JJOperation::builder().operation_type(op_type).build();
+            let mut episode = AgentDBEpisode::from_operation(&op, "s1".to_string(), "agent".to_string());
+            episode.timestamp = i as i64;
+            sync.store_episode(&episode).await.unwrap();
+        }
+
+        let local = sync.local_episodes.lock().unwrap().clone();
+        let timestamps: Vec<i64> = local.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![2, 3, 4]);
+        assert_eq!(AgentDBSync::new(true).max_local_episodes, DEFAULT_MAX_LOCAL_EPISODES);
+    }
+
+    #[tokio::test]
+    async fn test_batch_sync_penalises_undone_operations() {
+        let sync = AgentDBSync::new(true);
+        let ops: Vec<(JJOperation, String, String)> = [
+            (OperationType::Describe, "jj describe -m first"),
+            (OperationType::Rebase, "jj rebase -d main"),
+            (OperationType::Undo, "jj undo"),
+        ]
+        .into_iter()
+        .map(|(op_type, command)| {
+            let op = JJOperation::builder().operation_type(op_type).command(command.to_string()).build();
+            (op, "s1".to_string(), "agent".to_string())
+        })
+        .collect();
+
+        sync.batch_sync_operations(&ops).await.unwrap();
+
+        let local = sync.local_episodes.lock().unwrap().clone();
+        let rewards: Vec<f64> = local.iter().map(|e| e.reward).collect();
+        assert_eq!(rewards, vec![1.0, 0.0, 1.0]);
+        // The caller's operations are left untouched
+        assert!(!ops[1].0.has_tag(UNDONE_TAG));
+    }
 }
@@ -2,6 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 use napi_derive::napi;
+use std::collections::HashMap;
+
+use crate::error::JJError;
 
 /// Validate repository path to prevent directory traversal attacks
 fn validate_repo_path(path: &str) -> Result<String, String> {
@@ -21,6 +24,40 @@ fn validate_repo_path(path: &str) -> Result<String, String> {
     Ok(path.to_string())
 }
 
+/// Conventional Commits header, e.g. `feat(parser): support revsets`
+pub const CONVENTIONAL_COMMIT_PATTERN: &str =
+    r"^(build|chore|ci|docs|feat|fix|perf|refactor|revert|style|test)(\([\w./-]+\))?!?: \S";
+
+/// Subcommand used for branch operations
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum BranchCommand {
+    /// Legacy `jj branch` (removed in jj 0.26)
+    Branch,
+    /// `jj bookmark` (jj 0.21 and later)
+    Bookmark,
+}
+
+/// Which jj config file `jj config set` writes to
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum ConfigScope {
+    /// The repository's config (`--repo`)
+    Repo,
+    /// The user's config (`--user`)
+    User,
+}
+
+impl ConfigScope {
+    /// `jj config set` flag selecting this scope
+    pub fn flag(&self) -> &'static str {
+        match self {
+            ConfigScope::Repo => "--repo",
+            ConfigScope::User => "--user",
+        }
+    }
+}
+
 /// Configuration for JJWrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[napi(object)]
@@ -40,8 +77,60 @@ pub struct JJConfig {
     /// Maximum operation log entries to keep in memory
     pub max_log_entries: u32,
 
+    /// Record executed commands in the operation log (default: true)
+    #[serde(default = "default_enable_operation_log")]
+    pub enable_operation_log: bool,
+
+    /// Also cap the in-memory operation log at roughly this many bytes (default: no cap)
+    #[serde(default)]
+    pub max_log_bytes: Option<u32>,
+
     /// Enable AgentDB sync
     pub enable_agentdb_sync: bool,
+
+    /// Force the branch subcommand (default: detect from `jj --version`)
+    #[serde(default)]
+    pub branch_command: Option<BranchCommand>,
+
+    /// Override editors so commands fail instead of waiting on a TTY (default: true)
+    #[serde(default = "default_non_interactive")]
+    pub non_interactive: bool,
+
+    /// Reject commit messages not matching this regex (default: no check)
+    #[serde(default)]
+    pub commit_message_pattern: Option<String>,
+
+    /// Revset aliases passed to every command, e.g. `mine` => `author(exact:"me@x")`
+    #[serde(default)]
+    pub revset_aliases: HashMap<String, String>,
+
+    /// JSON Lines file the operation log is loaded from and appended to
+    #[serde(default)]
+    pub log_persist_path: Option<String>,
+
+    /// Reuse read-only command output for this many milliseconds (default: no caching)
+    #[serde(default)]
+    pub cache_ttl_ms: Option<u32>,
+
+    /// Don't log the same jj operation twice (default: false)
+    #[serde(default)]
+    pub dedup_operations: bool,
+
+    /// Maximum number of jj processes run at once (default: 4)
+    #[serde(default = "default_max_concurrent_ops")]
+    pub max_concurrent_ops: u32,
+}
+
+fn default_non_interactive() -> bool {
+    true
+}
+
+fn default_enable_operation_log() -> bool {
+    true
+}
+
+fn default_max_concurrent_ops() -> u32 {
+    4
 }
 
 impl JJConfig {
@@ -55,6 +144,46 @@ impl JJConfig {
         Self::default()
     }
 
+    /// Create a builder that validates on `build()`
+    pub fn builder() -> JJConfigBuilder {
+        JJConfigBuilder::default()
+    }
+
+    /// Validate the configuration
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if self.jj_path.trim().is_empty() {
+            return Err(JJError::InvalidConfig("jj_path cannot be empty".to_string()));
+        }
+        if self.timeout_ms == 0 {
+            return Err(JJError::InvalidConfig("timeout_ms must be greater than 0".to_string()));
+        }
+        if self.max_log_entries == 0 {
+            return Err(JJError::InvalidConfig(
+                "max_log_entries must be greater than 0".to_string(),
+            ));
+        }
+        if self.max_log_bytes == Some(0) {
+            return Err(JJError::InvalidConfig(
+                "max_log_bytes must be greater than 0".to_string(),
+            ));
+        }
+        if self.max_concurrent_ops == 0 {
+            return Err(JJError::InvalidConfig(
+                "max_concurrent_ops must be greater than 0".to_string(),
+            ));
+        }
+        validate_repo_path(&self.repo_path).map_err(JJError::InvalidConfig)?;
+        if self.revset_aliases.keys().any(|name| name.trim().is_empty()) {
+            return Err(JJError::InvalidConfig("revset alias names cannot be empty".to_string()));
+        }
+        if let Some(pattern) = &self.commit_message_pattern {
+            regex::Regex::new(pattern).map_err(|e| {
+                JJError::InvalidConfig(format!("Invalid commit message pattern: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
     /// Set jj executable path
     pub fn set_jj_path(&mut self, path: String) {
         self.jj_path = path;
@@ -105,11 +234,76 @@ impl JJConfig {
         self
     }
 
+    /// Turn operation logging on or off
+    pub fn with_operation_log(mut self, enabled: bool) -> Self {
+        self.enable_operation_log = enabled;
+        self
+    }
+
+    /// Cap the in-memory operation log at roughly `max` bytes
+    pub fn with_max_log_bytes(mut self, max: u32) -> Self {
+        self.max_log_bytes = Some(max);
+        self
+    }
+
     /// Enable AgentDB synchronization
     pub fn with_agentdb_sync(mut self, enable: bool) -> Self {
         self.enable_agentdb_sync = enable;
         self
     }
+
+    /// Force `branch` or `bookmark` subcommands instead of detecting them
+    pub fn with_branch_command(mut self, command: BranchCommand) -> Self {
+        self.branch_command = Some(command);
+        self
+    }
+
+    /// Set whether editors are overridden (disable only with a TTY attached)
+    pub fn with_non_interactive(mut self, non_interactive: bool) -> Self {
+        self.non_interactive = non_interactive;
+        self
+    }
+
+    /// Require commit messages to match `pattern`
+    pub fn with_commit_message_pattern(mut self, pattern: String) -> Self {
+        self.commit_message_pattern = Some(pattern);
+        self
+    }
+
+    /// Require Conventional Commits messages
+    pub fn with_conventional_commits(self) -> Self {
+        self.with_commit_message_pattern(CONVENTIONAL_COMMIT_PATTERN.to_string())
+    }
+
+    /// Define a revset alias usable in any revision argument
+    pub fn with_revset_alias(mut self, name: String, revset: String) -> Self {
+        self.revset_aliases.insert(name, revset);
+        self
+    }
+
+    /// Persist the operation log to a JSON Lines file
+    pub fn with_log_persist_path(mut self, path: String) -> Self {
+        self.log_persist_path = Some(path);
+        self
+    }
+
+    /// Cache read-only command output for `ttl_ms` milliseconds
+    pub fn with_cache_ttl(mut self, ttl_ms: u32) -> Self {
+        self.cache_ttl_ms = Some(ttl_ms);
+        self
+    }
+
+    /// Skip logging operations whose jj operation ID is already logged
+    pub fn with_dedup_operations(mut self, enabled: bool) -> Self {
+        self.dedup_operations = enabled;
+        self
+    }
+
+    /// Run at most `max` jj processes at once
+    pub fn with_max_concurrent_ops(mut self, max: u32) -> Self {
+        self.max_concurrent_ops = max;
+        self
+    }
 }
 
 impl Default for JJConfig {
@@ -123,11 +317,134 @@ impl Default for JJConfig {
             timeout_ms: 30000, // 30 seconds
             verbose: false,
             max_log_entries: 1000,
+            enable_operation_log: true,
+            max_log_bytes: None,
             enable_agentdb_sync: false,
+            branch_command: None,
+            non_interactive: true,
+            commit_message_pattern: None,
+            revset_aliases: HashMap::new(),
+            log_persist_path: None,
+            cache_ttl_ms: None,
+            dedup_operations: false,
+            max_concurrent_ops: default_max_concurrent_ops(),
         }
     }
 }
 
+/// Builder for [`JJConfig`]
+///
+/// Unlike the `with_*` methods, invalid values are not silently dropped:
+/// `build()` validates the result and reports the first problem.
+#[derive(Debug, Clone, Default)]
+pub struct JJConfigBuilder {
+    config: JJConfig,
+}
+
+impl JJConfigBuilder {
+    /// Set jj executable path
+    pub fn jj_path(mut self, path: String) -> Self {
+        self.config.jj_path = path;
+        self
+    }
+
+    /// Set repository path
+    pub fn repo_path(mut self, path: String) -> Self {
+        self.config.repo_path = path;
+        self
+    }
+
+    /// Set operation timeout in milliseconds
+    pub fn timeout_ms(mut self, timeout_ms: u32) -> Self {
+        self.config.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Enable verbose logging
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.config.verbose = verbose;
+        self
+    }
+
+    /// Set max log entries
+    pub fn max_log_entries(mut self, max: u32) -> Self {
+        self.config.max_log_entries = max;
+        self
+    }
+
+    /// Turn operation logging on or off
+    pub fn enable_operation_log(mut self, enabled: bool) -> Self {
+        self.config.enable_operation_log = enabled;
+        self
+    }
+
+    /// Cap the in-memory operation log at roughly `max` bytes
+    pub fn max_log_bytes(mut self, max: u32) -> Self {
+        self.config.max_log_bytes = Some(max);
+        self
+    }
+
+    /// Enable AgentDB synchronization
+    pub fn agentdb_sync(mut self, enable: bool) -> Self {
+        self.config.enable_agentdb_sync = enable;
+        self
+    }
+
+    /// Force `branch` or `bookmark` subcommands
+    pub fn branch_command(mut self, command: BranchCommand) -> Self {
+        self.config.branch_command = Some(command);
+        self
+    }
+
+    /// Allow or forbid commands that open editors
+    pub fn non_interactive(mut self, non_interactive: bool) -> Self {
+        self.config.non_interactive = non_interactive;
+        self
+    }
+
+    /// Require commit messages to match `pattern`
+    pub fn commit_message_pattern(mut self, pattern: String) -> Self {
+        self.config.commit_message_pattern = Some(pattern);
+        self
+    }
+
+    /// Define a revset alias
+    pub fn revset_alias(mut self, name: String, revset: String) -> Self {
+        self.config.revset_aliases.insert(name, revset);
+        self
+    }
+
+    /// Persist the operation log to a JSON Lines file
+    pub fn log_persist_path(mut self, path: String) -> Self {
+        self.config.log_persist_path = Some(path);
+        self
+    }
+
+    /// Cache read-only command output for `ttl_ms` milliseconds
+    pub fn cache_ttl_ms(mut self, ttl_ms: u32) -> Self {
+        self.config.cache_ttl_ms = Some(ttl_ms);
+        self
+    }
+
+    /// Skip logging operations whose jj operation ID is already logged
+    pub fn dedup_operations(mut self, enabled: bool) -> Self {
+        self.config.dedup_operations = enabled;
+        self
+    }
+
+    /// Run at most `max` jj processes at once
+    pub fn max_concurrent_ops(mut self, max: u32) -> Self {
+        self.config.max_concurrent_ops = max;
+        self
+    }
+
+    /// Validate and build the configuration
+    pub fn build(self) -> crate::error::Result<JJConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
 /// Get the default jj binary path (checks for embedded binary first)
 fn get_default_jj_path() -> Result<String, String> {
     // Check cache directory for extracted binary
@@ -164,6 +481,8 @@ mod tests {
         assert_eq!(config.jj_path, "jj");
         assert_eq!(config.timeout_ms, 30000);
         assert!(!config.verbose);
+        assert!(config.non_interactive);
+        assert!(config.enable_operation_log);
     }
 
     #[test]
@@ -177,4 +496,36 @@ mod tests {
         assert_eq!(config.timeout_ms, 60000);
         assert_eq!(config.max_log_entries, 500);
     }
+
+    #[test]
+    fn test_config_builder() {
+        let config = JJConfig::builder()
+            .jj_path("/usr/local/bin/jj".to_string())
+            .repo_path("/tmp/repo".to_string())
+            .timeout_ms(5000)
+            .max_log_entries(50)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.jj_path, "/usr/local/bin/jj");
+        assert_eq!(config.repo_path, "/tmp/repo");
+        assert_eq!(config.timeout_ms, 5000);
+        assert_eq!(config.max_log_entries, 50);
+    }
+
+    #[test]
+    fn test_config_builder_validation() {
+        let err = JJConfig::builder().timeout_ms(0).build().unwrap_err();
+        assert!(matches!(err, JJError::InvalidConfig(ref msg) if msg.contains("timeout_ms")));
+
+        assert!(JJConfig::builder().repo_path("../outside".to_string()).build().is_err());
+        assert!(JJConfig::builder().jj_path(String::new()).build().is_err());
+        assert!(JJConfig::builder()
+            .commit_message_pattern("feat(".to_string())
+            .build()
+            .is_err());
+        assert!(JJConfig::builder().max_concurrent_ops(0).build().is_err());
+        assert!(JJConfig::builder().max_log_bytes(0).build().is_err());
+        assert_eq!(JJConfig::builder().max_log_bytes(1 << 20).build().unwrap().max_log_bytes, Some(1 << 20));
+    }
 }
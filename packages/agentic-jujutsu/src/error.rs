@@ -1,12 +1,18 @@
 //! Error types for agentic-jujutsu
 
+use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Result type alias for agentic-jujutsu operations
 pub type Result<T> = std::result::Result<T, JJError>;
 
 /// Error types for Jujutsu operations
-#[derive(Error, Debug, Clone, PartialEq)]
+///
+/// Variants wrapping a real underlying error (`Io`, `Json`, `Http`) keep it
+/// behind an `Arc`, so the error stays `Clone` and `source()` exposes the
+/// chain. Equality compares the variant and message.
+#[derive(Error, Debug, Clone)]
 pub enum JJError {
     /// jj command not found or not installed
     #[error("jj command not found. Please install Jujutsu: https://github.com/jj-vcs/jj")]
@@ -36,10 +42,23 @@ pub enum JJError {
     #[error("I/O error: {0}")]
     IoError(String),
 
+    /// I/O error with its underlying cause
+    #[error("I/O error: {0}")]
+    Io(#[source] Arc<std::io::Error>),
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
+    /// JSON (de)serialization error with its underlying cause
+    #[error("Serialization error: {0}")]
+    Json(#[source] Arc<serde_json::Error>),
+
+    /// HTTP request error with its underlying cause
+    #[cfg(feature = "mcp")]
+    #[error("HTTP error: {0}")]
+    Http(#[source] Arc<reqwest::Error>),
+
     /// Unknown error
     #[error("Unknown error: {0}")]
     Unknown(String),
@@ -51,6 +70,34 @@ pub enum JJError {
     /// Cryptographic operation error
     #[error("Crypto error: {0}")]
     CryptoError(String),
+
+    /// Operation rejected by a pre-operation hook
+    #[error("Operation vetoed by hook: {0}")]
+    HookVetoed(String),
+
+    /// Revision or revset did not resolve to any commit
+    #[error("Revision not found: {0}")]
+    RevisionNotFound(String),
+
+    /// Operation blocked by unresolved conflicts
+    #[error("Unresolved conflicts: {0}")]
+    UnresolvedConflicts(String),
+
+    /// Path is not inside a jj repository
+    #[error("There is no jj repo in {}", .0.display())]
+    NotARepository(PathBuf),
+
+    /// jj config key has no value
+    #[error("Config key not found: {0}")]
+    ConfigKeyNotFound(String),
+
+    /// File content is not valid UTF-8 text
+    #[error("{0} is a binary file")]
+    BinaryFile(String),
+
+    /// Last operation can't be meaningfully undone locally (e.g. a push)
+    #[error("{0} cannot be undone locally; use undo() to force it")]
+    NotUndoable(String),
 }
 
 impl JJError {
@@ -63,20 +110,149 @@ impl JJError {
     pub fn is_recoverable(&self) -> bool {
         matches!(
             self,
-            JJError::CommandFailed(_) | JJError::ConflictResolutionFailed(_)
+            JJError::CommandFailed(_)
+                | JJError::ConflictResolutionFailed(_)
+                | JJError::UnresolvedConflicts(_)
         )
     }
+
+    /// Check if running the same command again may succeed
+    ///
+    /// True for timeouts, transient network failures while talking to a
+    /// remote, interrupted I/O and repository lock contention; false for
+    /// logic errors such as a missing revision or invalid arguments, which
+    /// fail the same way every time. Retry loops and agent recovery should
+    /// ask this rather than matching on messages themselves.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            JJError::CommandFailed(message) | JJError::Unknown(message) => {
+                is_transient_message(message)
+            }
+            JJError::Io(err) => matches!(
+                err.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::ConnectionRefused
+            ),
+            JJError::IoError(message) => is_transient_message(message),
+            #[cfg(feature = "mcp")]
+            JJError::Http(err) => err.is_timeout() || err.is_connect(),
+            JJError::JJNotFound
+            | JJError::ParseError(_)
+            | JJError::OperationNotFound(_)
+            | JJError::ConflictResolutionFailed(_)
+            | JJError::InvalidConfig(_)
+            | JJError::SerializationError(_)
+            | JJError::Json(_)
+            | JJError::MCPError(_)
+            | JJError::CryptoError(_)
+            | JJError::HookVetoed(_)
+            | JJError::RevisionNotFound(_)
+            | JJError::UnresolvedConflicts(_)
+            | JJError::NotARepository(_)
+            | JJError::ConfigKeyNotFound(_)
+            | JJError::BinaryFile(_)
+            | JJError::NotUndoable(_) => false,
+        }
+    }
+
+    /// Map jj stderr to a typed error
+    ///
+    /// Recognizes missing revisions and operations, unresolved conflicts,
+    /// unset config keys and "no repo" failures; anything else becomes
+    /// `CommandFailed(stderr)`.
+    pub fn classify(stderr: &str) -> JJError {
+        let message = stderr.trim();
+        let first_line = message
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("")
+            .trim()
+            .trim_start_matches("Error: ");
+        let lower = message.to_lowercase();
+
+        if lower.contains("no jj repo in") || lower.contains("not in a jj repo") {
+            let path = quoted(first_line).unwrap_or(".");
+            JJError::NotARepository(PathBuf::from(path))
+        } else if lower.contains("no such revision")
+            || (lower.contains("revision") && lower.contains("doesn't exist"))
+        {
+            let revision = quoted(first_line).unwrap_or(first_line);
+            JJError::RevisionNotFound(revision.to_string())
+        } else if lower.contains("no operation id matching") {
+            let operation = quoted(first_line).unwrap_or(first_line);
+            JJError::OperationNotFound(operation.to_string())
+        } else if let Some(index) = lower.find("value not found for ") {
+            let key = message[index + "value not found for ".len()..]
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim();
+            JJError::ConfigKeyNotFound(key.to_string())
+        } else if lower.contains("unresolved conflicts") || lower.contains("is conflicted") {
+            JJError::UnresolvedConflicts(first_line.to_string())
+        } else {
+            JJError::CommandFailed(stderr.to_string())
+        }
+    }
+}
+
+/// Check whether an error message describes a failure that may go away
+///
+/// Covers the executor's own timeout, network errors from git remotes and
+/// jj's lock/concurrency errors.
+fn is_transient_message(message: &str) -> bool {
+    const TRANSIENT: &[&str] = &[
+        "timeout exceeded",
+        "timed out",
+        "could not resolve host",
+        "failed to connect",
+        "connection refused",
+        "connection reset",
+        "network is unreachable",
+        "temporarily unavailable",
+        "temporary failure",
+        "failed to lock",
+        "concurrent modification",
+    ];
+    let lower = message.to_lowercase();
+    TRANSIENT.iter().any(|pattern| lower.contains(pattern))
+}
+
+/// Extract the first quoted (`"..."` or `` `...` ``) value from a jj message
+fn quoted(text: &str) -> Option<&str> {
+    let start = text.find(['"', '`'])?;
+    let delimiter = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    rest.find(delimiter).map(|end| &rest[..end])
+}
+
+impl PartialEq for JJError {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && self.to_string() == other.to_string()
+    }
 }
 
 impl From<std::io::Error> for JJError {
     fn from(err: std::io::Error) -> Self {
-        JJError::IoError(err.to_string())
+        JJError::Io(Arc::new(err))
     }
 }
 
 impl From<serde_json::Error> for JJError {
     fn from(err: serde_json::Error) -> Self {
-        JJError::SerializationError(err.to_string())
+        JJError::Json(Arc::new(err))
+    }
+}
+
+#[cfg(feature = "mcp")]
+impl From<reqwest::Error> for JJError {
+    fn from(err: reqwest::Error) -> Self {
+        JJError::Http(Arc::new(err))
     }
 }
 
@@ -90,6 +266,135 @@ mod tests {
         assert!(err.to_string().contains("jj command not found"));
     }
 
+    #[test]
+    fn test_classify_revision_not_found() {
+        assert_eq!(
+            JJError::classify("Error: Revision `xyz` doesn't exist\n"),
+            JJError::RevisionNotFound("xyz".into())
+        );
+        assert_eq!(
+            JJError::classify("Error: Revision \"feature\" doesn't exist"),
+            JJError::RevisionNotFound("feature".into())
+        );
+        assert!(matches!(
+            JJError::classify("Error: No such revision: abc"),
+            JJError::RevisionNotFound(_)
+        ));
+    }
+
+    #[test]
+    fn test_classify_operation_not_found() {
+        assert_eq!(
+            JJError::classify("Error: No operation ID matching \"deadbeef\"\n"),
+            JJError::OperationNotFound("deadbeef".into())
+        );
+    }
+
+    #[test]
+    fn test_classify_unresolved_conflicts() {
+        let err = JJError::classify("Error: There are unresolved conflicts at these paths:\nsrc/lib.rs");
+        assert_eq!(
+            err,
+            JJError::UnresolvedConflicts("There are unresolved conflicts at these paths:".into())
+        );
+        assert!(err.is_recoverable());
+    }
+
+    #[test]
+    fn test_classify_not_a_repository() {
+        assert_eq!(
+            JJError::classify("Error: There is no jj repo in \"/tmp/project\"\n"),
+            JJError::NotARepository(PathBuf::from("/tmp/project"))
+        );
+        assert_eq!(
+            JJError::classify("Error: not in a jj repo"),
+            JJError::NotARepository(PathBuf::from("."))
+        );
+    }
+
+    #[test]
+    fn test_classify_config_key_not_found() {
+        assert_eq!(
+            JJError::classify("Config error: Value not found for user.emial\nFor help, see https://jj-vcs.github.io/jj/latest/config/.\n"),
+            JJError::ConfigKeyNotFound("user.emial".into())
+        );
+    }
+
+    #[test]
+    fn test_io_error_keeps_source() {
+        use std::error::Error as _;
+
+        let err = JJError::from(std::io::Error::new(std::io::ErrorKind::NotFound, "missing.jsonl"));
+
+        assert_eq!(err.to_string(), "I/O error: missing.jsonl");
+        let source = err.source().expect("io-backed error has a source");
+        assert_eq!(source.to_string(), "missing.jsonl");
+        assert_eq!(err.clone(), err);
+        assert_ne!(err, JJError::IoError("missing.jsonl".into()));
+    }
+
+    #[test]
+    fn test_json_error_keeps_source() {
+        use std::error::Error as _;
+
+        let err = JJError::from(serde_json::from_str::<u32>("nope").unwrap_err());
+
+        assert!(matches!(err, JJError::Json(_)));
+        assert!(err.source().is_some());
+        assert!(JJError::SerializationError("flat".into()).source().is_none());
+    }
+
+    #[test]
+    fn test_classify_unrecognized() {
+        let stderr = "Error: Something unexpected happened\n";
+        assert_eq!(JJError::classify(stderr), JJError::CommandFailed(stderr.into()));
+    }
+
+    #[test]
+    fn test_retryable() {
+        use std::io::{Error, ErrorKind};
+
+        let retryable = [
+            JJError::CommandFailed("Command timeout exceeded".into()),
+            JJError::CommandFailed("Error: failed to connect to github.com: Connection refused".into()),
+            JJError::CommandFailed("fatal: Could not resolve host: github.com".into()),
+            JJError::CommandFailed("Error: Failed to lock working copy".into()),
+            JJError::Unknown("Resource temporarily unavailable".into()),
+            JJError::IoError("operation timed out".into()),
+            JJError::from(Error::new(ErrorKind::TimedOut, "slow")),
+            JJError::from(Error::new(ErrorKind::Interrupted, "signal")),
+        ];
+        for err in &retryable {
+            assert!(err.is_retryable(), "{:?} should be retryable", err);
+        }
+
+        let permanent = [
+            JJError::JJNotFound,
+            JJError::CommandFailed("Error: Something unexpected happened".into()),
+            JJError::ParseError("bad json".into()),
+            JJError::OperationNotFound("deadbeef".into()),
+            JJError::ConflictResolutionFailed("src/lib.rs".into()),
+            JJError::InvalidConfig("Invalid character in argument".into()),
+            JJError::IoError("permission denied".into()),
+            JJError::from(Error::new(ErrorKind::NotFound, "missing.jsonl")),
+            JJError::SerializationError("flat".into()),
+            JJError::from(serde_json::from_str::<u32>("nope").unwrap_err()),
+            JJError::Unknown("limiter closed".into()),
+            JJError::MCPError("bad request".into()),
+            JJError::CryptoError("bad key".into()),
+            JJError::HookVetoed("policy".into()),
+            JJError::RevisionNotFound("xyz".into()),
+            JJError::UnresolvedConflicts("src/lib.rs".into()),
+            JJError::NotARepository(PathBuf::from("/tmp")),
+            JJError::ConfigKeyNotFound("user.email".into()),
+            JJError::BinaryFile("logo.png".into()),
+            JJError::NotUndoable("GitPush".into()),
+        ];
+        for err in &permanent {
+            assert!(!err.is_retryable(), "{:?} should not be retryable", err);
+        }
+    }
+
     #[test]
     fn test_recoverable() {
         assert!(JJError::CommandFailed("test".into()).is_recoverable());
@@ -4,9 +4,12 @@
 //! agentic-flow hooks system, enabling automatic operation tracking, memory sync,
 //! and multi-agent coordination.
 
-use crate::{JJOperation, JJWrapper, OperationType, Result};
+use crate::native::BoxFuture;
+use crate::{JJError, JJOperation, JJWrapper, OperationType, Result};
 use chrono::Utc;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Context information for hook execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +25,12 @@ pub struct HookContext {
     /// Optional metadata for additional context
     #[serde(default)]
     pub metadata: serde_json::Value,
+    /// Command arguments (excluding the jj binary)
+    ///
+    /// Pre-operation hooks may rewrite these; the wrapper runs whatever is left
+    /// after all hooks have returned.
+    #[serde(default)]
+    pub args: Vec<String>,
 }
 
 impl HookContext {
@@ -33,6 +42,7 @@ impl HookContext {
             task_description,
             timestamp: Utc::now().timestamp(),
             metadata: serde_json::Value::Null,
+            args: Vec::new(),
         }
     }
 
@@ -41,6 +51,12 @@ impl HookContext {
         self.metadata = metadata;
         self
     }
+
+    /// Create context with command arguments
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
 }
 
 /// Types of hook events that can be triggered
@@ -60,6 +76,10 @@ pub enum HookEventType {
     SessionInit,
     /// Session cleanup
     SessionEnd,
+    /// Before a jj command runs (handlers may veto it)
+    PreOperation,
+    /// After a jj command completes
+    PostOperation,
 }
 
 /// Hook event containing operation and context information
@@ -69,6 +89,9 @@ pub struct JJHookEvent {
     pub event_type: HookEventType,
     /// Associated jj operation (if any)
     pub operation: Option<JJOperation>,
+    /// Type of the associated operation (if any)
+    #[serde(default)]
+    pub operation_type: Option<OperationType>,
     /// Hook execution context
     pub context: HookContext,
     /// Additional event metadata
@@ -85,11 +108,18 @@ impl JJHookEvent {
         Self {
             event_type,
             operation,
+            operation_type: None,
             context,
             metadata: serde_json::Value::Null,
         }
     }
 
+    /// Set the operation type carried by the event
+    pub fn with_operation_type(mut self, operation_type: OperationType) -> Self {
+        self.operation_type = Some(operation_type);
+        self
+    }
+
     /// Add metadata to the event
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = metadata;
@@ -97,6 +127,171 @@ impl JJHookEvent {
     }
 }
 
+/// Handler invoked for `PreOperation` / `PostOperation` events
+///
+/// Returning an error from a pre-operation handler aborts the command.
+/// Pre-operation handlers may also rewrite `event.context.args`.
+pub type OperationHook = Arc<dyn Fn(&mut JJHookEvent) -> Result<()> + Send + Sync>;
+
+/// Asynchronous operation hook, for handlers that need to do I/O
+///
+/// ```ignore
+/// struct Notify;
+///
+/// impl AsyncHook for Notify {
+///     fn handle<'a>(&'a self, ctx: &'a HookContext) -> BoxFuture<'a, Result<()>> {
+///         Box::pin(async move {
+///             send_webhook(&ctx.task_description).await
+///         })
+///     }
+/// }
+/// ```
+pub trait AsyncHook: Send + Sync {
+    /// Handle a hook event; an error from a pre-operation hook vetoes the command
+    fn handle<'a>(&'a self, ctx: &'a HookContext) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Registered operation hooks, run in registration order
+///
+/// Each pre-operation hook sees the argument edits made by hooks registered
+/// before it. Async hooks run after the sync hooks of the same phase and see
+/// the final arguments.
+#[derive(Clone, Default)]
+pub struct OperationHooks {
+    pre: Vec<OperationHook>,
+    post: Vec<OperationHook>,
+    async_pre: Vec<Arc<dyn AsyncHook>>,
+    async_post: Vec<Arc<dyn AsyncHook>>,
+}
+
+impl OperationHooks {
+    /// Create an empty hook registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a pre-operation hook
+    pub fn add_pre(&mut self, hook: OperationHook) {
+        self.pre.push(hook);
+    }
+
+    /// Register a post-operation hook
+    pub fn add_post(&mut self, hook: OperationHook) {
+        self.post.push(hook);
+    }
+
+    /// Register an async pre-operation hook
+    pub fn add_async_pre(&mut self, hook: Arc<dyn AsyncHook>) {
+        self.async_pre.push(hook);
+    }
+
+    /// Register an async post-operation hook
+    pub fn add_async_post(&mut self, hook: Arc<dyn AsyncHook>) {
+        self.async_post.push(hook);
+    }
+
+    /// Run pre-operation hooks, stopping at the first veto
+    pub fn run_pre(&self, event: &mut JJHookEvent) -> Result<()> {
+        for hook in &self.pre {
+            hook(event)?;
+        }
+        Ok(())
+    }
+
+    /// Run post-operation hooks
+    ///
+    /// The command has already run, so failures are reported but not propagated.
+    pub fn run_post(&self, event: &mut JJHookEvent) {
+        for hook in &self.post {
+            if let Err(e) = hook(event) {
+                eprintln!("[jj-hooks] post-operation hook failed: {}", e);
+            }
+        }
+    }
+
+    /// Await async pre-operation hooks, stopping at the first veto
+    pub async fn run_async_pre(&self, ctx: &HookContext) -> Result<()> {
+        for hook in &self.async_pre {
+            hook.handle(ctx).await?;
+        }
+        Ok(())
+    }
+
+    /// Await async post-operation hooks, reporting failures without propagating
+    pub async fn run_async_post(&self, ctx: &HookContext) {
+        for hook in &self.async_post {
+            if let Err(e) = hook.handle(ctx).await {
+                eprintln!("[jj-hooks] async post-operation hook failed: {}", e);
+            }
+        }
+    }
+
+    /// Check if no hooks are registered
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty()
+            && self.post.is_empty()
+            && self.async_pre.is_empty()
+            && self.async_post.is_empty()
+    }
+}
+
+impl std::fmt::Debug for OperationHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OperationHooks")
+            .field("pre", &self.pre.len())
+            .field("post", &self.post.len())
+            .field("async_pre", &self.async_pre.len())
+            .field("async_post", &self.async_post.len())
+            .finish()
+    }
+}
+
+/// Build a pre-operation hook that rejects commit messages not matching `pattern`
+///
+/// Checks the `-m`/`--message` values of `describe`, `commit` and `new`;
+/// multiple messages are joined with a blank line, as jj does. Commands
+/// without a message pass through untouched.
+pub fn commit_message_validator(pattern: &str) -> Result<OperationHook> {
+    let regex = Regex::new(pattern).map_err(|e| {
+        JJError::InvalidConfig(format!("Invalid commit message pattern: {}", e))
+    })?;
+
+    Ok(Arc::new(move |event: &mut JJHookEvent| {
+        let Some(message) = commit_message(&event.context.args) else {
+            return Ok(());
+        };
+        if regex.is_match(&message) {
+            Ok(())
+        } else {
+            Err(JJError::HookVetoed(format!(
+                "commit message {:?} does not match pattern {}",
+                message.lines().next().unwrap_or_default(),
+                regex.as_str()
+            )))
+        }
+    }))
+}
+
+/// Extract the description set by a command, if any
+fn commit_message(args: &[String]) -> Option<String> {
+    match args.first().map(|s| s.as_str()) {
+        Some("describe" | "desc" | "commit" | "ci" | "new") => {}
+        _ => return None,
+    }
+
+    let mut messages = Vec::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-m" || arg == "--message" {
+            messages.extend(iter.next().cloned());
+        } else if let Some(value) = arg.strip_prefix("--message=") {
+            messages.push(value.to_string());
+        }
+    }
+
+    (!messages.is_empty()).then(|| messages.join("\n\n"))
+}
+
 /// Integration layer for agentic-flow hooks
 pub struct JJHooksIntegration {
     /// Underlying JJ wrapper
@@ -293,6 +488,35 @@ impl JJHooksIntegration {
         Ok(())
     }
 
+    /// Register a hook fired before every command run by the wrapper
+    ///
+    /// Returning an error vetoes the command; edits to `event.context.args`
+    /// change the command that runs.
+    pub fn on_pre_operation<F>(&self, hook: F)
+    where
+        F: Fn(&mut JJHookEvent) -> Result<()> + Send + Sync + 'static,
+    {
+        self.wrapper.register_pre_operation_hook(hook);
+    }
+
+    /// Register a hook fired after every command run by the wrapper
+    pub fn on_post_operation<F>(&self, hook: F)
+    where
+        F: Fn(&mut JJHookEvent) -> Result<()> + Send + Sync + 'static,
+    {
+        self.wrapper.register_post_operation_hook(hook);
+    }
+
+    /// Register an async hook awaited before every command run by the wrapper
+    pub fn on_pre_operation_async<H: AsyncHook + 'static>(&self, hook: H) {
+        self.wrapper.register_async_pre_operation_hook(hook);
+    }
+
+    /// Register an async hook awaited after every command run by the wrapper
+    pub fn on_post_operation_async<H: AsyncHook + 'static>(&self, hook: H) {
+        self.wrapper.register_async_post_operation_hook(hook);
+    }
+
     /// Get current session context
     pub fn current_session(&self) -> Option<&HookContext> {
         self.current_session.as_ref()
@@ -401,4 +625,93 @@ mod tests {
         assert_eq!(operation.operation_type, "Describe");
         assert!(operation.command.contains("test.rs"));
     }
+
+    #[tokio::test]
+    async fn test_pre_operation_hook_vetoes_push() {
+        let wrapper = JJWrapper::with_config(JJConfig::default()).unwrap();
+        let integration = JJHooksIntegration::new(wrapper.clone(), false);
+
+        integration.on_pre_operation(|event| {
+            assert_eq!(event.event_type, HookEventType::PreOperation);
+            match event.operation_type {
+                Some(OperationType::GitPush) => Err(crate::JJError::Unknown(
+                    "pushing to main is not allowed".to_string(),
+                )),
+                _ => Ok(()),
+            }
+        });
+
+        let err = wrapper
+            .execute_checked(vec!["git".into(), "push".into(), "-b".into(), "main".into()])
+            .await
+            .unwrap_err();
+
+        match err {
+            crate::JJError::HookVetoed(reason) => {
+                assert!(reason.contains("pushing to main is not allowed"))
+            }
+            other => panic!("expected veto, got {:?}", other),
+        }
+
+        // Vetoed commands never run, so nothing is logged
+        assert_eq!(wrapper.get_operations(10).unwrap().len(), 0);
+    }
+
+    struct SlowPolicy;
+
+    impl AsyncHook for SlowPolicy {
+        fn handle<'a>(&'a self, ctx: &'a HookContext) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                // Simulate a remote policy lookup
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                if ctx.args.first().map(String::as_str) == Some("abandon") {
+                    return Err(crate::JJError::Unknown("abandon is disabled".to_string()));
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_pre_operation_hook_vetoes() {
+        let wrapper = JJWrapper::with_config(JJConfig::default()).unwrap();
+        let integration = JJHooksIntegration::new(wrapper.clone(), false);
+        integration.on_pre_operation_async(SlowPolicy);
+
+        let err = wrapper
+            .execute_checked(vec!["abandon".into(), "@".into()])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, crate::JJError::HookVetoed(ref r) if r.contains("abandon is disabled")));
+        assert_eq!(wrapper.get_operations(10).unwrap().len(), 0);
+    }
+
+    fn describe_event(args: &[&str]) -> JJHookEvent {
+        let context = HookContext::new("agent".to_string(), String::new(), String::new())
+            .with_args(args.iter().map(|s| s.to_string()).collect());
+        JJHookEvent::new(HookEventType::PreOperation, None, context)
+    }
+
+    #[test]
+    fn test_commit_message_validator() {
+        let hook = commit_message_validator(crate::CONVENTIONAL_COMMIT_PATTERN).unwrap();
+
+        assert!(hook(&mut describe_event(&["describe", "-m", "feat(parser): add revsets"])).is_ok());
+        assert!(hook(&mut describe_event(&["new", "--message=fix!: drop old flag"])).is_ok());
+        // Commands that don't set a message are not checked
+        assert!(hook(&mut describe_event(&["describe"])).is_ok());
+        assert!(hook(&mut describe_event(&["abandon", "-m", "whatever"])).is_ok());
+
+        let err = hook(&mut describe_event(&["describe", "-m", "Update stuff"])).unwrap_err();
+        assert!(matches!(err, JJError::HookVetoed(ref msg) if msg.contains("Update stuff")));
+    }
+
+    #[test]
+    fn test_commit_message_validator_rejects_bad_pattern() {
+        assert!(matches!(
+            commit_message_validator("(unclosed"),
+            Err(JJError::InvalidConfig(_))
+        ));
+    }
 }
@@ -31,16 +31,26 @@ pub mod wrapper;
 
 // Re-exports
 pub use agent_coordination::{AgentConflict, AgentCoordination, AgentStats, CoordinationStats};
-pub use agentdb_sync::{AgentDBEpisode, AgentDBSync, TaskStatistics};
-pub use config::JJConfig;
+pub use agentdb_sync::{default_reward, AgentDBEpisode, AgentDBSync, RewardFn, TaskStatistics};
+pub use config::{BranchCommand, ConfigScope, JJConfig, JJConfigBuilder, CONVENTIONAL_COMMIT_PATTERN};
 pub use crypto::{generate_signing_keypair, OperationSignature, SigningKeypair as MLDSAKeypair};
 pub use error::{JJError, Result};
-pub use hooks::{HookContext, HookEventType, JJHookEvent, JJHooksIntegration};
-pub use operations::{JJOperation, JJOperationLog, OperationType};
+pub use hooks::{
+    commit_message_validator, AsyncHook, HookContext, HookEventType, JJHookEvent, JJHooksIntegration, OperationHook,
+    OperationHooks,
+};
+pub use operations::{
+    JJOperation, JJOperationLog, OperationCategory, OperationMetrics, OperationSort, OperationType,
+    TaggingRule, DURATION_BUCKETS_MS, UNDONE_TAG,
+};
 pub use quantum_signing::{CommitSignature, QuantumSigner, SigningKeypair};
 pub use reasoning_bank::{DecisionSuggestion, LearningStats, Pattern, ReasoningBank, Trajectory};
-pub use types::{JJBranch, JJCommit, JJConflict, JJResult};
-pub use wrapper::JJWrapper;
+pub use types::{
+    rank_conflicts, AbandonPolicy, BatchOp, BranchStatus, BranchSyncState, CommitEdge, CommitGraph,
+    ConflictSeverity, DiffHunk, DiffSummary, FileChange, FileDiffStat, JJBranch, JJCommit,
+    JJConflict, JJDiffStat, JJResult, JJVersion, RebaseMode, SignatureStatus,
+};
+pub use wrapper::{JJWrapper, JJWrapperView};
 
 /// Version of the agentic-jujutsu crate
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
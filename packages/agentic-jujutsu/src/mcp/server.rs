@@ -153,7 +153,7 @@ pub fn default_handler(req: MCPRequest) -> Result<MCPResponse> {
             Ok(MCPResponse::success(
                 req.id,
                 serde_json::to_value(capabilities)
-                    .map_err(|e| JJError::SerializationError(e.to_string()))?,
+                    .map_err(JJError::from)?,
             ))
         }
         "capabilities" => {
@@ -161,7 +161,7 @@ pub fn default_handler(req: MCPRequest) -> Result<MCPResponse> {
             Ok(MCPResponse::success(
                 req.id,
                 serde_json::to_value(capabilities)
-                    .map_err(|e| JJError::SerializationError(e.to_string()))?,
+                    .map_err(JJError::from)?,
             ))
         }
         _ => Ok(MCPResponse::error(
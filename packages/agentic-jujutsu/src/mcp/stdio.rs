@@ -198,7 +198,7 @@ impl StdioServer {
 
                     // Send response
                     let json = serde_json::to_string(&response)
-                        .map_err(|e| JJError::SerializationError(e.to_string()))?;
+                        .map_err(JJError::from)?;
                     writeln!(writer, "{}", json)
                         .map_err(|e| JJError::MCPError(format!("Failed to write response: {}", e)))?;
                     writer.flush()
@@ -4,39 +4,163 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use crate::error::{JJError, Result};
+use std::future::Future;
+use std::pin::Pin;
 use std::time::Duration;
 use async_process::{Command, Stdio};
+use futures_lite::io::AsyncWriteExt;
 use tokio::time::timeout;
 
+#[cfg(any(test, feature = "test-utils"))]
+mod fake;
+#[cfg(any(test, feature = "test-utils"))]
+pub use fake::{FakeExecutor, FAKE_JJ_VERSION};
+
+/// Boxed future returned by [`CommandExecutor`]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Runs jj commands on behalf of `JJWrapper`
+///
+/// The default [`NativeExecutor`] spawns the jj binary; tests can substitute an
+/// implementation that records arguments and returns canned output.
+pub trait CommandExecutor: Send + Sync {
+    /// Run `jj_path` with `args` against `repo_path`, returning stdout on success
+    ///
+    /// `repo_path` is `None` for commands that do not operate on an existing
+    /// repository (e.g. `git clone`, `--version`).
+    fn execute<'a>(
+        &'a self,
+        jj_path: &'a str,
+        repo_path: Option<&'a str>,
+        args: &'a [String],
+        command_timeout: Duration,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// Like [`execute`](Self::execute), with `stdin` written to the process
+    ///
+    /// Used for input that can't be passed as an argument, such as a
+    /// multi-line description for `jj describe --stdin`. Executors that can't
+    /// provide input fail with [`JJError::CommandFailed`].
+    fn execute_with_stdin<'a>(
+        &'a self,
+        jj_path: &'a str,
+        repo_path: Option<&'a str>,
+        args: &'a [String],
+        stdin: &'a str,
+        command_timeout: Duration,
+    ) -> BoxFuture<'a, Result<String>> {
+        let _ = (jj_path, repo_path, args, stdin, command_timeout);
+        Box::pin(async {
+            Err(JJError::CommandFailed("This executor cannot pass input on stdin".to_string()))
+        })
+    }
+}
+
+/// Executor that spawns the jj binary as a child process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeExecutor;
+
+impl CommandExecutor for NativeExecutor {
+    /// Runs jj from the repository root (and passes `--repository`), so paths
+    /// in arguments and output are relative to the root regardless of the
+    /// host process's working directory. A missing repository directory is
+    /// reported as [`JJError::NotARepository`].
+    fn execute<'a>(
+        &'a self,
+        jj_path: &'a str,
+        repo_path: Option<&'a str>,
+        args: &'a [String],
+        command_timeout: Duration,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(run_in_repo(jj_path, repo_path, args, None, command_timeout))
+    }
+
+    fn execute_with_stdin<'a>(
+        &'a self,
+        jj_path: &'a str,
+        repo_path: Option<&'a str>,
+        args: &'a [String],
+        stdin: &'a str,
+        command_timeout: Duration,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(run_in_repo(jj_path, repo_path, args, Some(stdin), command_timeout))
+    }
+}
+
+/// Run jj from the root of `repo_path`, passing it as `--repository`
+async fn run_in_repo(
+    jj_path: &str,
+    repo_path: Option<&str>,
+    args: &[String],
+    stdin: Option<&str>,
+    command_timeout: Duration,
+) -> Result<String> {
+    let repo_root = repo_path
+        .map(|path| std::fs::canonicalize(path).map_err(|_| JJError::NotARepository(path.into())))
+        .transpose()?;
+    let repo_root = repo_root.as_ref().map(|root| root.to_string_lossy());
+
+    let mut args_refs: Vec<&str> = Vec::with_capacity(args.len() + 2);
+    if let Some(root) = &repo_root {
+        args_refs.push("--repository");
+        args_refs.push(root);
+    }
+    args_refs.extend(args.iter().map(|s| s.as_str()));
+    run_jj(jj_path, &args_refs, repo_root.as_deref(), stdin, command_timeout).await
+}
+
 /// Execute a jj command natively with timeout support
 pub async fn execute_jj_command(
     jj_path: &str,
     args: &[&str],
     command_timeout: Duration,
+) -> Result<String> {
+    run_jj(jj_path, args, None, None, command_timeout).await
+}
+
+/// Execute a jj command, optionally from `working_dir` and with `stdin` as input
+async fn run_jj(
+    jj_path: &str,
+    args: &[&str],
+    working_dir: Option<&str>,
+    stdin: Option<&str>,
+    command_timeout: Duration,
 ) -> Result<String> {
     // Build the command
     let mut cmd = Command::new(jj_path);
+    if let Some(dir) = working_dir {
+        cmd.current_dir(dir);
+    }
     cmd.args(args)
-        .stdin(Stdio::null())
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
+    let run = async {
+        let mut child = cmd.spawn()?;
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(input.as_bytes()).await?;
+            // Dropping the pipe closes it, so jj sees end of input
+        }
+        child.output().await
+    };
+
     // Execute with timeout
-    let output = timeout(command_timeout, cmd.output())
+    let output = timeout(command_timeout, run)
         .await
         .map_err(|_| JJError::CommandFailed("Command timeout exceeded".to_string()))?
         .map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 JJError::JJNotFound
             } else {
-                JJError::IoError(e.to_string())
+                JJError::from(e)
             }
         })?;
 
     // Check exit status
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        return Err(JJError::CommandFailed(stderr));
+        return Err(JJError::classify(&stderr));
     }
 
     // Return stdout
@@ -87,6 +211,66 @@ mod tests {
         assert_eq!(result.unwrap().trim(), "test");
     }
 
+    #[tokio::test]
+    async fn test_native_executor() {
+        let result = NativeExecutor
+            .execute("echo", None, &["test".to_string()], Duration::from_secs(5))
+            .await;
+
+        assert_eq!(result.unwrap().trim(), "test");
+
+        let repo = std::fs::canonicalize(std::env::temp_dir()).unwrap();
+        let repo = repo.to_str().unwrap();
+        let result = NativeExecutor
+            .execute("echo", Some(repo), &["status".to_string()], Duration::from_secs(5))
+            .await;
+        assert_eq!(result.unwrap().trim(), format!("--repository {} status", repo));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_native_executor_passes_stdin() {
+        let message = "Fix parser\n\n- keep lexer errors\n- add a test\n";
+        let result = NativeExecutor
+            .execute_with_stdin("cat", None, &[], message, Duration::from_secs(5))
+            .await;
+        assert_eq!(result.unwrap(), message);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_native_executor_runs_from_repo_root() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stand-in binary that ignores its arguments and prints its working directory
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("fake-jj");
+        std::fs::write(&script, "#!/bin/sh\npwd\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let repo = std::fs::canonicalize(dir.path()).unwrap();
+
+        let result = NativeExecutor
+            .execute(
+                script.to_str().unwrap(),
+                Some(repo.to_str().unwrap()),
+                &["status".to_string()],
+                Duration::from_secs(5),
+            )
+            .await;
+        assert_eq!(result.unwrap().trim(), repo.to_str().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_native_executor_missing_repo() {
+        let result = NativeExecutor
+            .execute("echo", Some("/nonexistent/repo-xyz"), &[], Duration::from_secs(5))
+            .await;
+        assert_eq!(
+            result.unwrap_err(),
+            JJError::NotARepository("/nonexistent/repo-xyz".into())
+        );
+    }
+
     #[tokio::test]
     async fn test_failed_command() {
         // Test with a command that will fail
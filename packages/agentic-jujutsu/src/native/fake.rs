@@ -0,0 +1,747 @@
+//! In-memory jj repository for tests that should not need the jj binary
+//!
+//! The native counterpart of the WASM simulation: [`FakeExecutor`] implements
+//! [`CommandExecutor`] over a small model of a repository (commits, bookmarks
+//! and the working copy), so wrapper methods run end-to-end, output parsing
+//! included. Only the commands the wrapper's core methods issue are modelled;
+//! anything else fails like an unknown jj subcommand.
+
+use super::{BoxFuture, CommandExecutor};
+use crate::error::{JJError, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Version reported for `jj --version`
+pub const FAKE_JJ_VERSION: &str = "jj 0.35.0";
+
+/// Author recorded on every fake commit
+const AUTHOR: (&str, &str) = ("Test User", "test.user@example.com");
+
+/// Timestamp recorded on every fake commit
+const TIMESTAMP: &str = "2024-01-01T12:00:00+00:00";
+
+/// Flags that take a value, across the modelled commands
+const VALUE_FLAGS: &[&str] = &[
+    "-m", "--message", "-r", "--revision", "--revisions", "-T", "--template", "-n", "--limit",
+    "--config", "--at-operation", "--at-op",
+];
+
+/// A commit in the fake repository
+#[derive(Debug, Clone)]
+struct FakeCommit {
+    commit_id: String,
+    change_id: String,
+    description: String,
+    /// Change IDs of the parents; empty only for the root commit
+    parents: Vec<String>,
+}
+
+/// Repository state behind a [`FakeExecutor`]
+///
+/// Commits are keyed by change ID, so rewrites keep their identity while the
+/// commit ID changes. IDs are derived from a counter, so a given sequence of
+/// commands always produces the same output.
+#[derive(Debug)]
+struct FakeRepo {
+    commits: Vec<FakeCommit>,
+    working_copy: String,
+    bookmarks: BTreeMap<String, String>,
+    generation: u32,
+}
+
+impl FakeRepo {
+    /// A freshly initialised repository: the root plus an empty working copy
+    fn new() -> Self {
+        let root = FakeCommit {
+            commit_id: Self::commit_id_for(0),
+            change_id: Self::change_id_for(0),
+            description: String::new(),
+            parents: Vec::new(),
+        };
+        let mut repo = Self {
+            working_copy: root.change_id.clone(),
+            commits: vec![root],
+            bookmarks: BTreeMap::new(),
+            generation: 0,
+        };
+        let root_change = repo.working_copy.clone();
+        repo.working_copy = repo.create(vec![root_change], String::new());
+        repo
+    }
+
+    /// jj-style change ID (letters k-z) for a generation; 0 is the root's
+    fn change_id_for(generation: u32) -> String {
+        const ALPHABET: &[u8; 16] = b"zyxwvutsrqponmlk";
+        let short: String = (0..8)
+            .rev()
+            .map(|i| ALPHABET[((generation >> (i * 4)) & 0xf) as usize] as char)
+            .collect();
+        short.repeat(4)
+    }
+
+    /// Hex commit ID for a generation; 0 is the root's all-zero ID
+    fn commit_id_for(generation: u32) -> String {
+        format!("{:08x}", generation.wrapping_mul(0x9e37_79b1)).repeat(5)
+    }
+
+    fn next_generation(&mut self) -> u32 {
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Add a commit on top of `parents` and return its change ID
+    fn create(&mut self, parents: Vec<String>, description: String) -> String {
+        let generation = self.next_generation();
+        let change_id = Self::change_id_for(generation);
+        self.commits.push(FakeCommit {
+            commit_id: Self::commit_id_for(generation),
+            change_id: change_id.clone(),
+            description,
+            parents,
+        });
+        change_id
+    }
+
+    fn get(&self, change_id: &str) -> &FakeCommit {
+        self.commits
+            .iter()
+            .find(|c| c.change_id == change_id)
+            .expect("change IDs in the fake repo always resolve")
+    }
+
+    fn get_mut(&mut self, change_id: &str) -> &mut FakeCommit {
+        self.commits
+            .iter_mut()
+            .find(|c| c.change_id == change_id)
+            .expect("change IDs in the fake repo always resolve")
+    }
+
+    fn is_root(&self, change_id: &str) -> bool {
+        self.get(change_id).parents.is_empty()
+    }
+
+    /// Give a commit a new commit ID, along with all its descendants
+    ///
+    /// Mirrors jj rebasing descendants whenever a commit is rewritten.
+    fn rewrite(&mut self, change_id: &str) {
+        let generation = self.next_generation();
+        self.get_mut(change_id).commit_id = Self::commit_id_for(generation);
+        let children: Vec<String> = self
+            .commits
+            .iter()
+            .filter(|c| c.parents.iter().any(|p| p == change_id))
+            .map(|c| c.change_id.clone())
+            .collect();
+        for child in children {
+            self.rewrite(&child);
+        }
+    }
+
+    /// Resolve a revset to change IDs, newest first
+    ///
+    /// Supports `@`, `root()`, `all()`, bookmark names, commit or change ID
+    /// prefixes, and any of those followed by `-` (parents).
+    fn resolve(&self, revset: &str) -> Result<Vec<String>> {
+        let revset = revset.trim();
+        if let Some(base) = revset.strip_suffix('-') {
+            let mut parents: Vec<String> = Vec::new();
+            for change_id in self.resolve(base)? {
+                for parent in &self.get(&change_id).parents {
+                    if !parents.contains(parent) {
+                        parents.push(parent.clone());
+                    }
+                }
+            }
+            return Ok(parents);
+        }
+
+        match revset {
+            "@" => Ok(vec![self.working_copy.clone()]),
+            "root()" => Ok(vec![self.commits[0].change_id.clone()]),
+            "all()" => Ok(self.commits.iter().rev().map(|c| c.change_id.clone()).collect()),
+            _ => {
+                if let Some(change_id) = self.bookmarks.get(revset) {
+                    return Ok(vec![change_id.clone()]);
+                }
+                let matches: Vec<&FakeCommit> = self
+                    .commits
+                    .iter()
+                    .filter(|c| {
+                        !revset.is_empty()
+                            && (c.commit_id.starts_with(revset) || c.change_id.starts_with(revset))
+                    })
+                    .collect();
+                match matches.as_slice() {
+                    [commit] => Ok(vec![commit.change_id.clone()]),
+                    [] => Err(JJError::classify(&format!(
+                        "Error: Revision `{}` doesn't exist",
+                        revset
+                    ))),
+                    _ => Err(JJError::classify(&format!(
+                        "Error: Commit ID prefix `{}` is ambiguous",
+                        revset
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Resolve a revset that must name exactly one commit
+    fn resolve_single(&self, revset: &str) -> Result<String> {
+        let mut change_ids = self.resolve(revset)?;
+        match change_ids.len() {
+            1 => Ok(change_ids.remove(0)),
+            0 => Err(JJError::classify(&format!("Error: Revset `{}` didn't resolve to any revisions", revset))),
+            _ => Err(JJError::classify(&format!("Error: Revset `{}` resolved to more than one revision", revset))),
+        }
+    }
+
+    fn run(&mut self, args: &[String], stdin: Option<&str>) -> Result<String> {
+        let Some((command, rest)) = args.split_first() else {
+            return Err(unsupported("no subcommand given"));
+        };
+        let args = ParsedArgs::parse(rest);
+        match command.as_str() {
+            "--version" | "version" => Ok(format!("{}\n", FAKE_JJ_VERSION)),
+            "status" | "st" => Ok(self.status()),
+            "log" => self.log(&args),
+            "new" => self.new_change(&args),
+            "describe" | "desc" => self.describe(&args, stdin),
+            "split" => self.split(&args),
+            "edit" => self.edit(&args),
+            "abandon" => self.abandon(&args),
+            "bookmark" | "branch" => self.bookmark(&args),
+            other => Err(unsupported(&format!("unrecognized subcommand '{}'", other))),
+        }
+    }
+
+    fn summary_line(&self, change_id: &str) -> String {
+        let commit = self.get(change_id);
+        let description = commit.description.lines().next().unwrap_or("");
+        format!(
+            "{} {} {}",
+            &commit.change_id[..8],
+            &commit.commit_id[..8],
+            if description.is_empty() { "(no description set)" } else { description }
+        )
+    }
+
+    fn status(&self) -> String {
+        let parents = &self.get(&self.working_copy).parents;
+        let mut out = format!(
+            "The working copy has no changes.\nWorking copy  (@) : {}\n",
+            self.summary_line(&self.working_copy)
+        );
+        for parent in parents {
+            out.push_str(&format!("Parent commit (@-): {}\n", self.summary_line(parent)));
+        }
+        out
+    }
+
+    /// `jj log`, as JSON lines for the wrapper's commit templates
+    ///
+    /// Without `-T` commits are rendered as the `Commit ID:` / `Change ID:` /
+    /// `Author:` blocks read by the N-API `log` method.
+    fn log(&self, args: &ParsedArgs) -> Result<String> {
+        let mut change_ids = self.resolve(args.value(&["-r", "--revisions"]).unwrap_or("all()"))?;
+        if let Some(limit) = args.value(&["-n", "--limit"]) {
+            let limit: usize = limit
+                .parse()
+                .map_err(|_| unsupported(&format!("invalid value '{}' for '--limit'", limit)))?;
+            change_ids.truncate(limit);
+        }
+
+        match args.value(&["-T", "--template"]) {
+            Some(template) if template.contains(r#""commit_id""#) => Ok(change_ids
+                .iter()
+                .map(|change_id| self.commit_json(change_id, template) + "\n")
+                .collect()),
+            Some(template) => Err(unsupported(&format!("unsupported template: {}", template))),
+            None => Ok(change_ids
+                .iter()
+                .map(|change_id| {
+                    let commit = self.get(change_id);
+                    format!(
+                        "Commit ID: {}\nChange ID: {}\nAuthor: {} <{}>\n",
+                        commit.commit_id, commit.change_id, AUTHOR.0, AUTHOR.1
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+
+    /// Render one commit as the JSON commit templates would
+    fn commit_json(&self, change_id: &str, template: &str) -> String {
+        let commit = self.get(change_id);
+        let mut json = serde_json::json!({
+            "commit_id": commit.commit_id,
+            "change_id": commit.change_id,
+            "description": commit.description,
+            "author": if commit.parents.is_empty() { "" } else { AUTHOR.0 },
+            "author_email": if commit.parents.is_empty() { "" } else { AUTHOR.1 },
+            "timestamp": TIMESTAMP,
+            "empty": true,
+            "conflict": false,
+            "working_copy": change_id == self.working_copy,
+        });
+        if template.contains(r#""signature""#) {
+            json["signature"] = "none".into();
+        }
+        if template.contains(r#""hidden""#) {
+            json["hidden"] = false.into();
+        }
+        json.to_string()
+    }
+
+    fn new_change(&mut self, args: &ParsedArgs) -> Result<String> {
+        args.reject_switches(&[])?;
+        let mut parents = Vec::new();
+        for revset in args.positional_or("@") {
+            for change_id in self.resolve(revset)? {
+                if !parents.contains(&change_id) {
+                    parents.push(change_id);
+                }
+            }
+        }
+        let description = args.value(&["-m", "--message"]).map(jj_description).unwrap_or_default();
+        self.working_copy = self.create(parents, description);
+        Ok(String::new())
+    }
+
+    fn describe(&mut self, args: &ParsedArgs, stdin: Option<&str>) -> Result<String> {
+        args.reject_switches(&["--stdin"])?;
+        let from_stdin = args.switches.iter().any(|s| s == "--stdin");
+        let message = match from_stdin {
+            true => Some(stdin.unwrap_or_default()),
+            false => args.value(&["-m", "--message"]),
+        };
+        let Some(message) = message else {
+            return match args.editor()?.as_deref() {
+                Some("false") => Err(editor_failed("false")),
+                _ => Err(unsupported("describe needs -m; the fake repo has no editor")),
+            };
+        };
+        let mut revsets = args.positional.clone();
+        revsets.extend(args.value(&["-r"]).map(str::to_string));
+        if revsets.is_empty() {
+            revsets.push("@".to_string());
+        }
+
+        let mut change_ids = Vec::new();
+        for revset in &revsets {
+            change_ids.extend(self.resolve(revset)?);
+        }
+        for change_id in change_ids {
+            if self.is_root(&change_id) {
+                return Err(unsupported("The root commit 000000000000 is immutable"));
+            }
+            self.get_mut(&change_id).description = jj_description(message);
+            self.rewrite(&change_id);
+        }
+        Ok(String::new())
+    }
+
+    /// Split a revision in two; the fake has no files, so paths are only required
+    ///
+    /// The first part is a new commit on the revision's parents, and the
+    /// revision itself, keeping its change ID and description, is rebased
+    /// onto it. The first part's description comes from `-m`, or else from
+    /// the editor, which jj pre-fills with the revision's description.
+    fn split(&mut self, args: &ParsedArgs) -> Result<String> {
+        args.reject_switches(&[])?;
+        if args.positional.is_empty() {
+            return Err(unsupported("split needs paths; the fake repo has no diff editor"));
+        }
+        let change_id = self.resolve_single(args.value(&["-r", "--revision"]).unwrap_or("@"))?;
+        if self.is_root(&change_id) {
+            return Err(unsupported("The root commit 000000000000 is immutable"));
+        }
+
+        let description = match args.value(&["-m", "--message"]) {
+            Some(message) => jj_description(message),
+            None => match args.editor()?.as_deref() {
+                // Exits without touching the pre-filled description
+                Some("true" | "cmd /c rem") => self.get(&change_id).description.clone(),
+                Some(command) => return Err(editor_failed(command)),
+                None => return Err(unsupported("split needs -m; the fake repo has no editor")),
+            },
+        };
+        let parents = self.get(&change_id).parents.clone();
+        let first = self.create(parents, description);
+        self.get_mut(&change_id).parents = vec![first];
+        self.rewrite(&change_id);
+        Ok(String::new())
+    }
+
+    fn edit(&mut self, args: &ParsedArgs) -> Result<String> {
+        args.reject_switches(&[])?;
+        let [revset] = args.positional.as_slice() else {
+            return Err(unsupported("edit takes exactly one revision"));
+        };
+        let change_id = self.resolve_single(revset)?;
+        if self.is_root(&change_id) {
+            return Err(unsupported("The root commit 000000000000 is immutable"));
+        }
+        self.working_copy = change_id;
+        Ok(String::new())
+    }
+
+    /// Abandon commits, rebasing their children onto their parents
+    ///
+    /// Abandoning the working copy starts a new empty one on its parent.
+    fn abandon(&mut self, args: &ParsedArgs) -> Result<String> {
+        args.reject_switches(&["--restore-descendants"])?;
+        // Revsets may overlap; each commit is abandoned once
+        let mut change_ids: Vec<String> = Vec::new();
+        for revset in args.positional_or("@") {
+            for change_id in self.resolve(revset)? {
+                if !change_ids.contains(&change_id) {
+                    change_ids.push(change_id);
+                }
+            }
+        }
+        if change_ids.iter().any(|change_id| self.is_root(change_id)) {
+            return Err(unsupported("The root commit 000000000000 is immutable"));
+        }
+
+        for change_id in change_ids {
+            let parents = self.get(&change_id).parents.clone();
+            self.commits.retain(|c| c.change_id != change_id);
+
+            let children: Vec<String> = self
+                .commits
+                .iter()
+                .filter(|c| c.parents.contains(&change_id))
+                .map(|c| c.change_id.clone())
+                .collect();
+            for child in children {
+                let commit = self.get_mut(&child);
+                commit.parents.retain(|p| *p != change_id);
+                for parent in &parents {
+                    if !commit.parents.contains(parent) {
+                        commit.parents.push(parent.clone());
+                    }
+                }
+                self.rewrite(&child);
+            }
+
+            self.bookmarks.retain(|_, target| *target != change_id);
+            if self.working_copy == change_id {
+                self.working_copy = self.create(parents, String::new());
+            }
+        }
+        Ok(String::new())
+    }
+
+    fn bookmark(&mut self, args: &ParsedArgs) -> Result<String> {
+        let Some((action, names)) = args.positional.split_first() else {
+            return Err(unsupported("bookmark needs a subcommand"));
+        };
+        match action.as_str() {
+            "list" | "l" => {
+                args.reject_switches(&["--all", "-a", "--all-remotes"])?;
+                Ok(self
+                    .bookmarks
+                    .iter()
+                    .map(|(name, change_id)| format!("{}: {}\n", name, self.summary_line(change_id)))
+                    .collect())
+            }
+            "create" | "c" | "set" | "s" => {
+                args.reject_switches(&["--allow-backwards", "-B"])?;
+                if names.is_empty() {
+                    return Err(unsupported("bookmark needs at least one name"));
+                }
+                let change_id = self.resolve_single(args.value(&["-r", "--revision"]).unwrap_or("@"))?;
+                for name in names {
+                    if action.starts_with('c') && self.bookmarks.contains_key(name) {
+                        return Err(unsupported(&format!(
+                            "Bookmark already exists: {}\nHint: Use `jj bookmark set` to update it.",
+                            name
+                        )));
+                    }
+                    self.bookmarks.insert(name.clone(), change_id.clone());
+                }
+                Ok(String::new())
+            }
+            "delete" | "d" => {
+                args.reject_switches(&[])?;
+                for name in names {
+                    if self.bookmarks.remove(name).is_none() {
+                        return Err(unsupported(&format!("No such bookmark: {}", name)));
+                    }
+                }
+                Ok(String::new())
+            }
+            other => Err(unsupported(&format!("unrecognized subcommand '{}'", other))),
+        }
+    }
+}
+
+/// Arguments after the subcommand, split into flags and positionals
+#[derive(Debug, Default)]
+struct ParsedArgs {
+    positional: Vec<String>,
+    values: HashMap<String, String>,
+    switches: Vec<String>,
+    /// Every `--config` setting, in order
+    config: Vec<String>,
+}
+
+impl ParsedArgs {
+    fn parse(args: &[String]) -> Self {
+        let mut parsed = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if VALUE_FLAGS.contains(&arg.as_str()) {
+                if let Some(value) = iter.next() {
+                    if arg == "--config" {
+                        parsed.config.push(value.clone());
+                    }
+                    parsed.values.insert(arg.clone(), value.clone());
+                }
+            } else if arg.starts_with('-') && arg != "-" {
+                parsed.switches.push(arg.clone());
+            } else {
+                parsed.positional.push(arg.clone());
+            }
+        }
+        parsed
+    }
+
+    /// Value of the first of `flags` that was given
+    fn value(&self, flags: &[&str]) -> Option<&str> {
+        flags.iter().find_map(|flag| self.values.get(*flag)).map(String::as_str)
+    }
+
+    /// Positional arguments, or `default` if there are none
+    fn positional_or<'a>(&'a self, default: &'a str) -> Vec<&'a str> {
+        if self.positional.is_empty() {
+            vec![default]
+        } else {
+            self.positional.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Editor command set with `--config ui.editor=...`, the last one winning
+    ///
+    /// Values are TOML as in jj: a quoted value is a command, while a bare
+    /// `true` or `false` is a boolean and fails as a config type error.
+    fn editor(&self) -> Result<Option<String>> {
+        let mut editor = None;
+        for value in self.config.iter().filter_map(|setting| setting.strip_prefix("ui.editor=")) {
+            if let Some(command) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                editor = Some(command.to_string());
+            } else if matches!(value, "true" | "false") {
+                return Err(JJError::classify(&format!(
+                    "Config error: Invalid type or value for ui.editor\n\
+                     Caused by: invalid type: boolean `{}`, expected a string or array",
+                    value
+                )));
+            } else {
+                editor = Some(value.to_string());
+            }
+        }
+        Ok(editor)
+    }
+
+    /// Fail on switches other than `allowed` and the log-only `--no-graph`
+    fn reject_switches(&self, allowed: &[&str]) -> Result<()> {
+        match self
+            .switches
+            .iter()
+            .find(|s| *s != "--no-graph" && !allowed.contains(&s.as_str()))
+        {
+            Some(switch) => Err(unsupported(&format!("unexpected argument '{}' found", switch))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// jj stores non-empty descriptions with a trailing newline
+fn jj_description(message: &str) -> String {
+    match message.trim_end() {
+        "" => String::new(),
+        text => format!("{}\n", text),
+    }
+}
+
+/// Error jj reports when the description editor exits unsuccessfully
+fn editor_failed(command: &str) -> JJError {
+    JJError::classify(&format!(
+        "Error: Failed to edit description\nCaused by: Editor '{}' exited with exit status: 1",
+        command
+    ))
+}
+
+/// Error for input the fake repository does not model
+fn unsupported(message: &str) -> JJError {
+    JJError::classify(&format!("Error: {}", message))
+}
+
+/// [`CommandExecutor`] backed by an in-memory jj repository
+///
+/// Starts as a freshly initialised repository: the root commit plus an empty,
+/// undescribed working-copy commit. Supports `status`, `log` (with the
+/// wrapper's JSON commit templates), `new`, `describe`, `split`, `edit`,
+/// `abandon`, `bookmark`/`branch` (`create`, `set`, `delete`, `list`) and
+/// `--version`. `--config` and `--at-operation` are accepted; of the config,
+/// only `ui.editor` is modelled, for commands that would open an editor.
+///
+/// ```ignore
+/// let wrapper = JJWrapper::with_config_checked(JJConfig::default())?
+///     .with_executor(Arc::new(FakeExecutor::new()));
+/// ```
+#[derive(Debug)]
+pub struct FakeExecutor {
+    repo: Mutex<FakeRepo>,
+}
+
+impl FakeExecutor {
+    /// Create an executor over a freshly initialised repository
+    pub fn new() -> Self {
+        Self {
+            repo: Mutex::new(FakeRepo::new()),
+        }
+    }
+}
+
+impl Default for FakeExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandExecutor for FakeExecutor {
+    fn execute<'a>(
+        &'a self,
+        _jj_path: &'a str,
+        _repo_path: Option<&'a str>,
+        args: &'a [String],
+        _command_timeout: Duration,
+    ) -> BoxFuture<'a, Result<String>> {
+        let result = self.repo.lock().unwrap().run(args, None);
+        Box::pin(async move { result })
+    }
+
+    fn execute_with_stdin<'a>(
+        &'a self,
+        _jj_path: &'a str,
+        _repo_path: Option<&'a str>,
+        args: &'a [String],
+        stdin: &'a str,
+        _command_timeout: Duration,
+    ) -> BoxFuture<'a, Result<String>> {
+        let result = self.repo.lock().unwrap().run(args, Some(stdin));
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run(executor: &FakeExecutor, args: &[&str]) -> Result<String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        executor.execute("jj", None, &args, Duration::from_secs(1)).await
+    }
+
+    #[test]
+    fn test_ids() {
+        assert_eq!(FakeRepo::change_id_for(0), "z".repeat(32));
+        assert_eq!(FakeRepo::commit_id_for(0), "0".repeat(40));
+        assert_ne!(FakeRepo::change_id_for(1), FakeRepo::change_id_for(2));
+        assert_ne!(FakeRepo::commit_id_for(1), FakeRepo::commit_id_for(2));
+    }
+
+    #[tokio::test]
+    async fn test_revsets() {
+        let executor = FakeExecutor::new();
+        run(&executor, &["new", "-m", "second"]).await.unwrap();
+        run(&executor, &["bookmark", "create", "main", "-r", "@-"]).await.unwrap();
+
+        let repo = executor.repo.lock().unwrap();
+        let first = repo.resolve_single("@-").unwrap();
+        assert_eq!(repo.resolve("main").unwrap(), vec![first.clone()]);
+        assert_eq!(repo.resolve(&first[..8]).unwrap(), vec![first.clone()]);
+        assert_eq!(repo.resolve("@--").unwrap(), repo.resolve("root()").unwrap());
+        assert_eq!(repo.resolve("all()").unwrap().len(), 3);
+        assert!(matches!(repo.resolve("nope"), Err(JJError::RevisionNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_describe_rewrites_descendants() {
+        let executor = FakeExecutor::new();
+        run(&executor, &["new"]).await.unwrap();
+        let commit_ids = |executor: &FakeExecutor| {
+            let repo = executor.repo.lock().unwrap();
+            ["@-", "@"].map(|revset| repo.get(&repo.resolve_single(revset).unwrap()).commit_id.clone())
+        };
+        let before = commit_ids(&executor);
+
+        run(&executor, &["describe", "@-", "-m", "first"]).await.unwrap();
+
+        let after = commit_ids(&executor);
+        assert_ne!(before[0], after[0]);
+        assert_ne!(before[1], after[1]);
+    }
+
+    #[tokio::test]
+    async fn test_abandon_working_copy() {
+        let executor = FakeExecutor::new();
+        run(&executor, &["describe", "-m", "scratch"]).await.unwrap();
+        run(&executor, &["bookmark", "create", "scratch"]).await.unwrap();
+
+        run(&executor, &["abandon"]).await.unwrap();
+
+        let repo = executor.repo.lock().unwrap();
+        assert_eq!(repo.commits.len(), 2);
+        assert_eq!(repo.get(&repo.working_copy).description, "");
+        assert!(repo.bookmarks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abandon_overlapping_revsets() {
+        let executor = FakeExecutor::new();
+        run(&executor, &["describe", "-m", "scratch"]).await.unwrap();
+        run(&executor, &["bookmark", "create", "scratch"]).await.unwrap();
+
+        run(&executor, &["abandon", "@", "scratch", "@"]).await.unwrap();
+
+        let repo = executor.repo.lock().unwrap();
+        assert_eq!(repo.commits.len(), 2);
+        assert_eq!(repo.get(&repo.working_copy).description, "");
+    }
+
+    #[tokio::test]
+    async fn test_abandon_root_changes_nothing() {
+        let executor = FakeExecutor::new();
+        run(&executor, &["describe", "-m", "kept"]).await.unwrap();
+
+        assert!(run(&executor, &["abandon", "@", "root()"]).await.is_err());
+
+        let repo = executor.repo.lock().unwrap();
+        assert_eq!(repo.get(&repo.working_copy).description, "kept\n");
+    }
+
+    #[tokio::test]
+    async fn test_bookmark_list() {
+        let executor = FakeExecutor::new();
+        run(&executor, &["describe", "-m", "feature work"]).await.unwrap();
+        run(&executor, &["bookmark", "create", "feature"]).await.unwrap();
+
+        let output = run(&executor, &["bookmark", "list"]).await.unwrap();
+        assert!(output.starts_with("feature: "));
+        assert!(output.trim_end().ends_with(" feature work"));
+        let err = run(&executor, &["bookmark", "create", "feature"]).await.unwrap_err();
+        assert!(matches!(err, JJError::CommandFailed(msg) if msg.contains("already exists")));
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_command() {
+        let executor = FakeExecutor::new();
+        let err = run(&executor, &["rebase", "-s", "@", "-d", "root()"]).await.unwrap_err();
+        assert!(matches!(err, JJError::CommandFailed(msg) if msg.contains("rebase")));
+    }
+}
@@ -24,8 +24,12 @@
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, RwLock};
 use uuid::Uuid;
 use napi_derive::napi;
 
@@ -54,6 +58,16 @@ pub enum OperationType {
     Rebase,
     /// Squash commits
     Squash,
+    /// Absorb working-copy changes into ancestors
+    Absorb,
+    /// Workspace management
+    Workspace,
+    /// File-level command (chmod, track, untrack)
+    File,
+    /// Sign or unsign commits
+    Sign,
+    /// Run configured formatters over commits
+    Fix,
     /// Resolve conflicts
     Resolve,
     /// Branch operation
@@ -119,6 +133,11 @@ impl OperationType {
             OperationType::Abandon => "Abandon".to_string(),
             OperationType::Rebase => "Rebase".to_string(),
             OperationType::Squash => "Squash".to_string(),
+            OperationType::Absorb => "Absorb".to_string(),
+            OperationType::Workspace => "Workspace".to_string(),
+            OperationType::File => "File".to_string(),
+            OperationType::Sign => "Sign".to_string(),
+            OperationType::Fix => "Fix".to_string(),
             OperationType::Resolve => "Resolve".to_string(),
             OperationType::Branch => "Branch".to_string(),
             OperationType::BranchDelete => "BranchDelete".to_string(),
@@ -147,6 +166,55 @@ impl OperationType {
         }
     }
 
+    /// jj-style kebab-case name (`git-fetch`, `branch-delete`)
+    ///
+    /// This is the canonical form accepted by [`from_string`](Self::from_string).
+    /// [`as_string`](Self::as_string) keeps returning the variant name for
+    /// existing consumers.
+    #[inline]
+    pub fn as_kebab(&self) -> &'static str {
+        match self {
+            OperationType::Commit => "commit",
+            OperationType::Snapshot => "snapshot",
+            OperationType::Describe => "describe",
+            OperationType::New => "new",
+            OperationType::Edit => "edit",
+            OperationType::Abandon => "abandon",
+            OperationType::Rebase => "rebase",
+            OperationType::Squash => "squash",
+            OperationType::Absorb => "absorb",
+            OperationType::Workspace => "workspace",
+            OperationType::File => "file",
+            OperationType::Sign => "sign",
+            OperationType::Fix => "fix",
+            OperationType::Resolve => "resolve",
+            OperationType::Branch => "branch",
+            OperationType::BranchDelete => "branch-delete",
+            OperationType::Bookmark => "bookmark",
+            OperationType::Tag => "tag",
+            OperationType::Checkout => "checkout",
+            OperationType::Restore => "restore",
+            OperationType::Split => "split",
+            OperationType::Duplicate => "duplicate",
+            OperationType::Undo => "undo",
+            OperationType::Fetch => "fetch",
+            OperationType::GitFetch => "git-fetch",
+            OperationType::Push => "push",
+            OperationType::GitPush => "git-push",
+            OperationType::Clone => "clone",
+            OperationType::Init => "init",
+            OperationType::GitImport => "git-import",
+            OperationType::GitExport => "git-export",
+            OperationType::Move => "move",
+            OperationType::Diffedit => "diffedit",
+            OperationType::Merge => "merge",
+            OperationType::Status => "status",
+            OperationType::Log => "log",
+            OperationType::Diff => "diff",
+            OperationType::Unknown => "unknown",
+        }
+    }
+
     /// Check if operation modifies history
     #[inline]
     pub fn modifies_history(&self) -> bool {
@@ -158,6 +226,9 @@ impl OperationType {
                 | OperationType::Abandon
                 | OperationType::Rebase
                 | OperationType::Squash
+                | OperationType::Absorb
+                | OperationType::Sign
+                | OperationType::Fix
                 | OperationType::Split
                 | OperationType::Move
                 | OperationType::Merge
@@ -184,9 +255,131 @@ impl OperationType {
     pub fn is_automatic(&self) -> bool {
         matches!(self, OperationType::Snapshot)
     }
+
+    /// Classify the operation into a category
+    ///
+    /// `History` is broader than [`modifies_history`](Self::modifies_history): it
+    /// also covers operations that change commit contents or the working-copy
+    /// commit, such as `New`, `Resolve`, `Restore` and `Checkout`.
+    pub fn category(&self) -> OperationCategory {
+        match self {
+            OperationType::Commit
+            | OperationType::Describe
+            | OperationType::New
+            | OperationType::Edit
+            | OperationType::Abandon
+            | OperationType::Rebase
+            | OperationType::Squash
+            | OperationType::Absorb
+            | OperationType::Sign
+            | OperationType::Fix
+            | OperationType::Resolve
+            | OperationType::Checkout
+            | OperationType::Restore
+            | OperationType::Split
+            | OperationType::Duplicate
+            | OperationType::Move
+            | OperationType::Diffedit
+            | OperationType::Merge => OperationCategory::History,
+            OperationType::Fetch
+            | OperationType::GitFetch
+            | OperationType::Push
+            | OperationType::GitPush
+            | OperationType::Clone
+            | OperationType::GitImport
+            | OperationType::GitExport => OperationCategory::Remote,
+            OperationType::Snapshot => OperationCategory::Automatic,
+            OperationType::Status | OperationType::Log | OperationType::Diff => {
+                OperationCategory::Read
+            }
+            OperationType::Branch
+            | OperationType::BranchDelete
+            | OperationType::Bookmark
+            | OperationType::Tag => OperationCategory::Branching,
+            OperationType::Undo
+            | OperationType::Init
+            | OperationType::Workspace
+            | OperationType::File
+            | OperationType::Unknown => OperationCategory::Other,
+        }
+    }
+}
+
+/// Coarse grouping of operation types
+///
+/// Used by dashboards to group operations without combining several boolean
+/// checks. See [`OperationType::category`].
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[napi]
+pub enum OperationCategory {
+    /// Operations that create or rewrite commits
+    History,
+    /// Operations that interact with remotes
+    Remote,
+    /// Automatic operations (snapshots)
+    Automatic,
+    /// Read-only queries
+    Read,
+    /// Branch, bookmark and tag management
+    Branching,
+    /// Everything else
+    Other,
+}
+
+/// Sort key for [`JJOperationLog::sorted_by`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationSort {
+    /// When the operation ran
+    ByTimestamp,
+    /// How long the operation took
+    ByDuration,
+    /// Operation type name
+    ByType,
 }
 
 impl OperationType {
+    /// Infer the type from a `jj op log` description
+    ///
+    /// jj describes operations in prose, e.g. "rebase commit 3f2a...",
+    /// "snapshot working copy" or "push all tracked bookmarks to git remote
+    /// origin". Unrecognised descriptions map to `Unknown`.
+    pub fn from_description(description: &str) -> OperationType {
+        let description = description.trim().to_lowercase();
+        let first_word = description.split_whitespace().next().unwrap_or_default();
+        match first_word {
+            "snapshot" => OperationType::Snapshot,
+            "describe" => OperationType::Describe,
+            "new" => OperationType::New,
+            "commit" => OperationType::Commit,
+            "edit" => OperationType::Edit,
+            "abandon" => OperationType::Abandon,
+            "rebase" => OperationType::Rebase,
+            "squash" => OperationType::Squash,
+            "absorb" => OperationType::Absorb,
+            "split" => OperationType::Split,
+            "duplicate" => OperationType::Duplicate,
+            "restore" => OperationType::Restore,
+            "resolve" => OperationType::Resolve,
+            "undo" => OperationType::Undo,
+            "fetch" => OperationType::GitFetch,
+            "push" => OperationType::GitPush,
+            "import" if description.contains("git") => OperationType::GitImport,
+            "export" if description.contains("git") => OperationType::GitExport,
+            "sign" | "unsign" => OperationType::Sign,
+            "fix" | "fixed" => OperationType::Fix,
+            "initialize" | "add" if description.contains("repo") => OperationType::Init,
+            "add" | "forget" | "rename" if description.contains("workspace") => {
+                OperationType::Workspace
+            }
+            "delete" if description.contains("bookmark") || description.contains("branch") => {
+                OperationType::BranchDelete
+            }
+            _ if description.contains("bookmark") => OperationType::Bookmark,
+            _ if description.contains("branch") => OperationType::Branch,
+            _ => OperationType::Unknown,
+        }
+    }
+
     /// Parse from string
     pub fn from_string(s: &str) -> OperationType {
         match s.to_lowercase().as_str() {
@@ -198,9 +391,14 @@ impl OperationType {
             "abandon" => OperationType::Abandon,
             "rebase" => OperationType::Rebase,
             "squash" => OperationType::Squash,
+            "absorb" => OperationType::Absorb,
+            "workspace" => OperationType::Workspace,
+            "file" => OperationType::File,
+            "sign" => OperationType::Sign,
+            "fix" => OperationType::Fix,
             "resolve" => OperationType::Resolve,
             "branch" => OperationType::Branch,
-            "branch-delete" => OperationType::BranchDelete,
+            "branch-delete" | "branchdelete" => OperationType::BranchDelete,
             "bookmark" => OperationType::Bookmark,
             "tag" => OperationType::Tag,
             "checkout" => OperationType::Checkout,
@@ -209,13 +407,13 @@ impl OperationType {
             "duplicate" => OperationType::Duplicate,
             "undo" => OperationType::Undo,
             "fetch" => OperationType::Fetch,
-            "git-fetch" => OperationType::GitFetch,
+            "git-fetch" | "gitfetch" => OperationType::GitFetch,
             "push" => OperationType::Push,
-            "git-push" => OperationType::GitPush,
+            "git-push" | "gitpush" => OperationType::GitPush,
             "clone" => OperationType::Clone,
             "init" => OperationType::Init,
-            "git-import" => OperationType::GitImport,
-            "git-export" => OperationType::GitExport,
+            "git-import" | "gitimport" => OperationType::GitImport,
+            "git-export" | "gitexport" => OperationType::GitExport,
             "move" => OperationType::Move,
             "diffedit" => OperationType::Diffedit,
             "merge" => OperationType::Merge,
@@ -231,6 +429,12 @@ impl OperationType {
 ///
 /// Represents a single operation in the jujutsu operation log with metadata.
 ///
+/// Equality and hashing use `operation_id` alone, so clones and edited copies
+/// of an operation compare equal and a `HashSet` deduplicates them. The
+/// wrapper-generated `id` is ignored, as it is a fresh UUID per construction.
+/// Operations the wrapper records itself get unique synthetic IDs, so two
+/// commands never compare equal just because they ran close together.
+///
 /// # Examples
 ///
 /// ```rust
@@ -342,6 +546,30 @@ impl JJOperation {
         self.timestamp.clone()
     }
 
+    /// Time since this operation started
+    ///
+    /// Saturates to zero when the timestamp is in the future (clock skew
+    /// between agents) or cannot be parsed.
+    pub fn age(&self) -> Duration {
+        self.parsed_timestamp()
+            .map(|ts| (Utc::now() - ts).max(Duration::zero()))
+            .unwrap_or_else(Duration::zero)
+    }
+
+    /// Time between `other` and this operation, zero if `other` is later
+    pub fn elapsed_since(&self, other: &JJOperation) -> Duration {
+        match (self.parsed_timestamp(), other.parsed_timestamp()) {
+            (Some(this), Some(other)) => (this - other).max(Duration::zero()),
+            _ => Duration::zero(),
+        }
+    }
+
+    fn parsed_timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|ts| ts.with_timezone(&Utc))
+    }
+
     /// Short operation ID (first 12 characters)
     #[inline]
     pub fn short_id(&self) -> String {
@@ -384,6 +612,44 @@ impl JJOperation {
         &self.tags
     }
 
+    /// Whether `operation_id` is an ID assigned by jj (hex), not a synthetic one
+    ///
+    /// Operations recorded by the wrapper itself use `<nanos>-<uuid>@<host>` IDs.
+    pub fn has_jj_operation_id(&self) -> bool {
+        !self.operation_id.is_empty() && self.operation_id.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Approximate size of this operation serialized as JSON, in bytes
+    ///
+    /// Counts the string contents plus a fixed allowance for field names,
+    /// numbers and punctuation; cheap enough to compute on every insert.
+    pub fn estimated_size(&self) -> usize {
+        const FIXED_OVERHEAD: usize = 320;
+        let optional = [
+            &self.parent_id,
+            &self.error,
+            &self.quantum_fingerprint,
+            &self.signature,
+            &self.signature_public_key,
+        ];
+        FIXED_OVERHEAD
+            + self.id.len()
+            + self.operation_id.len()
+            + self.operation_type.len()
+            + self.command.len()
+            + self.user.len()
+            + self.hostname.len()
+            + self.timestamp.len()
+            + self.metadata.len()
+            + self.tags.iter().map(|tag| tag.len() + 3).sum::<usize>()
+            + optional.iter().filter_map(|value| value.as_ref()).map(String::len).sum::<usize>()
+    }
+
+    /// Check whether this operation carries `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
     /// Get metadata as HashMap (internal)
     pub(crate) fn get_metadata_map(&self) -> HashMap<String, String> {
         serde_json::from_str(&self.metadata).unwrap_or_default()
@@ -528,7 +794,7 @@ impl JJOperation {
     pub fn generate_quantum_fingerprint(&self) -> Result<String> {
         // Serialize operation data for fingerprinting
         let data = serde_json::to_string(self)
-            .map_err(|e| JJError::SerializationError(e.to_string()))?;
+            .map_err(JJError::from)?;
 
         // Return the data as hex-encoded string for now
         // The actual quantum fingerprint generation will be done in wrapper.rs
@@ -543,6 +809,20 @@ impl JJOperation {
     }
 }
 
+impl PartialEq for JJOperation {
+    fn eq(&self, other: &Self) -> bool {
+        self.operation_id == other.operation_id
+    }
+}
+
+impl Eq for JJOperation {}
+
+impl std::hash::Hash for JJOperation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.operation_id.hash(state);
+    }
+}
+
 /// Builder for JJOperation
 pub struct JJOperationBuilder {
     operation_id: Option<String>,
@@ -677,6 +957,121 @@ impl JJOperationBuilder {
     }
 }
 
+/// Tag marking an operation that a later `Undo` reversed
+pub const UNDONE_TAG: &str = "undone";
+
+/// Indices of operations reversed by a later successful `Undo`
+pub(crate) fn undone_indices(ops: &[JJOperation]) -> Vec<usize> {
+    // Operations that are still in effect, most recent last
+    let mut pending: Vec<usize> = Vec::new();
+    let mut undone = Vec::new();
+
+    for (i, op) in ops.iter().enumerate() {
+        let op_type = op.get_operation_type();
+        if op_type == OperationType::Undo {
+            if !op.success {
+                continue;
+            }
+            let position = match undo_target(&op.command) {
+                Some(id) => pending.iter().rposition(|&j| {
+                    ops[j].operation_id == id || ops[j].operation_id.starts_with(&id)
+                }),
+                None => pending.len().checked_sub(1),
+            };
+            if let Some(position) = position {
+                undone.push(pending.remove(position));
+            }
+        } else if op.success
+            && !matches!(
+                op_type.category(),
+                OperationCategory::Read | OperationCategory::Automatic
+            )
+        {
+            pending.push(i);
+        }
+    }
+
+    undone.sort_unstable();
+    undone
+}
+
+/// Operation id given to `jj undo <id>` / `jj op undo <id>`, if any
+fn undo_target(command: &str) -> Option<String> {
+    command
+        .split_whitespace()
+        .skip_while(|arg| *arg != "undo")
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .map(str::to_string)
+}
+
+/// Predicate used by a [`TaggingRule`]
+pub type TagPredicate = Arc<dyn Fn(&JJOperation) -> bool + Send + Sync>;
+
+/// Rule that adds a tag to operations matching a predicate
+///
+/// Applied by `JJWrapper::execute` to each operation before it is logged.
+#[derive(Clone)]
+pub struct TaggingRule {
+    tag: String,
+    predicate: TagPredicate,
+}
+
+impl TaggingRule {
+    /// Create a rule tagging operations for which `predicate` returns true
+    pub fn new<F>(tag: &str, predicate: F) -> Self
+    where
+        F: Fn(&JJOperation) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            tag: tag.to_string(),
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    /// Tag operations slower than `threshold_ms` as `slow`
+    pub fn slow(threshold_ms: u32) -> Self {
+        Self::new("slow", move |op| op.duration_ms > threshold_ms)
+    }
+
+    /// Tag failed operations as `error`
+    pub fn failed() -> Self {
+        Self::new("error", |op| !op.success)
+    }
+
+    /// Tag pushes naming one of `bookmarks` (`-b`/`--bookmark`) as `protected`
+    pub fn protected_push(bookmarks: Vec<String>) -> Self {
+        Self::new("protected", move |op| {
+            if !matches!(op.operation_type.as_str(), "GitPush" | "Push") {
+                return false;
+            }
+            let tokens: Vec<&str> = op.command.split_whitespace().collect();
+            tokens.windows(2).any(|pair| {
+                matches!(pair[0], "-b" | "--bookmark" | "--branch")
+                    && bookmarks.iter().any(|b| b == pair[1])
+            })
+        })
+    }
+
+    /// Tag applied by this rule
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Add the tag to `operation` if it matches
+    pub fn apply(&self, operation: &mut JJOperation) {
+        if (self.predicate)(operation) {
+            operation.add_tag(self.tag.clone());
+        }
+    }
+}
+
+impl std::fmt::Debug for TaggingRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaggingRule").field("tag", &self.tag).finish()
+    }
+}
+
 /// Collection of operations with query capabilities
 ///
 /// Provides methods for storing, querying, and analyzing jujutsu operations.
@@ -701,47 +1096,373 @@ impl JJOperationBuilder {
 #[derive(Debug, Clone)]
 pub struct JJOperationLog {
     /// Operations stored in memory
-    operations: Arc<Mutex<Vec<JJOperation>>>,
+    operations: Arc<RwLock<Vec<JJOperation>>>,
 
     /// Maximum number of operations to keep
     max_entries: usize,
+
+    /// Channels notified of each added operation
+    subscribers: Arc<Mutex<Vec<SyncSender<JJOperation>>>>,
+
+    /// Operations not delivered because a subscriber's channel was full
+    dropped_events: Arc<AtomicUsize>,
+
+    /// Skip operations whose jj operation ID is already logged
+    dedup: bool,
+
+    /// Evict oldest operations once their estimated size exceeds this
+    max_bytes: Option<usize>,
+
+    /// Running sum of the operations' estimated sizes
+    ///
+    /// Only changed while holding the `operations` write lock.
+    total_bytes: Arc<AtomicUsize>,
+}
+
+/// Split a `jj op log` line into (is operation node, text after the graph)
+fn split_graph_prefix(line: &str) -> (bool, &str) {
+    const NODES: [char; 5] = ['@', '○', '◉', '◆', '×'];
+    const EDGES: [char; 12] = ['│', '├', '┤', '─', '╮', '╯', '╭', '╰', '┬', '┴', '|', '~'];
+
+    let text = line.trim_start_matches(|c: char| c.is_whitespace() || NODES.contains(&c) || EDGES.contains(&c));
+    let prefix = &line[..line.len() - text.len()];
+    (prefix.contains(NODES), text.trim_end())
+}
+
+/// Parse the time part of a `jj op log` header into (start, duration in ms)
+fn parse_op_time(text: &str) -> (Option<DateTime<chrono::FixedOffset>>, Option<u32>) {
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f %:z";
+
+    if let Some((start, end)) = text.split_once(" - ") {
+        if let (Ok(start), Ok(end)) = (
+            DateTime::parse_from_str(start.trim(), FORMAT),
+            DateTime::parse_from_str(end.trim(), FORMAT),
+        ) {
+            let duration = (end - start).num_milliseconds().max(0) as u32;
+            return (Some(start), Some(duration));
+        }
+    }
+
+    let duration = text.split_once("lasted ").and_then(|(_, lasted)| parse_lasted(lasted));
+    (None, duration)
+}
+
+/// Parse a human duration such as "8 milliseconds" or "1s 250ms" into ms
+fn parse_lasted(text: &str) -> Option<u32> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut total_ms = 0f64;
+    let mut found = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        // Number and unit may be separate ("8 milliseconds") or joined ("8ms")
+        let token = tokens[i];
+        let split = token.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(token.len());
+        let (number, mut unit) = token.split_at(split);
+        let Ok(value) = number.parse::<f64>() else {
+            i += 1;
+            continue;
+        };
+        if unit.is_empty() {
+            i += 1;
+            unit = tokens.get(i).copied().unwrap_or_default();
+        }
+        let factor = match unit.trim_end_matches(',') {
+            "ns" | "nanosecond" | "nanoseconds" => 1e-6,
+            "us" | "µs" | "microsecond" | "microseconds" => 1e-3,
+            "ms" | "millisecond" | "milliseconds" => 1.0,
+            "s" | "second" | "seconds" => 1_000.0,
+            "m" | "min" | "minute" | "minutes" => 60_000.0,
+            "h" | "hour" | "hours" => 3_600_000.0,
+            _ => break,
+        };
+        total_ms += value * factor;
+        found = true;
+        i += 1;
+    }
+    found.then(|| total_ms.round() as u32)
 }
 
+/// Default channel capacity for [`JJOperationLog::subscribe`]
+pub const DEFAULT_SUBSCRIBER_CAPACITY: usize = 256;
+
 impl JJOperationLog {
     /// Create a new operation log
     pub fn new(max_entries: usize) -> Self {
         Self {
-            operations: Arc::new(Mutex::new(Vec::with_capacity(max_entries))),
+            operations: Arc::new(RwLock::new(Vec::with_capacity(max_entries))),
             max_entries,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            dropped_events: Arc::new(AtomicUsize::new(0)),
+            dedup: false,
+            max_bytes: None,
+            total_bytes: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Enable or disable deduplication by jj operation ID
+    ///
+    /// When enabled, adding an operation whose ID is a real jj operation ID
+    /// (see [`JJOperation::has_jj_operation_id`]) that is already in the log
+    /// is a no-op. Wrapper-generated synthetic IDs are never deduplicated.
+    pub fn with_dedup(mut self, enabled: bool) -> Self {
+        self.dedup = enabled;
+        self
+    }
+
+    /// Also cap the log by memory: at most `max_bytes` of operations
+    ///
+    /// Sizes are [`JJOperation::estimated_size`] estimates. Oldest operations
+    /// are evicted first, on top of the `max_entries` count limit; the newest
+    /// operation is always kept, even if it alone exceeds the budget.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Estimated total size of the logged operations, in bytes
+    pub fn estimated_bytes(&self) -> usize {
+        let _ops = self.operations.read().unwrap();
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Recompute the running size after operations were changed in place
+    fn recount_bytes(&self, ops: &[JJOperation]) {
+        let total = ops.iter().map(JJOperation::estimated_size).sum();
+        self.total_bytes.store(total, Ordering::Relaxed);
+    }
+
     /// Add an operation to the log
     pub fn add_operation(&self, operation: JJOperation) {
-        let mut ops = self.operations.lock().unwrap();
-        ops.push(operation);
+        let notify = !self.subscribers.lock().unwrap().is_empty();
+        let event = notify.then(|| operation.clone());
+
+        {
+            let mut ops = self.operations.write().unwrap();
+            if self.dedup
+                && operation.has_jj_operation_id()
+                && ops.iter().any(|op| op.operation_id == operation.operation_id)
+            {
+                return;
+            }
+            let mut total = self.total_bytes.load(Ordering::Relaxed) + operation.estimated_size();
+            ops.push(operation);
+
+            // Trim to max_entries if exceeded
+            if ops.len() > self.max_entries {
+                let excess = ops.len() - self.max_entries;
+                total -= ops.drain(0..excess).map(|op| op.estimated_size()).sum::<usize>();
+            }
+
+            if let Some(max_bytes) = self.max_bytes {
+                let mut excess = 0;
+                while total > max_bytes && excess + 1 < ops.len() {
+                    total -= ops[excess].estimated_size();
+                    excess += 1;
+                }
+                ops.drain(0..excess);
+            }
+            self.total_bytes.store(total, Ordering::Relaxed);
+        }
 
-        // Trim to max_entries if exceeded
-        if ops.len() > self.max_entries {
-            let excess = ops.len() - self.max_entries;
-            ops.drain(0..excess);
+        if let Some(event) = event {
+            self.broadcast(event);
         }
     }
 
+    /// Subscribe to operations as they are added
+    ///
+    /// Uses a bounded channel of [`DEFAULT_SUBSCRIBER_CAPACITY`]; see
+    /// [`subscribe_with_capacity`](Self::subscribe_with_capacity).
+    pub fn subscribe(&self) -> Receiver<JJOperation> {
+        self.subscribe_with_capacity(DEFAULT_SUBSCRIBER_CAPACITY)
+    }
+
+    /// Subscribe with a custom channel capacity
+    ///
+    /// When a subscriber falls behind and its channel is full, new operations
+    /// are dropped for that subscriber (counted by
+    /// [`dropped_events`](Self::dropped_events)) rather than blocking the
+    /// caller of `add_operation`. Dropping the receiver unsubscribes.
+    pub fn subscribe_with_capacity(&self, capacity: usize) -> Receiver<JJOperation> {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Number of operations dropped because a subscriber's channel was full
+    pub fn dropped_events(&self) -> usize {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Send an operation to every subscriber without blocking
+    fn broadcast(&self, operation: JJOperation) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|sender| match sender.try_send(operation.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.dropped_events.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+
     /// Get recent operations (most recent first)
     pub fn get_recent(&self, limit: usize) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter().rev().take(limit).cloned().collect()
     }
 
+    /// Load operations from a JSON Lines file written by [`append_jsonl`](Self::append_jsonl)
+    ///
+    /// A missing file loads nothing. Lines that don't parse, typically a
+    /// partial last line left by a crash, are skipped; an unterminated last
+    /// line is closed so later appends start on a fresh line. Returns the
+    /// number of operations loaded.
+    pub fn load_jsonl(&self, path: &Path) -> Result<usize> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(JJError::IoError(format!("{}: {}", path.display(), e))),
+        };
+
+        let mut loaded = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            if let Ok(operation) = serde_json::from_str::<JJOperation>(line) {
+                self.add_operation(operation);
+                loaded += 1;
+            }
+        }
+
+        if !content.is_empty() && !content.ends_with('\n') {
+            std::fs::OpenOptions::new()
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(b"\n"))
+                .map_err(|e| JJError::IoError(format!("{}: {}", path.display(), e)))?;
+        }
+
+        Ok(loaded)
+    }
+
+    /// Serialize every operation, oldest first, as a CBOR array
+    ///
+    /// A compact alternative to JSON Lines for archiving large histories;
+    /// JSONL remains the interoperable format. Read back with
+    /// [`import_cbor`](Self::import_cbor).
+    #[cfg(feature = "cbor")]
+    pub fn export_cbor(&self) -> Result<Vec<u8>> {
+        let ops = self.operations.read().unwrap();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&*ops, &mut bytes)
+            .map_err(|e| JJError::SerializationError(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Add operations from [`export_cbor`](Self::export_cbor) output
+    ///
+    /// Unlike [`load_jsonl`](Self::load_jsonl) the input is all-or-nothing:
+    /// corrupt data is an error and nothing is added. Returns the number of
+    /// operations added.
+    #[cfg(feature = "cbor")]
+    pub fn import_cbor(&self, bytes: &[u8]) -> Result<usize> {
+        let operations: Vec<JJOperation> = ciborium::from_reader(bytes)
+            .map_err(|e| JJError::SerializationError(e.to_string()))?;
+        let count = operations.len();
+        for operation in operations {
+            self.add_operation(operation);
+        }
+        Ok(count)
+    }
+
+    /// Parse the default human-readable `jj op log` output
+    ///
+    /// Each operation is a header line (`<id> <user>@<host> <time>`) followed
+    /// by its description and an optional `args:` line, all behind jj's
+    /// graph characters. Absolute time ranges (`<start> - <end>`) set the
+    /// timestamp and duration; relative ones (`5 minutes ago, lasted 8
+    /// milliseconds`) only set the duration and are kept in the `time`
+    /// metadata. Operations are returned newest first, as jj prints them.
+    pub fn parse_op_log_text(output: &str) -> Vec<JJOperation> {
+        let mut operations: Vec<JJOperation> = Vec::new();
+        let mut has_description = false;
+
+        for line in output.lines() {
+            let (is_node, text) = split_graph_prefix(line);
+            if text.is_empty() {
+                continue;
+            }
+
+            if is_node {
+                let mut parts = text.splitn(3, ' ');
+                let id = parts.next().unwrap_or_default().to_string();
+                let user_host = parts.next().unwrap_or_default();
+                let rest = parts.next().unwrap_or_default().trim();
+
+                let Some((user, host)) = user_host.split_once('@') else {
+                    // The root operation has no user, only a description
+                    let description = format!("{} {}", user_host, rest).trim().to_string();
+                    let mut operation = JJOperation::new(id, description, String::new(), String::new());
+                    operation.operation_type = OperationType::from_description(&operation.command).as_string();
+                    operations.push(operation);
+                    has_description = true;
+                    continue;
+                };
+
+                let mut operation = JJOperation::new(id, String::new(), user.to_string(), host.to_string());
+                let (start, duration_ms) = parse_op_time(rest);
+                match start {
+                    Some(start) => operation.timestamp = start.to_rfc3339(),
+                    None => operation.set_metadata("time".to_string(), rest.to_string()),
+                }
+                operation.duration_ms = duration_ms.unwrap_or(0);
+                operations.push(operation);
+                has_description = false;
+            } else if let Some(operation) = operations.last_mut() {
+                if let Some(args) = text.strip_prefix("args:") {
+                    operation.set_metadata("args".to_string(), args.trim().to_string());
+                } else if !has_description {
+                    operation.command = text.to_string();
+                    operation.operation_type = OperationType::from_description(text).as_string();
+                    has_description = true;
+                }
+            }
+        }
+
+        operations
+    }
+
+    /// Append one operation to a JSON Lines file, creating it if needed
+    pub fn append_jsonl(path: &Path, operation: &JJOperation) -> Result<()> {
+        let mut line = serde_json::to_string(operation)
+            .map_err(JJError::from)?;
+        line.push('\n');
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| JJError::IoError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Get a page of operations, newest first
+    ///
+    /// `offset` counts from the newest operation. Pages past the end are
+    /// empty and the last page may be short.
+    pub fn get_page(&self, offset: usize, limit: usize) -> Vec<JJOperation> {
+        let ops = self.operations.read().unwrap();
+        ops.iter().rev().skip(offset).take(limit).cloned().collect()
+    }
+
     /// Get all operations
     pub fn get_all(&self) -> Vec<JJOperation> {
-        self.operations.lock().unwrap().clone()
+        self.operations.read().unwrap().clone()
     }
 
     /// Find operation by ID
     pub fn find_by_id(&self, id: &str) -> Option<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .find(|op| op.id == id || op.operation_id == id)
             .cloned()
@@ -753,6 +1474,43 @@ impl JJOperationLog {
             .ok_or_else(|| JJError::OperationNotFound(id.to_string()))
     }
 
+    /// Get operations logged after the one with the given ID, oldest first
+    ///
+    /// Returns an empty list when `id` is the most recent operation. If `id`
+    /// is unknown (or has already been evicted) the caller has to resync the
+    /// whole log, so this is an error rather than an empty list.
+    pub fn operations_after(&self, id: &str) -> Result<Vec<JJOperation>> {
+        let ops = self.operations.read().unwrap();
+        let position = ops
+            .iter()
+            .position(|op| op.id == id || op.operation_id == id)
+            .ok_or_else(|| JJError::OperationNotFound(id.to_string()))?;
+        Ok(ops[position + 1..].to_vec())
+    }
+
+    /// Get all operations sorted by `sort`, descending if `desc`
+    ///
+    /// The sort is stable: operations with equal keys stay in insertion
+    /// order either way. Unparseable timestamps sort before valid ones.
+    pub fn sorted_by(&self, sort: OperationSort, desc: bool) -> Vec<JJOperation> {
+        let mut ops = self.get_all();
+        ops.sort_by(|a, b| {
+            let ordering = match sort {
+                OperationSort::ByTimestamp => DateTime::parse_from_rfc3339(&a.timestamp)
+                    .ok()
+                    .cmp(&DateTime::parse_from_rfc3339(&b.timestamp).ok()),
+                OperationSort::ByDuration => a.duration_ms.cmp(&b.duration_ms),
+                OperationSort::ByType => a.operation_type.cmp(&b.operation_type),
+            };
+            if desc {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        ops
+    }
+
     /// Filter operations by type
     pub fn filter_by_type(&self, op_type: OperationType) -> Vec<JJOperation> {
         self.get_by_type(op_type)
@@ -760,7 +1518,7 @@ impl JJOperationLog {
 
     /// Get operations by type
     pub fn get_by_type(&self, op_type: OperationType) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.get_operation_type() == op_type)
             .cloned()
@@ -775,7 +1533,7 @@ impl JJOperationLog {
     ) -> Vec<JJOperation> {
         let start_str = start.to_rfc3339();
         let end_str = end.to_rfc3339();
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.timestamp >= start_str && op.timestamp <= end_str)
             .cloned()
@@ -784,24 +1542,68 @@ impl JJOperationLog {
 
     /// Filter operations by user
     pub fn filter_by_user(&self, user: &str) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter().filter(|op| op.user == user).cloned().collect()
     }
 
+    /// Get operations carrying `tag`
+    pub fn filter_by_tag(&self, tag: &str) -> Vec<JJOperation> {
+        let ops = self.operations.read().unwrap();
+        ops.iter()
+            .filter(|op| op.tags().iter().any(|t| t == tag))
+            .cloned()
+            .collect()
+    }
+
+    /// Get operations logged by a wrapper for `repo_path`
+    ///
+    /// Matches the `repo_path` metadata stamped by `JJWrapper::execute`.
+    pub fn filter_by_repo(&self, repo_path: &str) -> Vec<JJOperation> {
+        let ops = self.operations.read().unwrap();
+        ops.iter()
+            .filter(|op| op.get_metadata("repo_path").as_deref() == Some(repo_path))
+            .cloned()
+            .collect()
+    }
+
+    /// Get operations logged while `task_id` was active
+    ///
+    /// Matches the `task_id` metadata stamped between `JJWrapper::begin_task`
+    /// and `end_task`, in log order.
+    pub fn filter_by_task(&self, task_id: &str) -> Vec<JJOperation> {
+        let ops = self.operations.read().unwrap();
+        ops.iter()
+            .filter(|op| op.get_metadata("task_id").as_deref() == Some(task_id))
+            .cloned()
+            .collect()
+    }
+
     /// Get operations in the last N hours
     pub fn recent_operations(&self, hours: i64) -> Vec<JJOperation> {
         let cutoff = (Utc::now() - Duration::hours(hours)).to_rfc3339();
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.timestamp >= cutoff)
             .cloned()
             .collect()
     }
 
+    /// Get operations that started more than `age` ago
+    ///
+    /// Operations with unparseable timestamps are not included.
+    pub fn operations_older_than(&self, age: Duration) -> Vec<JJOperation> {
+        let cutoff = Utc::now() - age;
+        let ops = self.operations.read().unwrap();
+        ops.iter()
+            .filter(|op| op.parsed_timestamp().is_some_and(|ts| ts < cutoff))
+            .cloned()
+            .collect()
+    }
+
     /// Search operations by command or description
     pub fn search(&self, query: &str) -> Vec<JJOperation> {
         let query_lower = query.to_lowercase();
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.command.to_lowercase().contains(&query_lower))
             .cloned()
@@ -810,13 +1612,13 @@ impl JJOperationLog {
 
     /// Get failed operations
     pub fn failed_operations(&self) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter().filter(|op| !op.success).cloned().collect()
     }
 
     /// Get operations that modified history
     pub fn history_modifying_operations(&self) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.get_operation_type().modifies_history())
             .cloned()
@@ -825,7 +1627,7 @@ impl JJOperationLog {
 
     /// Get remote operations
     pub fn remote_operations(&self) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.get_operation_type().is_remote_operation())
             .cloned()
@@ -834,7 +1636,7 @@ impl JJOperationLog {
 
     /// Get user-initiated operations (exclude snapshots)
     pub fn get_user_operations(&self, limit: usize) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .rev()
             .filter(|op| op.is_user_initiated())
@@ -846,13 +1648,13 @@ impl JJOperationLog {
     /// Get total operation count
     #[inline]
     pub fn count(&self) -> usize {
-        self.operations.lock().unwrap().len()
+        self.operations.read().unwrap().len()
     }
 
     /// Check if log is empty
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.operations.lock().unwrap().is_empty()
+        self.operations.read().unwrap().is_empty()
     }
 
     /// Get length
@@ -863,12 +1665,71 @@ impl JJOperationLog {
 
     /// Clear all operations
     pub fn clear(&self) {
-        self.operations.lock().unwrap().clear();
+        let mut ops = self.operations.write().unwrap();
+        ops.clear();
+        self.total_bytes.store(0, Ordering::Relaxed);
+    }
+
+    /// Remove automatic snapshots older than `older_than`
+    ///
+    /// User-initiated operations are always kept, as are snapshots whose
+    /// timestamp cannot be parsed. Returns the number of operations removed.
+    pub fn compact(&self, older_than: Duration) -> usize {
+        let cutoff = Utc::now() - older_than;
+        let mut ops = self.operations.write().unwrap();
+        let before = ops.len();
+        let mut removed_bytes = 0;
+        ops.retain(|op| {
+            let keep = !op.is_snapshot()
+                || match DateTime::parse_from_rfc3339(&op.timestamp) {
+                    Ok(ts) => ts.with_timezone(&Utc) >= cutoff,
+                    Err(_) => true,
+                };
+            if !keep {
+                removed_bytes += op.estimated_size();
+            }
+            keep
+        });
+        self.total_bytes.fetch_sub(removed_bytes, Ordering::Relaxed);
+        before - ops.len()
+    }
+
+    /// Find operations that were reversed by a later `Undo`, oldest first
+    ///
+    /// Each successful undo reverses the operation named by its
+    /// `jj op undo <id>` argument (ids may be abbreviated); a bare `jj undo`
+    /// reverses the most recent operation not already undone, so consecutive
+    /// undos walk back through history. Snapshots and read-only commands are
+    /// never targets. The returned copies carry the [`UNDONE_TAG`] tag.
+    pub fn find_undone_operations(&self) -> Vec<JJOperation> {
+        let ops = self.operations.read().unwrap();
+        undone_indices(&ops)
+            .into_iter()
+            .map(|i| {
+                let mut op = ops[i].clone();
+                op.add_tag(UNDONE_TAG.to_string());
+                op
+            })
+            .collect()
+    }
+
+    /// Tag operations reversed by a later `Undo` with [`UNDONE_TAG`] in place
+    ///
+    /// Returns the number of operations found; already-tagged ones are
+    /// counted again but not duplicated.
+    pub fn mark_undone_operations(&self) -> usize {
+        let mut ops = self.operations.write().unwrap();
+        let undone = undone_indices(&ops);
+        for &i in &undone {
+            ops[i].add_tag(UNDONE_TAG.to_string());
+        }
+        self.recount_bytes(&ops);
+        undone.len()
     }
 
     /// Get statistics about operations
     pub fn statistics(&self) -> OperationStatistics {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         let mut stats = OperationStatistics::default();
 
         for op in ops.iter() {
@@ -893,12 +1754,34 @@ impl JJOperationLog {
             stats.avg_duration_ms = stats.total_duration_ms / stats.total as u64;
         }
 
+        let timed: Vec<(DateTime<Utc>, bool)> = ops
+            .iter()
+            .filter_map(|op| op.parsed_timestamp().map(|ts| (ts, op.success)))
+            .collect();
+        let oldest = timed.iter().map(|(ts, _)| *ts).min();
+        let newest = timed.iter().map(|(ts, _)| *ts).max();
+        if let (Some(oldest), Some(newest)) = (oldest, newest) {
+            let span = newest - oldest;
+            if span >= Duration::hours(1) {
+                let hours = span.num_milliseconds() as f64 / 3_600_000.0;
+                stats.operations_per_hour = Some(timed.len() as f64 / hours);
+
+                let failure_rate = |ops: &[&(DateTime<Utc>, bool)]| {
+                    ops.iter().filter(|(_, success)| !success).count() as f64 / ops.len() as f64
+                };
+                let all: Vec<_> = timed.iter().collect();
+                let recent: Vec<_> =
+                    timed.iter().filter(|(ts, _)| *ts > newest - Duration::hours(1)).collect();
+                stats.failure_trend = Some(failure_rate(&recent) - failure_rate(&all));
+            }
+        }
+
         stats
     }
 
     /// Get average operation duration
     pub fn avg_duration_ms(&self) -> f64 {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         if ops.is_empty() {
             return 0.0;
         }
@@ -909,7 +1792,7 @@ impl JJOperationLog {
 
     /// Get success rate
     pub fn success_rate(&self) -> f64 {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         if ops.is_empty() {
             return 0.0;
         }
@@ -918,14 +1801,106 @@ impl JJOperationLog {
         successful as f64 / ops.len() as f64
     }
 
-    /// Get an iterator over operations
-    pub fn iter(&self) -> Vec<JJOperation> {
-        self.get_all()
+    /// Get a typed metrics snapshot of the log
+    ///
+    /// Unlike [`statistics`](Self::statistics), the snapshot is N-API compatible
+    /// and includes percentile durations and remote/history breakdowns.
+    pub fn metrics(&self) -> OperationMetrics {
+        let ops = self.operations.read().unwrap();
+        let mut metrics = OperationMetrics::default();
+
+        if ops.is_empty() {
+            return metrics;
+        }
+
+        let mut durations: Vec<u32> = Vec::with_capacity(ops.len());
+        let mut successful = 0usize;
+
+        for op in ops.iter() {
+            *metrics.by_type.entry(op.operation_type.clone()).or_insert(0) += 1;
+
+            if op.success {
+                successful += 1;
+            } else {
+                metrics.failed_operations += 1;
+            }
+
+            let op_type = op.get_operation_type();
+            if op_type.is_remote_operation() {
+                metrics.remote_operations += 1;
+            }
+            if op_type.modifies_history() {
+                metrics.history_modifying_operations += 1;
+            }
+
+            durations.push(op.duration_ms);
+        }
+
+        metrics.duration_buckets = DURATION_BUCKETS_MS
+            .iter()
+            .map(|bound| durations.iter().filter(|d| *d <= bound).count() as u32)
+            .collect();
+
+        metrics.total_operations = ops.len() as u32;
+        metrics.success_rate = successful as f64 / ops.len() as f64;
+        metrics.avg_duration_ms =
+            durations.iter().map(|d| *d as u64).sum::<u64>() as f64 / durations.len() as f64;
+
+        // Nearest-rank percentile
+        durations.sort_unstable();
+        let rank = ((durations.len() as f64) * 0.95).ceil() as usize;
+        metrics.p95_duration_ms = durations[rank.saturating_sub(1)] as f64;
+
+        metrics
     }
 
-    /// Sign an operation by ID
-    ///
-    /// Signs the specified operation with the provided keypair.
+    /// Count operations by category
+    pub fn count_by_category(&self) -> HashMap<OperationCategory, usize> {
+        let ops = self.operations.read().unwrap();
+        let mut counts = HashMap::new();
+        for op in ops.iter() {
+            *counts.entry(op.get_operation_type().category()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Bucket operations by timestamp into fixed intervals
+    ///
+    /// Returns ordered `(bucket_start, count)` pairs for the buckets that hold
+    /// at least one operation; empty buckets in between are omitted, so a
+    /// small `bucket` over a long span stays cheap. Bucket starts are floored
+    /// to a multiple of `bucket` since the Unix epoch, so alignment is deterministic.
+    /// Operations with unparseable timestamps are ignored.
+    pub fn histogram(&self, bucket: Duration) -> Vec<(DateTime<Utc>, usize)> {
+        let bucket_ms = bucket.num_milliseconds();
+        if bucket_ms <= 0 {
+            return Vec::new();
+        }
+
+        let ops = self.operations.read().unwrap();
+        let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+
+        for op in ops.iter() {
+            if let Ok(ts) = DateTime::parse_from_rfc3339(&op.timestamp) {
+                let start = ts.timestamp_millis().div_euclid(bucket_ms) * bucket_ms;
+                *counts.entry(start).or_insert(0) += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .filter_map(|(start, count)| DateTime::from_timestamp_millis(start).map(|dt| (dt, count)))
+            .collect()
+    }
+
+    /// Get an iterator over operations
+    pub fn iter(&self) -> Vec<JJOperation> {
+        self.get_all()
+    }
+
+    /// Sign an operation by ID
+    ///
+    /// Signs the specified operation with the provided keypair.
     ///
     /// # Arguments
     ///
@@ -951,12 +1926,15 @@ impl JJOperationLog {
     /// log.sign_operation(&op_id, &keypair.secret_key, &keypair.public_key).unwrap();
     /// ```
     pub fn sign_operation(&self, operation_id: &str, secret_key: &str, public_key: &str) -> Result<()> {
-        let mut ops = self.operations.lock().unwrap();
+        let mut ops = self.operations.write().unwrap();
         let operation = ops.iter_mut()
             .find(|op| op.id == operation_id || op.operation_id == operation_id)
             .ok_or_else(|| JJError::OperationNotFound(operation_id.to_string()))?;
 
+        let old_size = operation.estimated_size();
         operation.sign(secret_key, public_key)?;
+        self.total_bytes.fetch_add(operation.estimated_size(), Ordering::Relaxed);
+        self.total_bytes.fetch_sub(old_size, Ordering::Relaxed);
         Ok(())
     }
 
@@ -1003,7 +1981,7 @@ impl JJOperationLog {
     /// println!("Verified {}/{} operations ({} invalid)", valid, total, invalid);
     /// ```
     pub fn verify_all_operations(&self, public_key: Option<&str>) -> Result<(usize, usize, usize)> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         let mut total_signed = 0;
         let mut valid_count = 0;
         let mut invalid_count = 0;
@@ -1044,7 +2022,7 @@ impl JJOperationLog {
 
     /// Get all signed operations
     pub fn signed_operations(&self) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| op.is_signed())
             .cloned()
@@ -1053,7 +2031,7 @@ impl JJOperationLog {
 
     /// Get all unsigned operations
     pub fn unsigned_operations(&self) -> Vec<JJOperation> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         ops.iter()
             .filter(|op| !op.is_signed())
             .cloned()
@@ -1073,12 +2051,14 @@ impl JJOperationLog {
     ///
     /// The number of operations that were signed
     pub fn sign_all_operations(&self, secret_key: &str, public_key: &str) -> Result<usize> {
-        let mut ops = self.operations.lock().unwrap();
+        let mut ops = self.operations.write().unwrap();
         let mut signed_count = 0;
 
         for operation in ops.iter_mut() {
             if !operation.is_signed() {
+                let unsigned_size = operation.estimated_size();
                 operation.sign(secret_key, public_key)?;
+                self.total_bytes.fetch_add(operation.estimated_size() - unsigned_size, Ordering::Relaxed);
                 signed_count += 1;
             }
         }
@@ -1095,7 +2075,7 @@ impl JJOperationLog {
     ///
     /// `Ok(true)` if chain is valid, `Ok(false)` if broken
     pub fn verify_signature_chain(&self) -> Result<bool> {
-        let ops = self.operations.lock().unwrap();
+        let ops = self.operations.read().unwrap();
         let signed_ops: Vec<&JJOperation> = ops.iter()
             .filter(|op| op.is_signed())
             .collect();
@@ -1135,7 +2115,7 @@ impl Default for JJOperationLog {
 }
 
 /// Statistics about operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[derive(Default)]
 pub struct OperationStatistics {
     /// Total number of operations
@@ -1147,7 +2127,8 @@ pub struct OperationStatistics {
     /// Number of failed operations
     pub failed: usize,
 
-    /// Operations by type
+    /// Operations by type (serialized with string keys, e.g. `{"Commit": 2}`)
+    #[serde(with = "by_type_keys")]
     pub by_type: HashMap<OperationType, usize>,
 
     /// Total duration in milliseconds
@@ -1158,13 +2139,274 @@ pub struct OperationStatistics {
 
     /// Maximum duration in milliseconds
     pub max_duration_ms: u64,
+
+    /// Operations per hour over the span of the log
+    ///
+    /// `None` when the timestamps span less than an hour, where a rate
+    /// would be extrapolated from too little data.
+    #[serde(default)]
+    pub operations_per_hour: Option<f64>,
+
+    /// Failure rate over the last hour of the log minus the overall rate
+    ///
+    /// Positive means failures are rising. `None` under the same conditions
+    /// as `operations_per_hour`, since the window would cover every operation.
+    #[serde(default)]
+    pub failure_trend: Option<f64>,
+}
+
+/// Serialize `HashMap<OperationType, usize>` as a map with string keys
+///
+/// Keys use the variant name (`OperationType::as_string`) and are emitted in
+/// sorted order so the JSON is stable.
+mod by_type_keys {
+    use super::OperationType;
+    use serde::de::IntoDeserializer;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+
+    pub fn serialize<S>(map: &HashMap<OperationType, usize>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let sorted: BTreeMap<String, usize> =
+            map.iter().map(|(k, v)| (k.as_string(), *v)).collect();
+        serializer.collect_map(sorted)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<OperationType, usize>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = HashMap::<String, usize>::deserialize(deserializer)?;
+        raw.into_iter()
+            .map(|(key, count)| {
+                let op_type = OperationType::deserialize(key.as_str().into_deserializer())?;
+                Ok((op_type, count))
+            })
+            .collect()
+    }
+}
+
+/// Typed metrics snapshot of an operation log
+///
+/// Returned by [`JJOperationLog::metrics`] and `JJWrapper::get_metrics` for
+/// monitoring agents that want structured data instead of a JSON string.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[napi(object)]
+pub struct OperationMetrics {
+    /// Total number of operations
+    pub total_operations: u32,
+
+    /// Number of failed operations
+    pub failed_operations: u32,
+
+    /// Fraction of successful operations (0.0 to 1.0)
+    pub success_rate: f64,
+
+    /// Average duration in milliseconds
+    pub avg_duration_ms: f64,
+
+    /// 95th percentile duration in milliseconds
+    pub p95_duration_ms: f64,
+
+    /// Number of operations that interact with remotes
+    pub remote_operations: u32,
+
+    /// Number of operations that modify history
+    pub history_modifying_operations: u32,
+
+    /// Operation counts keyed by operation type
+    pub by_type: HashMap<String, u32>,
+
+    /// Cumulative counts of operations at or under each [`DURATION_BUCKETS_MS`] bound
+    #[serde(default)]
+    pub duration_buckets: Vec<u32>,
+}
+
+/// Upper bounds (ms) of the duration histogram in [`OperationMetrics`]
+pub const DURATION_BUCKETS_MS: [u32; 10] = [10, 50, 100, 250, 500, 1000, 2500, 5000, 10000, 30000];
+
+impl OperationMetrics {
+    /// Render the snapshot in Prometheus text exposition format
+    ///
+    /// Metric names are prefixed with `jj_`. The values describe the
+    /// operations currently retained in the log, which drops old entries
+    /// once full, so every metric is a gauge rather than a counter.
+    pub fn to_prometheus(&self) -> String {
+        let total = self.total_operations;
+        let mut out = String::new();
+
+        out.push_str("# HELP jj_operations Retained operations, by type\n");
+        out.push_str("# TYPE jj_operations gauge\n");
+        let mut by_type: Vec<_> = self.by_type.iter().collect();
+        by_type.sort();
+        for (op_type, count) in by_type {
+            out.push_str(&format!(
+                "jj_operations{{type=\"{}\"}} {}\n",
+                escape_label_value(op_type),
+                count
+            ));
+        }
+
+        for (name, help, value) in [
+            ("jj_operations_succeeded", "Retained operations that succeeded", total - self.failed_operations),
+            ("jj_operations_failed", "Retained operations that failed", self.failed_operations),
+            ("jj_remote_operations", "Retained operations that interact with remotes", self.remote_operations),
+            (
+                "jj_history_operations",
+                "Retained operations that modify history",
+                self.history_modifying_operations,
+            ),
+        ] {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+        }
+
+        out.push_str("# HELP jj_operation_duration_ms_bucket Retained operations at or under each duration in milliseconds\n");
+        out.push_str("# TYPE jj_operation_duration_ms_bucket gauge\n");
+        for (i, bound) in DURATION_BUCKETS_MS.iter().enumerate() {
+            let count = self.duration_buckets.get(i).copied().unwrap_or(0);
+            out.push_str(&format!("jj_operation_duration_ms_bucket{{le=\"{}\"}} {}\n", bound, count));
+        }
+        out.push_str(&format!("jj_operation_duration_ms_bucket{{le=\"+Inf\"}} {}\n", total));
+        for (name, help, value) in [
+            ("jj_operation_duration_ms_sum", "Total duration of retained operations in milliseconds", self.avg_duration_ms * total as f64),
+            ("jj_operation_duration_ms_count", "Retained operations with a duration", total as f64),
+        ] {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+        }
+
+        out
+    }
 }
 
+/// Escape a Prometheus label value (backslash, double quote, newline)
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Every `OperationType` variant, for tests that must cover them all
+    const ALL_OPERATION_TYPES: [OperationType; 38] = [
+        OperationType::Commit,
+        OperationType::Snapshot,
+        OperationType::Describe,
+        OperationType::New,
+        OperationType::Edit,
+        OperationType::Abandon,
+        OperationType::Rebase,
+        OperationType::Squash,
+        OperationType::Absorb,
+        OperationType::Workspace,
+        OperationType::File,
+        OperationType::Sign,
+        OperationType::Fix,
+        OperationType::Resolve,
+        OperationType::Branch,
+        OperationType::BranchDelete,
+        OperationType::Bookmark,
+        OperationType::Tag,
+        OperationType::Checkout,
+        OperationType::Restore,
+        OperationType::Split,
+        OperationType::Duplicate,
+        OperationType::Undo,
+        OperationType::Fetch,
+        OperationType::GitFetch,
+        OperationType::Push,
+        OperationType::GitPush,
+        OperationType::Clone,
+        OperationType::Init,
+        OperationType::GitImport,
+        OperationType::GitExport,
+        OperationType::Move,
+        OperationType::Diffedit,
+        OperationType::Merge,
+        OperationType::Status,
+        OperationType::Log,
+        OperationType::Diff,
+        OperationType::Unknown,
+    ];
+
+    /// Fails to compile when a variant is added without updating
+    /// `ALL_OPERATION_TYPES` (bump its length and add the variant there)
+    #[allow(dead_code)]
+    fn assert_all_operation_types_listed(op_type: OperationType) {
+        match op_type {
+            OperationType::Commit
+            | OperationType::Snapshot
+            | OperationType::Describe
+            | OperationType::New
+            | OperationType::Edit
+            | OperationType::Abandon
+            | OperationType::Rebase
+            | OperationType::Squash
+            | OperationType::Absorb
+            | OperationType::Workspace
+            | OperationType::File
+            | OperationType::Sign
+            | OperationType::Fix
+            | OperationType::Resolve
+            | OperationType::Branch
+            | OperationType::BranchDelete
+            | OperationType::Bookmark
+            | OperationType::Tag
+            | OperationType::Checkout
+            | OperationType::Restore
+            | OperationType::Split
+            | OperationType::Duplicate
+            | OperationType::Undo
+            | OperationType::Fetch
+            | OperationType::GitFetch
+            | OperationType::Push
+            | OperationType::GitPush
+            | OperationType::Clone
+            | OperationType::Init
+            | OperationType::GitImport
+            | OperationType::GitExport
+            | OperationType::Move
+            | OperationType::Diffedit
+            | OperationType::Merge
+            | OperationType::Status
+            | OperationType::Log
+            | OperationType::Diff
+            | OperationType::Unknown => {}
+        }
+    }
+
+    #[test]
+    fn test_operation_type_string_round_trip() {
+        for op_type in ALL_OPERATION_TYPES {
+            assert_eq!(
+                OperationType::from_string(&op_type.as_string()),
+                op_type,
+                "{:?}",
+                op_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_operation_type_kebab_round_trip() {
+        for op_type in ALL_OPERATION_TYPES {
+            assert_eq!(
+                OperationType::from_string(op_type.as_kebab()),
+                op_type,
+                "{:?}",
+                op_type
+            );
+        }
+        assert_eq!(OperationType::GitFetch.as_kebab(), "git-fetch");
+        assert_eq!(OperationType::BranchDelete.as_kebab(), "branch-delete");
+        assert_eq!(OperationType::Diffedit.as_kebab(), "diffedit");
+    }
+
     #[test]
     fn test_operation_type_conversion() {
         assert_eq!(
@@ -1191,6 +2433,83 @@ mod tests {
         assert!(!OperationType::Commit.is_automatic());
     }
 
+    #[test]
+    fn test_operation_category() {
+        use OperationCategory::*;
+
+        let expected = [
+            (OperationType::Commit, History),
+            (OperationType::Snapshot, Automatic),
+            (OperationType::Describe, History),
+            (OperationType::New, History),
+            (OperationType::Edit, History),
+            (OperationType::Abandon, History),
+            (OperationType::Rebase, History),
+            (OperationType::Squash, History),
+            (OperationType::Absorb, History),
+            (OperationType::Workspace, Other),
+            (OperationType::File, Other),
+            (OperationType::Sign, History),
+            (OperationType::Fix, History),
+            (OperationType::Resolve, History),
+            (OperationType::Branch, Branching),
+            (OperationType::BranchDelete, Branching),
+            (OperationType::Bookmark, Branching),
+            (OperationType::Tag, Branching),
+            (OperationType::Checkout, History),
+            (OperationType::Restore, History),
+            (OperationType::Split, History),
+            (OperationType::Duplicate, History),
+            (OperationType::Undo, Other),
+            (OperationType::Fetch, Remote),
+            (OperationType::GitFetch, Remote),
+            (OperationType::Push, Remote),
+            (OperationType::GitPush, Remote),
+            (OperationType::Clone, Remote),
+            (OperationType::Init, Other),
+            (OperationType::GitImport, Remote),
+            (OperationType::GitExport, Remote),
+            (OperationType::Move, History),
+            (OperationType::Diffedit, History),
+            (OperationType::Merge, History),
+            (OperationType::Status, Read),
+            (OperationType::Log, Read),
+            (OperationType::Diff, Read),
+            (OperationType::Unknown, Other),
+        ];
+
+        assert_eq!(expected.len(), ALL_OPERATION_TYPES.len());
+        for (op_type, category) in expected {
+            assert_eq!(op_type.category(), category, "{:?}", op_type);
+            if op_type.modifies_history() {
+                assert_eq!(category, History);
+            }
+            assert_eq!(op_type.is_remote_operation(), category == Remote);
+            assert_eq!(op_type.is_automatic(), category == Automatic);
+        }
+    }
+
+    #[test]
+    fn test_count_by_category() {
+        let log = JJOperationLog::new(100);
+        for op_type in [
+            OperationType::Commit,
+            OperationType::Rebase,
+            OperationType::Push,
+            OperationType::Snapshot,
+            OperationType::Status,
+        ] {
+            log.add_operation(JJOperation::builder().operation_type(op_type).build());
+        }
+
+        let counts = log.count_by_category();
+        assert_eq!(counts.get(&OperationCategory::History), Some(&2));
+        assert_eq!(counts.get(&OperationCategory::Remote), Some(&1));
+        assert_eq!(counts.get(&OperationCategory::Automatic), Some(&1));
+        assert_eq!(counts.get(&OperationCategory::Read), Some(&1));
+        assert_eq!(counts.get(&OperationCategory::Branching), None);
+    }
+
     #[test]
     fn test_operation_creation() {
         let mut op = JJOperation::new(
@@ -1205,6 +2524,35 @@ mod tests {
         assert!(op.is_user_initiated());
     }
 
+    #[test]
+    fn test_operation_equality_by_operation_id() {
+        let op = JJOperation::new(
+            "3f9a2b1c".into(),
+            "jj describe".into(),
+            "alice".into(),
+            "localhost".into(),
+        );
+        let mut copy = op.clone();
+        copy.add_tag("reviewed".into());
+        assert_eq!(op, copy);
+
+        // Same command, fresh `id`, different jj operation
+        let other = JJOperation::new(
+            "7c4d8e0f".into(),
+            "jj describe".into(),
+            "alice".into(),
+            "localhost".into(),
+        );
+        assert_ne!(op, other);
+
+        let rebuilt = JJOperation::new("3f9a2b1c".into(), "jj new".into(), "bob".into(), "ci".into());
+        assert_ne!(op.id, rebuilt.id);
+        assert_eq!(op, rebuilt);
+
+        let unique: std::collections::HashSet<JJOperation> = [op, copy, other, rebuilt].into_iter().collect();
+        assert_eq!(unique.len(), 2);
+    }
+
     #[test]
     fn test_operation_builder() {
         let op = JJOperation::builder()
@@ -1280,6 +2628,39 @@ mod tests {
         assert_eq!(all[4].operation_id, "op9");
     }
 
+    #[test]
+    fn test_operation_log_byte_limit() {
+        let large_op = |i: usize| {
+            JJOperation::new(
+                format!("op{}", i),
+                format!("jj describe -m '{}'", "x".repeat(4_000)),
+                "alice".into(),
+                "localhost".into(),
+            )
+        };
+        let op_size = large_op(0).estimated_size();
+        assert!(op_size > 4_000);
+
+        let log = JJOperationLog::new(1000).with_max_bytes(op_size * 3 + op_size / 2);
+        for i in 0..10 {
+            log.add_operation(large_op(i));
+            assert!(log.estimated_bytes() <= op_size * 3 + op_size / 2);
+        }
+
+        // Count limit alone would have kept all ten
+        let all = log.get_all();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].operation_id, "op7");
+        assert_eq!(all[2].operation_id, "op9");
+
+        // An operation larger than the whole budget still displaces the rest
+        let tiny = JJOperationLog::new(1000).with_max_bytes(10);
+        tiny.add_operation(large_op(0));
+        tiny.add_operation(large_op(1));
+        assert_eq!(tiny.get_all()[0].operation_id, "op1");
+        assert_eq!(tiny.count(), 1);
+    }
+
     #[test]
     fn test_filter_by_type() {
         let log = JJOperationLog::new(100);
@@ -1445,4 +2826,718 @@ mod tests {
         let remote = log.remote_operations();
         assert_eq!(remote.len(), 2);
     }
+
+    #[test]
+    fn test_metrics() {
+        let log = JJOperationLog::new(100);
+        for duration in [100, 200, 300] {
+            log.add_operation(
+                JJOperation::builder()
+                    .operation_type(OperationType::Commit)
+                    .duration_ms(duration)
+                    .build(),
+            );
+        }
+        log.add_operation(
+            JJOperation::builder()
+                .operation_type(OperationType::Rebase)
+                .duration_ms(1000)
+                .build(),
+        );
+        log.add_operation(
+            JJOperation::builder()
+                .operation_type(OperationType::Push)
+                .duration_ms(400)
+                .failed("Network error".to_string())
+                .build(),
+        );
+
+        let metrics = log.metrics();
+        assert_eq!(metrics.total_operations, 5);
+        assert_eq!(metrics.failed_operations, 1);
+        assert!((metrics.success_rate - 0.8).abs() < 0.001);
+        assert!((metrics.avg_duration_ms - 400.0).abs() < 0.001);
+        assert_eq!(metrics.p95_duration_ms, 1000.0);
+        assert_eq!(metrics.remote_operations, 1);
+        assert_eq!(metrics.history_modifying_operations, 4);
+        assert_eq!(metrics.by_type.get("Commit"), Some(&3));
+        assert_eq!(metrics.by_type.get("Rebase"), Some(&1));
+        assert_eq!(metrics.by_type.get("Push"), Some(&1));
+    }
+
+    #[test]
+    fn test_concurrent_readers_and_writer() {
+        let log = JJOperationLog::new(1000);
+
+        let writer = {
+            let log = log.clone();
+            std::thread::spawn(move || {
+                for i in 0..200 {
+                    log.add_operation(
+                        JJOperation::builder()
+                            .operation_id(format!("op{}", i))
+                            .operation_type(OperationType::Commit)
+                            .user("alice".to_string())
+                            .build(),
+                    );
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let log = log.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        let _ = log.statistics();
+                        let _ = log.get_user_operations(10);
+                        let _ = log.get_recent(5);
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(log.count(), 200);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let log = JJOperationLog::new(100);
+        for ts in [
+            "2024-01-01T10:05:00+00:00",
+            "2024-01-01T10:30:00+00:00",
+            "2024-01-01T12:10:00+00:00",
+        ] {
+            let mut op = JJOperation::builder()
+                .operation_type(OperationType::Commit)
+                .build();
+            op.timestamp = ts.to_string();
+            log.add_operation(op);
+        }
+
+        let histogram = log.histogram(Duration::hours(1));
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram[0].0.to_rfc3339(), "2024-01-01T10:00:00+00:00");
+        assert_eq!(histogram[0].1, 2);
+        assert_eq!(histogram[1].0.to_rfc3339(), "2024-01-01T12:00:00+00:00");
+        assert_eq!(histogram[1].1, 1);
+
+        // Empty buckets are not materialised, however fine the bucket size
+        let mut ancient = JJOperation::builder().build();
+        ancient.timestamp = "1970-01-01T00:00:00+00:00".to_string();
+        log.add_operation(ancient);
+        assert_eq!(log.histogram(Duration::milliseconds(1)).len(), 4);
+
+        assert!(JJOperationLog::new(10).histogram(Duration::hours(1)).is_empty());
+    }
+
+    #[test]
+    fn test_estimated_bytes_tracks_changes() {
+        let log = JJOperationLog::new(3);
+        let exact = |log: &JJOperationLog| log.get_all().iter().map(JJOperation::estimated_size).sum::<usize>();
+        let old = (Utc::now() - Duration::hours(48)).to_rfc3339();
+        for (i, op_type) in [OperationType::Snapshot, OperationType::Commit, OperationType::Rebase, OperationType::Undo]
+            .into_iter()
+            .enumerate()
+        {
+            let mut op = JJOperation::builder()
+                .operation_type(op_type)
+                .command(format!("jj {}", "x".repeat(i * 10)))
+                .build();
+            op.timestamp = old.clone();
+            log.add_operation(op);
+            assert_eq!(log.estimated_bytes(), exact(&log));
+        }
+
+        log.mark_undone_operations();
+        assert_eq!(log.estimated_bytes(), exact(&log));
+
+        let keypair = crate::crypto::generate_signing_keypair();
+        log.sign_all_operations(&keypair.secret_key, &keypair.public_key).unwrap();
+        assert_eq!(log.estimated_bytes(), exact(&log));
+
+        log.add_operation(JJOperation::builder().operation_type(OperationType::Snapshot).build());
+        let mut snapshot = JJOperation::builder().operation_type(OperationType::Snapshot).build();
+        snapshot.timestamp = old;
+        log.add_operation(snapshot);
+        assert_eq!(log.compact(Duration::hours(24)), 1);
+        assert_eq!(log.estimated_bytes(), exact(&log));
+
+        log.clear();
+        assert_eq!(log.estimated_bytes(), 0);
+    }
+
+    #[test]
+    fn test_compact() {
+        let log = JJOperationLog::new(100);
+        let old = (Utc::now() - Duration::hours(48)).to_rfc3339();
+        let fresh = (Utc::now() - Duration::minutes(5)).to_rfc3339();
+        for (op_type, ts) in [
+            (OperationType::Snapshot, &old),
+            (OperationType::Commit, &old),
+            (OperationType::Snapshot, &fresh),
+            (OperationType::Snapshot, &old),
+            (OperationType::Describe, &fresh),
+        ] {
+            let mut op = JJOperation::builder().operation_type(op_type).build();
+            op.timestamp = ts.clone();
+            log.add_operation(op);
+        }
+
+        assert_eq!(log.compact(Duration::hours(24)), 2);
+
+        let remaining: Vec<String> = log.get_all().into_iter().map(|op| op.operation_type).collect();
+        assert_eq!(remaining, vec!["Commit", "Snapshot", "Describe"]);
+        assert_eq!(log.compact(Duration::hours(24)), 0);
+    }
+
+    fn op_at(timestamp: String) -> JJOperation {
+        let mut op = JJOperation::builder().operation_type(OperationType::Commit).build();
+        op.timestamp = timestamp;
+        op
+    }
+
+    #[test]
+    fn test_operation_age() {
+        let hour_ago = op_at((Utc::now() - Duration::hours(1)).to_rfc3339());
+        let age = hour_ago.age();
+        assert!(age >= Duration::hours(1) && age < Duration::hours(1) + Duration::minutes(1));
+
+        let future = op_at((Utc::now() + Duration::hours(1)).to_rfc3339());
+        assert_eq!(future.age(), Duration::zero());
+        assert_eq!(op_at("not a time".to_string()).age(), Duration::zero());
+    }
+
+    #[test]
+    fn test_operation_elapsed_since() {
+        let first = op_at("2024-01-01T10:00:00+00:00".to_string());
+        let second = op_at("2024-01-01T11:30:00+01:00".to_string());
+
+        assert_eq!(second.elapsed_since(&first), Duration::minutes(30));
+        assert_eq!(first.elapsed_since(&second), Duration::zero());
+    }
+
+    #[test]
+    fn test_statistics_rate_and_failure_trend() {
+        let log = JJOperationLog::new(100);
+        let start = Utc::now() - Duration::hours(4);
+        // Eight successes over the first three hours, then two of three failing
+        for i in 0..8 {
+            log.add_operation(op_at((start + Duration::minutes(i * 20)).to_rfc3339()));
+        }
+        for (minutes, success) in [(200, true), (220, false), (230, false)] {
+            let mut op = op_at((start + Duration::minutes(minutes)).to_rfc3339());
+            op.success = success;
+            log.add_operation(op);
+        }
+
+        let stats = log.statistics();
+
+        // 11 operations over 230 minutes
+        let rate = stats.operations_per_hour.unwrap();
+        assert!((rate - 11.0 / (230.0 / 60.0)).abs() < 1e-9, "{}", rate);
+        let trend = stats.failure_trend.unwrap();
+        assert!((trend - (2.0 / 3.0 - 2.0 / 11.0)).abs() < 1e-9, "{}", trend);
+        assert!(trend > 0.0);
+    }
+
+    #[test]
+    fn test_statistics_rate_needs_an_hour() {
+        let log = JJOperationLog::new(100);
+        log.add_operation(op_at((Utc::now() - Duration::minutes(30)).to_rfc3339()));
+        log.add_operation(op_at(Utc::now().to_rfc3339()));
+
+        let stats = log.statistics();
+
+        assert_eq!(stats.operations_per_hour, None);
+        assert_eq!(stats.failure_trend, None);
+        assert_eq!(JJOperationLog::new(10).statistics().operations_per_hour, None);
+    }
+
+    #[test]
+    fn test_operations_older_than() {
+        let log = JJOperationLog::new(100);
+        for ts in [
+            (Utc::now() - Duration::days(3)).to_rfc3339(),
+            (Utc::now() - Duration::minutes(10)).to_rfc3339(),
+            (Utc::now() + Duration::hours(1)).to_rfc3339(),
+            "garbage".to_string(),
+        ] {
+            log.add_operation(op_at(ts));
+        }
+
+        assert_eq!(log.operations_older_than(Duration::days(1)).len(), 1);
+        assert_eq!(log.operations_older_than(Duration::minutes(5)).len(), 2);
+    }
+
+    fn log_with_ids(ids: &[&str]) -> JJOperationLog {
+        let log = JJOperationLog::new(100);
+        for id in ids {
+            log.add_operation(JJOperation::builder().operation_id(id.to_string()).build());
+        }
+        log
+    }
+
+    fn page_ids(log: &JJOperationLog, offset: usize, limit: usize) -> Vec<String> {
+        log.get_page(offset, limit).into_iter().map(|op| op.operation_id).collect()
+    }
+
+    #[test]
+    fn test_jsonl_skips_partial_last_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ops.jsonl");
+        let first = JJOperation::builder().operation_id("op1".to_string()).build();
+        JJOperationLog::append_jsonl(&path, &first).unwrap();
+        // Simulate a crash in the middle of writing the second record
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"id":"trunc"#).unwrap();
+
+        let log = JJOperationLog::new(100);
+        assert_eq!(log.load_jsonl(&path).unwrap(), 1);
+
+        // The next append starts on its own line and survives a reload
+        let third = JJOperation::builder().operation_id("op3".to_string()).build();
+        JJOperationLog::append_jsonl(&path, &third).unwrap();
+        let reloaded = JJOperationLog::new(100);
+        assert_eq!(reloaded.load_jsonl(&path).unwrap(), 2);
+        assert!(reloaded.find_by_id("op3").is_some());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_round_trip() {
+        let log = JJOperationLog::new(100);
+        for i in 0..20 {
+            let mut op = JJOperation::builder()
+                .operation_type(if i % 2 == 0 { OperationType::Commit } else { OperationType::GitFetch })
+                .command(format!("jj commit -m step-{}", i))
+                .user("agent".to_string())
+                .duration_ms(i * 10)
+                .build();
+            op.set_metadata("step".to_string(), i.to_string());
+            log.add_operation(op);
+        }
+
+        let bytes = log.export_cbor().unwrap();
+        let json: usize = log.get_all().iter().map(|op| serde_json::to_vec(op).unwrap().len() + 1).sum();
+        assert!(bytes.len() < json, "{} >= {}", bytes.len(), json);
+
+        let restored = JJOperationLog::new(100);
+        assert_eq!(restored.import_cbor(&bytes).unwrap(), 20);
+        let as_json = |log: &JJOperationLog| -> Vec<serde_json::Value> {
+            log.get_all().iter().map(|op| serde_json::to_value(op).unwrap()).collect()
+        };
+        assert_eq!(as_json(&restored), as_json(&log));
+
+        assert!(matches!(
+            restored.import_cbor(&bytes[..bytes.len() / 2]),
+            Err(JJError::SerializationError(_))
+        ));
+        assert_eq!(restored.len(), 20);
+    }
+
+    #[test]
+    fn test_load_missing_jsonl() {
+        let log = JJOperationLog::new(100);
+        assert_eq!(log.load_jsonl(Path::new("/nonexistent/ops.jsonl")).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_operation_type_from_description() {
+        for (description, expected) in [
+            ("snapshot working copy", OperationType::Snapshot),
+            ("describe commit 0123456789abcdef", OperationType::Describe),
+            ("new empty commit", OperationType::New),
+            ("rebase commit 0123456789abcdef", OperationType::Rebase),
+            ("squash commits into 0123456789abcdef", OperationType::Squash),
+            ("absorb changes into 2 commits", OperationType::Absorb),
+            ("undo operation 0123456789abcdef", OperationType::Undo),
+            ("fetch from git remote(s) origin", OperationType::GitFetch),
+            ("push all tracked bookmarks to git remote origin", OperationType::GitPush),
+            ("import git refs", OperationType::GitImport),
+            ("create bookmark main pointing to commit 0123", OperationType::Bookmark),
+            ("delete bookmark old-feature", OperationType::BranchDelete),
+            ("add workspace 'default'", OperationType::Workspace),
+            ("sign commits", OperationType::Sign),
+            ("fixed 2 commits of 5 checked", OperationType::Fix),
+            ("something new in jj", OperationType::Unknown),
+        ] {
+            assert_eq!(OperationType::from_description(description), expected, "{}", description);
+        }
+    }
+
+    #[test]
+    fn test_metrics_to_prometheus() {
+        let log = JJOperationLog::new(10);
+        for (op_type, duration, success) in [
+            (OperationType::Commit, 5, true),
+            (OperationType::Commit, 120, true),
+            (OperationType::GitPush, 4000, false),
+        ] {
+            log.add_operation(
+                JJOperation::builder()
+                    .operation_type(op_type)
+                    .duration_ms(duration)
+                    .build()
+                    .with_success(success),
+            );
+        }
+
+        let text = log.metrics().to_prometheus();
+
+        assert!(!text.contains("counter"));
+        assert!(text.contains("# TYPE jj_operations gauge\n"));
+        assert!(text.contains("jj_operations{type=\"Commit\"} 2\n"));
+        assert!(text.contains("jj_operations{type=\"GitPush\"} 1\n"));
+        assert!(text.contains("jj_operations_succeeded 2\n"));
+        assert!(text.contains("jj_operations_failed 1\n"));
+        assert!(text.contains("jj_operation_duration_ms_bucket{le=\"10\"} 1\n"));
+        assert!(text.contains("jj_operation_duration_ms_bucket{le=\"250\"} 2\n"));
+        assert!(text.contains("jj_operation_duration_ms_bucket{le=\"5000\"} 3\n"));
+        assert!(text.contains("jj_operation_duration_ms_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("jj_operation_duration_ms_sum 4125\n"));
+        assert!(text.contains("jj_operation_duration_ms_count 3\n"));
+    }
+
+    #[test]
+    fn test_prometheus_escapes_label_values() {
+        let metrics = OperationMetrics {
+            total_operations: 1,
+            by_type: HashMap::from([("we\"ird\\type\n".to_string(), 1)]),
+            ..Default::default()
+        };
+
+        assert!(metrics
+            .to_prometheus()
+            .contains("jj_operations{type=\"we\\\"ird\\\\type\\n\"} 1\n"));
+    }
+
+    #[test]
+    fn test_dedup_by_jj_operation_id() {
+        let log = JJOperationLog::new(10).with_dedup(true);
+        let real = JJOperation::new("e1f2a3b4c5d6".into(), "jj describe".into(), "alice".into(), "host".into());
+
+        log.add_operation(real.clone());
+        log.add_operation(real);
+        for _ in 0..2 {
+            log.add_operation(JJOperation::new(
+                "1714557600@host".into(),
+                "jj status".into(),
+                "alice".into(),
+                "host".into(),
+            ));
+        }
+
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.search("jj status").len(), 2);
+    }
+
+    #[test]
+    fn test_dedup_disabled_by_default() {
+        let log = JJOperationLog::new(10);
+        let real = JJOperation::new("e1f2a3b4c5d6".into(), "jj describe".into(), "alice".into(), "host".into());
+
+        log.add_operation(real.clone());
+        log.add_operation(real);
+
+        assert_eq!(log.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_op_log_text() {
+        let output = "\
+@  e1f2a3b4c5d6 alice@laptop 2024-05-01 10:00:00.000 +02:00 - 2024-05-01 10:00:00.250 +02:00
+│  describe commit 0123456789abcdef0123456789abcdef01234567
+│  args: jj describe -m 'Fix parser'
+○  a1b2c3d4e5f6 bob@ci-runner 5 minutes ago, lasted 8 milliseconds
+│  snapshot working copy
+│  args: jj st
+○  9f8e7d6c5b4a alice@laptop 2024-05-01 09:00:00.000 +02:00 - 2024-05-01 09:00:01.500 +02:00
+│  push all tracked bookmarks to git remote origin
+│  args: jj git push
+○  000000000000 root()
+";
+
+        let ops = JJOperationLog::parse_op_log_text(output);
+
+        assert_eq!(ops.len(), 4);
+        assert_eq!(ops[0].operation_id, "e1f2a3b4c5d6");
+        assert_eq!(ops[0].user, "alice");
+        assert_eq!(ops[0].hostname, "laptop");
+        assert_eq!(ops[0].command, "describe commit 0123456789abcdef0123456789abcdef01234567");
+        assert_eq!(ops[0].operation_type, "Describe");
+        assert_eq!(ops[0].timestamp, "2024-05-01T10:00:00+02:00");
+        assert_eq!(ops[0].duration_ms, 250);
+        assert_eq!(ops[0].get_metadata("args").unwrap(), "jj describe -m 'Fix parser'");
+
+        assert_eq!(ops[1].user, "bob");
+        assert!(ops[1].is_snapshot());
+        assert_eq!(ops[1].duration_ms, 8);
+        assert_eq!(ops[1].get_metadata("time").unwrap(), "5 minutes ago, lasted 8 milliseconds");
+
+        assert_eq!(ops[2].operation_type, "GitPush");
+        assert_eq!(ops[2].duration_ms, 1500);
+
+        assert_eq!(ops[3].operation_id, "000000000000");
+        assert_eq!(ops[3].command, "root()");
+    }
+
+    #[test]
+    fn test_parse_lasted() {
+        assert_eq!(parse_lasted("8 milliseconds"), Some(8));
+        assert_eq!(parse_lasted("1s 250ms"), Some(1250));
+        assert_eq!(parse_lasted("2 minutes"), Some(120_000));
+        assert_eq!(parse_lasted("a while"), None);
+    }
+
+    #[test]
+    fn test_sorted_by_duration_desc() {
+        let log = JJOperationLog::new(10);
+        for (id, duration) in [("a", 50), ("b", 300), ("c", 50), ("d", 10)] {
+            log.add_operation(JJOperation::builder().operation_id(id.into()).duration_ms(duration).build());
+        }
+
+        let ids: Vec<_> = log
+            .sorted_by(OperationSort::ByDuration, true)
+            .into_iter()
+            .map(|op| op.operation_id)
+            .collect();
+
+        // Equal durations keep insertion order
+        assert_eq!(ids, vec!["b", "a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_sorted_by_timestamp_asc() {
+        let log = JJOperationLog::new(10);
+        for (id, timestamp) in [
+            ("late", "2024-05-01T12:00:00+00:00"),
+            ("early", "2024-05-01T10:00:00+02:00"),
+            ("middle", "2024-05-01T11:00:00+00:00"),
+        ] {
+            let mut op = JJOperation::builder().operation_id(id.into()).build();
+            op.timestamp = timestamp.to_string();
+            log.add_operation(op);
+        }
+
+        let ids: Vec<_> = log
+            .sorted_by(OperationSort::ByTimestamp, false)
+            .into_iter()
+            .map(|op| op.operation_id)
+            .collect();
+
+        assert_eq!(ids, vec!["early", "middle", "late"]);
+    }
+
+    #[test]
+    fn test_get_page() {
+        let log = log_with_ids(&["op1", "op2", "op3", "op4", "op5"]);
+
+        assert_eq!(page_ids(&log, 0, 2), vec!["op5", "op4"]);
+        assert_eq!(page_ids(&log, 2, 2), vec!["op3", "op2"]);
+    }
+
+    #[test]
+    fn test_get_page_partial_last_page() {
+        let log = log_with_ids(&["op1", "op2", "op3", "op4", "op5"]);
+
+        assert_eq!(page_ids(&log, 4, 2), vec!["op1"]);
+        assert_eq!(page_ids(&log, 3, usize::MAX), vec!["op2", "op1"]);
+    }
+
+    #[test]
+    fn test_get_page_past_end() {
+        let log = log_with_ids(&["op1", "op2"]);
+
+        assert!(log.get_page(2, 10).is_empty());
+        assert!(log.get_page(usize::MAX, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_operations_after() {
+        let log = log_with_ids(&["op1", "op2", "op3", "op4"]);
+
+        let after: Vec<String> = log
+            .operations_after("op2")
+            .unwrap()
+            .into_iter()
+            .map(|op| op.operation_id)
+            .collect();
+        assert_eq!(after, vec!["op3", "op4"]);
+    }
+
+    #[test]
+    fn test_operations_after_most_recent() {
+        let log = log_with_ids(&["op1", "op2"]);
+
+        assert!(log.operations_after("op2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_operations_after_unknown_id() {
+        let log = log_with_ids(&["op1"]);
+
+        assert_eq!(
+            log.operations_after("missing").unwrap_err(),
+            JJError::OperationNotFound("missing".to_string())
+        );
+    }
+
+    fn op(op_type: OperationType, id: &str, command: &str) -> JJOperation {
+        JJOperation::builder()
+            .operation_type(op_type)
+            .operation_id(id.to_string())
+            .command(command.to_string())
+            .build()
+    }
+
+    #[test]
+    fn test_find_undone_operations() {
+        let log = JJOperationLog::new(100);
+        log.add_operation(op(OperationType::Describe, "op1", "jj describe -m first"));
+        log.add_operation(op(OperationType::Rebase, "op2", "jj rebase -d main"));
+        log.add_operation(op(OperationType::Status, "op3", "jj status"));
+        // parent_id links the signature chain; it does not name the undo target
+        let mut undo = op(OperationType::Undo, "op4", "jj undo");
+        undo.parent_id = Some("op1".to_string());
+        log.add_operation(undo);
+
+        let undone = log.find_undone_operations();
+
+        assert_eq!(undone.len(), 1);
+        assert_eq!(undone[0].operation_id, "op2");
+        assert!(undone[0].has_tag(UNDONE_TAG));
+        // Finding does not modify the log
+        assert!(!log.find_by_id("op2").unwrap().has_tag(UNDONE_TAG));
+    }
+
+    #[test]
+    fn test_find_undone_operations_explicit_and_chained() {
+        let log = JJOperationLog::new(100);
+        log.add_operation(op(OperationType::Describe, "aaa111", "jj describe -m first"));
+        log.add_operation(op(OperationType::New, "bbb222", "jj new"));
+        log.add_operation(op(OperationType::Squash, "ccc333", "jj squash"));
+        log.add_operation(op(OperationType::Undo, "u1", "jj op undo aaa"));
+        log.add_operation(op(OperationType::Undo, "u2", "jj undo"));
+        let mut failed = op(OperationType::Undo, "u3", "jj undo");
+        failed.success = false;
+        log.add_operation(failed);
+
+        let ids: Vec<String> = log
+            .find_undone_operations()
+            .into_iter()
+            .map(|op| op.operation_id)
+            .collect();
+        assert_eq!(ids, vec!["aaa111", "ccc333"]);
+
+        assert_eq!(log.mark_undone_operations(), 2);
+        assert_eq!(log.filter_by_tag(UNDONE_TAG).len(), 2);
+    }
+
+    #[test]
+    fn test_statistics_json_round_trip() {
+        let log = JJOperationLog::new(100);
+        for op_type in [
+            OperationType::Commit,
+            OperationType::Commit,
+            OperationType::Rebase,
+            OperationType::Describe,
+        ] {
+            log.add_operation(JJOperation::builder().operation_type(op_type).build());
+        }
+        let stats = log.statistics();
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(
+            json["by_type"],
+            serde_json::json!({"Commit": 2, "Describe": 1, "Rebase": 1})
+        );
+
+        let back: OperationStatistics = serde_json::from_value(json).unwrap();
+        assert_eq!(back, stats);
+
+        let bad = serde_json::json!({
+            "total": 1, "successful": 1, "failed": 0, "by_type": {"NotAType": 1},
+            "total_duration_ms": 0, "avg_duration_ms": 0, "max_duration_ms": 0
+        });
+        assert!(serde_json::from_value::<OperationStatistics>(bad).is_err());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let log = JJOperationLog::new(100);
+        let receiver = log.subscribe();
+
+        for op_type in [OperationType::New, OperationType::Describe, OperationType::Rebase] {
+            log.add_operation(JJOperation::builder().operation_type(op_type).build());
+        }
+
+        let received: Vec<String> = receiver.try_iter().map(|op| op.operation_type).collect();
+        assert_eq!(received, vec!["New", "Describe", "Rebase"]);
+        assert_eq!(log.dropped_events(), 0);
+    }
+
+    #[test]
+    fn test_subscribe_drops_when_full() {
+        let log = JJOperationLog::new(100);
+        let slow = log.subscribe_with_capacity(1);
+        let dropped = log.subscribe();
+        drop(dropped);
+
+        log.add_operation(JJOperation::builder().operation_type(OperationType::New).build());
+        log.add_operation(JJOperation::builder().operation_type(OperationType::Edit).build());
+
+        // The log itself is unaffected by a slow consumer
+        assert_eq!(log.len(), 2);
+        assert_eq!(slow.try_iter().count(), 1);
+        assert_eq!(log.dropped_events(), 1);
+        assert_eq!(log.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tagging_rules() {
+        let mut push = JJOperation::builder()
+            .operation_type(OperationType::GitPush)
+            .command("jj git push -b main".to_string())
+            .duration_ms(6000)
+            .build();
+        let mut feature_push = JJOperation::builder()
+            .operation_type(OperationType::GitPush)
+            .command("jj git push -b feature".to_string())
+            .failed("rejected".to_string())
+            .build();
+
+        let rules = [
+            TaggingRule::slow(5000),
+            TaggingRule::failed(),
+            TaggingRule::protected_push(vec!["main".to_string()]),
+        ];
+        for rule in &rules {
+            rule.apply(&mut push);
+            rule.apply(&mut feature_push);
+        }
+
+        assert_eq!(push.tags(), ["slow", "protected"]);
+        assert_eq!(feature_push.tags(), ["error"]);
+
+        let log = JJOperationLog::new(10);
+        log.add_operation(push);
+        log.add_operation(feature_push);
+        assert_eq!(log.filter_by_tag("protected").len(), 1);
+        assert_eq!(log.filter_by_tag("error")[0].command, "jj git push -b feature");
+        assert!(log.filter_by_tag("missing").is_empty());
+    }
+
+    #[test]
+    fn test_metrics_empty() {
+        let metrics = JJOperationLog::new(10).metrics();
+        assert_eq!(metrics.total_operations, 0);
+        assert_eq!(metrics.success_rate, 0.0);
+        assert!(metrics.by_type.is_empty());
+    }
 }
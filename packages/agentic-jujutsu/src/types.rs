@@ -94,6 +94,37 @@ impl JJResult {
             Err(JJError::CommandFailed(self.stderr.clone()))
         }
     }
+
+    /// Convert into `Ok(self)` on success or `Err(CommandFailed(stderr))` otherwise
+    pub fn ok_or_err(self) -> Result<JJResult> {
+        if self.success() {
+            Ok(self)
+        } else {
+            Err(JJError::CommandFailed(self.stderr))
+        }
+    }
+
+    /// Iterate over stdout lines
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.stdout.lines()
+    }
+
+    /// Get stdout without surrounding whitespace
+    #[inline]
+    pub fn trimmed_stdout(&self) -> &str {
+        self.stdout.trim()
+    }
+
+    /// Render as a JSON object, e.g. for an MCP tool result
+    ///
+    /// Contains the serialized fields plus a `success` flag. The other result
+    /// types (`JJCommit`, `JJDiff`, `JJConflict`, `JJBranch`, ...) implement
+    /// `Serialize` and go through `serde_json::to_value` directly.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        json["success"] = self.success().into();
+        json
+    }
 }
 
 /// Commit metadata
@@ -154,6 +185,45 @@ pub struct JJCommit {
 
     /// Whether this is an empty commit
     pub is_empty: bool,
+
+    /// Whether this is the working-copy commit (`@`) of the current workspace
+    #[serde(default)]
+    pub is_working_copy: bool,
+
+    /// Whether this commit is hidden (an obsolete version of a rewritten or
+    /// abandoned change, as listed by `jj evolog`)
+    #[serde(default)]
+    pub is_hidden: bool,
+
+    /// Signature verification result, if it was requested
+    #[serde(default)]
+    pub signed: Option<SignatureStatus>,
+}
+
+/// Result of verifying a commit's cryptographic (GPG/SSH) signature
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum SignatureStatus {
+    /// Signed and verified
+    Good,
+    /// Signed, but the signature does not verify
+    Bad,
+    /// Signed by a key that cannot be checked (e.g. not in the keyring)
+    Unknown,
+    /// No signature
+    Unsigned,
+}
+
+impl SignatureStatus {
+    /// Map a jj `signature.status()` value ("none" for unsigned commits)
+    pub fn from_jj(status: &str) -> SignatureStatus {
+        match status.trim().to_lowercase().as_str() {
+            "good" => SignatureStatus::Good,
+            "bad" | "invalid" => SignatureStatus::Bad,
+            "none" | "" => SignatureStatus::Unsigned,
+            _ => SignatureStatus::Unknown,
+        }
+    }
 }
 
 impl JJCommit {
@@ -179,6 +249,9 @@ impl JJCommit {
             is_merge: false,
             has_conflicts: false,
             is_empty: false,
+            is_working_copy: false,
+            is_hidden: false,
+            signed: None,
         }
     }
 
@@ -265,6 +338,7 @@ pub struct JJCommitBuilder {
     is_merge: bool,
     has_conflicts: bool,
     is_empty: bool,
+    is_working_copy: bool,
 }
 
 impl JJCommitBuilder {
@@ -349,6 +423,12 @@ impl JJCommitBuilder {
         self
     }
 
+    /// Mark as the working-copy commit
+    pub fn is_working_copy(mut self, is_working_copy: bool) -> Self {
+        self.is_working_copy = is_working_copy;
+        self
+    }
+
     /// Build the commit
     pub fn build(self) -> JJCommit {
         JJCommit {
@@ -365,6 +445,9 @@ impl JJCommitBuilder {
             is_merge: self.is_merge,
             has_conflicts: self.has_conflicts,
             is_empty: self.is_empty,
+            is_working_copy: self.is_working_copy,
+            is_hidden: false,
+            signed: None,
         }
     }
 }
@@ -404,6 +487,22 @@ pub struct JJBranch {
 
     /// Creation timestamp (ISO 8601 format)
     pub created_at: String,
+
+    /// Remote a local bookmark is tracked by (first one, if several)
+    #[serde(default)]
+    pub tracking_remote: Option<String>,
+
+    /// Whether the bookmark is conflicted (divergent targets, no single `target`)
+    #[serde(default)]
+    pub conflicted: bool,
+
+    /// Commits the local bookmark has that the tracked remote lacks (lower bound)
+    #[serde(default)]
+    pub ahead: Option<u32>,
+
+    /// Commits the tracked remote has that the local bookmark lacks (lower bound)
+    #[serde(default)]
+    pub behind: Option<u32>,
 }
 
 impl JJBranch {
@@ -417,6 +516,10 @@ impl JJBranch {
             is_tracking: false,
             is_current: false,
             created_at: Utc::now().to_rfc3339(),
+            tracking_remote: None,
+            conflicted: false,
+            ahead: None,
+            behind: None,
         }
     }
 
@@ -445,6 +548,146 @@ impl JJBranch {
     }
 }
 
+/// Sync state of a local bookmark relative to its remote counterpart
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum BranchSyncState {
+    /// Local and remote point at the same commit
+    UpToDate,
+    /// Local has commits the remote lacks
+    Ahead,
+    /// Remote has commits the local bookmark lacks
+    Behind,
+    /// Both sides have commits the other lacks
+    Diverged,
+    /// No remote tracking bookmark exists
+    Untracked,
+}
+
+/// Parent edge in a [`CommitGraph`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(object)]
+pub struct CommitEdge {
+    /// Child commit ID
+    pub child: String,
+
+    /// Parent commit ID
+    pub parent: String,
+}
+
+/// Commits plus the parent edges between them
+///
+/// Edges only connect commits that are both in the graph; a commit whose
+/// parents all lie outside it is a root (see [`CommitGraph::roots`]). The
+/// full parent list is still available in each node's `parents`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[napi(object)]
+pub struct CommitGraph {
+    /// Commits in `jj log` order (newest first)
+    pub nodes: Vec<JJCommit>,
+
+    /// Child-to-parent edges; merges have one edge per parent
+    pub edges: Vec<CommitEdge>,
+}
+
+impl CommitGraph {
+    /// Build a graph from commits whose `parents` are filled in
+    pub fn from_commits(nodes: Vec<JJCommit>) -> Self {
+        let ids: std::collections::HashSet<&str> = nodes.iter().map(|c| c.id.as_str()).collect();
+        let edges = nodes
+            .iter()
+            .flat_map(|commit| {
+                commit
+                    .parents
+                    .iter()
+                    .filter(|parent| ids.contains(parent.as_str()))
+                    .map(|parent| CommitEdge {
+                        child: commit.id.clone(),
+                        parent: parent.clone(),
+                    })
+            })
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// IDs of commits with no parent inside the graph
+    pub fn roots(&self) -> Vec<String> {
+        self.nodes
+            .iter()
+            .filter(|commit| !self.edges.iter().any(|edge| edge.child == commit.id))
+            .map(|commit| commit.id.clone())
+            .collect()
+    }
+
+    /// Render as a Graphviz DOT digraph with child -> parent edges
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut dot = String::from("digraph commits {\n");
+        for commit in &self.nodes {
+            let short_id: String = commit.id.chars().take(12).collect();
+            let summary = commit.message.lines().next().unwrap_or_default();
+            // `\n` inside a DOT string is a label line break
+            dot.push_str(&format!(
+                "  \"{}\" [label=\"{}\\n{}\"];\n",
+                escape(&commit.id),
+                escape(&short_id),
+                escape(summary)
+            ));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!("  \"{}\" -> \"{}\";\n", escape(&edge.child), escape(&edge.parent)));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Ahead/behind status of a bookmark against its remote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[napi(object)]
+pub struct BranchStatus {
+    /// Bookmark name
+    pub name: String,
+
+    /// Sync state
+    pub state: BranchSyncState,
+
+    /// Commits on the local bookmark not on the remote
+    pub ahead: u32,
+
+    /// Commits on the remote not on the local bookmark
+    pub behind: u32,
+}
+
+impl BranchStatus {
+    /// Create a status from ahead/behind counts
+    pub fn new(name: String, ahead: u32, behind: u32) -> Self {
+        let state = match (ahead, behind) {
+            (0, 0) => BranchSyncState::UpToDate,
+            (_, 0) => BranchSyncState::Ahead,
+            (0, _) => BranchSyncState::Behind,
+            _ => BranchSyncState::Diverged,
+        };
+        Self {
+            name,
+            state,
+            ahead,
+            behind,
+        }
+    }
+
+    /// Create a status for a bookmark with no remote counterpart
+    pub fn untracked(name: String) -> Self {
+        Self {
+            name,
+            state: BranchSyncState::Untracked,
+            ahead: 0,
+            behind: 0,
+        }
+    }
+}
+
 /// Conflict representation
 ///
 /// Represents a merge conflict with detailed information about conflicting sides.
@@ -469,7 +712,7 @@ pub struct JJConflict {
     /// Path to the conflicted file
     pub path: String,
 
-    /// Number of conflict markers
+    /// Number of conflicted hunks (0 if not known)
     pub num_conflicts: u32,
 
     /// Sides involved in the conflict
@@ -512,6 +755,225 @@ impl JJConflict {
     pub fn num_sides(&self) -> u32 {
         self.sides.len() as u32
     }
+
+    /// Set the hunk count from the file as jj materialized it, with conflict markers
+    ///
+    /// Content with NUL bytes marks the conflict as binary instead.
+    pub fn count_hunks(&mut self, file_content: &str) {
+        if file_content.contains('\0') {
+            self.is_binary = true;
+        } else {
+            self.num_conflicts = parse_conflict_hunks(file_content).len() as u32;
+        }
+    }
+
+    /// Rough amount of merge work: extra sides times conflicted hunks
+    ///
+    /// Unknown counts are treated as the minimum (two sides, one hunk).
+    pub fn severity_score(&self) -> u32 {
+        let sides = self.num_sides().max(2);
+        let hunks = self.num_conflicts.max(1);
+        (sides - 1) * hunks
+    }
+
+    /// Classify how hard this conflict is likely to be to resolve
+    pub fn severity(&self) -> ConflictSeverity {
+        if self.is_resolved {
+            return ConflictSeverity::Low;
+        }
+        match self.severity_score() {
+            score if score >= 6 => ConflictSeverity::High,
+            score if score >= 3 || self.is_binary => ConflictSeverity::Medium,
+            _ => ConflictSeverity::Low,
+        }
+    }
+
+    /// Describe this conflict as JSON for an external resolver or model prompt
+    ///
+    /// `file_content` is the file as jj materialized it (e.g. from
+    /// `JJWrapper::file_at`), with conflict markers in any of jj's styles
+    /// (`diff`, `snapshot` or `git`). Each hunk lists its 1-based marker lines
+    /// and the content and label of every side and base; diff sections are
+    /// expanded into their base and side. Binary conflicts, or content with
+    /// NUL bytes, are summarized by size instead.
+    pub fn to_prompt_json(&self, file_content: &str) -> String {
+        let mut prompt = serde_json::json!({
+            "path": self.path,
+            "conflict_type": self.conflict_type,
+            "num_sides": self.num_sides(),
+        });
+        if self.is_binary || file_content.contains('\0') {
+            prompt["binary"] = serde_json::json!({ "size_bytes": file_content.len() });
+        } else {
+            prompt["hunks"] = serde_json::Value::Array(parse_conflict_hunks(file_content));
+        }
+        prompt.to_string()
+    }
+}
+
+/// One section between jj conflict markers
+enum ConflictSection {
+    /// Literal content of a side or a base
+    Snapshot { label: String, is_base: bool, lines: Vec<String> },
+    /// Diff from a base to a side (jj's default `diff` marker style)
+    Diff { base: String, side: String, lines: Vec<String> },
+}
+
+impl ConflictSection {
+    fn push(&mut self, line: &str) {
+        match self {
+            ConflictSection::Snapshot { lines, .. } | ConflictSection::Diff { lines, .. } => {
+                lines.push(line.to_string())
+            }
+        }
+    }
+}
+
+/// Parse jj conflict markers into the hunk objects used by [`JJConflict::to_prompt_json`]
+fn parse_conflict_hunks(content: &str) -> Vec<serde_json::Value> {
+    let is_marker = |line: &str, c: char| line.len() >= 7 && line.chars().take(7).all(|ch| ch == c);
+    let label_of = |line: &str| {
+        let text = line.trim_start_matches(|ch| "<>+%-|=".contains(ch)).trim();
+        text.strip_prefix("Contents of ").unwrap_or(text).to_string()
+    };
+    let entry = |label: String, lines: Vec<&str>| serde_json::json!({ "label": label, "content": lines.join("\n") });
+
+    let mut hunks = Vec::new();
+    let mut open: Option<(usize, Vec<ConflictSection>)> = None;
+    for (index, line) in content.lines().enumerate() {
+        if is_marker(line, '<') {
+            let label = label_of(line);
+            let mut sections = Vec::new();
+            if !label.starts_with("Conflict") {
+                // git style: the first side's label is on the opening marker
+                sections.push(ConflictSection::Snapshot { label, is_base: false, lines: Vec::new() });
+            }
+            open = Some((index + 1, sections));
+            continue;
+        }
+        let Some((start, sections)) = open.as_mut() else {
+            continue;
+        };
+
+        if is_marker(line, '>') {
+            let (mut sides, mut bases) = (Vec::new(), Vec::new());
+            for section in sections.drain(..) {
+                match section {
+                    ConflictSection::Snapshot { label, is_base, lines } => {
+                        let target = if is_base { &mut bases } else { &mut sides };
+                        target.push(entry(label, lines.iter().map(String::as_str).collect()));
+                    }
+                    ConflictSection::Diff { base, side, lines } => {
+                        // ' ' lines are in both, '-' only in the base, '+' only in the side
+                        let keep = |skip: char| {
+                            lines
+                                .iter()
+                                .filter(|l| !l.starts_with(skip))
+                                .map(|l| l.get(1..).unwrap_or(""))
+                                .collect::<Vec<_>>()
+                        };
+                        bases.push(entry(base, keep('+')));
+                        sides.push(entry(side, keep('-')));
+                    }
+                }
+            }
+            hunks.push(serde_json::json!({
+                "start_line": *start,
+                "end_line": index + 1,
+                "sides": sides,
+                "bases": bases,
+            }));
+            open = None;
+        } else if is_marker(line, '%') {
+            let label = label_of(line);
+            let (base, side) = label
+                .strip_prefix("Changes from ")
+                .and_then(|rest| rest.split_once(" to "))
+                .unwrap_or(("base", "side"));
+            sections.push(ConflictSection::Diff {
+                base: base.to_string(),
+                side: side.to_string(),
+                lines: Vec::new(),
+            });
+        } else if is_marker(line, '+') || is_marker(line, '=') || is_marker(line, '-') || is_marker(line, '|') {
+            let is_base = is_marker(line, '-') || is_marker(line, '|');
+            sections.push(ConflictSection::Snapshot { label: label_of(line), is_base, lines: Vec::new() });
+        } else if let Some(section) = sections.last_mut() {
+            section.push(line);
+        }
+    }
+    hunks
+}
+
+/// How hard a conflict is likely to be to resolve
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum ConflictSeverity {
+    /// Two sides, few hunks
+    Low,
+    /// Several hunks, more than two sides, or binary content
+    Medium,
+    /// Many hunks across several sides
+    High,
+}
+
+/// What happens to the descendants of an abandoned commit
+///
+/// jj always rebases descendants onto the abandoned commit's parents; the
+/// policies differ in whether the descendants keep the abandoned changes.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum AbandonPolicy {
+    /// Rebase descendants, dropping the abandoned changes from them (jj's default)
+    #[default]
+    RebaseDescendants,
+    /// Keep descendants' contents unchanged (`--restore-descendants`)
+    KeepDescendants,
+}
+
+impl AbandonPolicy {
+    /// Extra `jj abandon` flags for this policy
+    pub fn args(&self) -> &'static [&'static str] {
+        match self {
+            AbandonPolicy::RebaseDescendants => &[],
+            AbandonPolicy::KeepDescendants => &["--restore-descendants"],
+        }
+    }
+}
+
+/// Which commits `jj rebase` moves
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(string_enum)]
+pub enum RebaseMode {
+    /// The source commit and all its descendants (`-s`)
+    #[default]
+    Source,
+    /// The whole branch relative to the destination (`-b`)
+    Branch,
+    /// Only the given revision; its descendants stay in place (`-r`)
+    Revision,
+}
+
+impl RebaseMode {
+    /// `jj rebase` flag selecting this mode
+    pub fn flag(&self) -> &'static str {
+        match self {
+            RebaseMode::Source => "-s",
+            RebaseMode::Branch => "-b",
+            RebaseMode::Revision => "-r",
+        }
+    }
+}
+
+/// Order conflicts worst first: by severity, then score, then path
+pub fn rank_conflicts(mut conflicts: Vec<JJConflict>) -> Vec<JJConflict> {
+    conflicts.sort_by(|a, b| {
+        b.severity()
+            .cmp(&a.severity())
+            .then_with(|| b.severity_score().cmp(&a.severity_score()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    conflicts
 }
 
 impl JJConflict {
@@ -615,6 +1077,40 @@ pub struct JJDiff {
 
     /// Diff content (unified diff format)
     pub content: String,
+
+    /// Per-file line statistics
+    #[serde(default)]
+    pub files: Vec<FileDiffStat>,
+
+    /// Parsed hunks in diff order; files without hunk headers have none
+    #[serde(default)]
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// One `@@ -a,b +c,d @@` hunk of a unified diff
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[napi(object)]
+pub struct DiffHunk {
+    /// File the hunk belongs to (new path for renames)
+    pub path: String,
+
+    /// First line of the hunk in the old file
+    pub old_start: u32,
+
+    /// Number of old-file lines covered
+    pub old_lines: u32,
+
+    /// First line of the hunk in the new file
+    pub new_start: u32,
+
+    /// Number of new-file lines covered
+    pub new_lines: u32,
+
+    /// Context after the closing `@@`, usually the enclosing function
+    pub section: String,
+
+    /// Hunk body lines, each keeping its ` `, `+`, `-` or `\` prefix
+    pub lines: Vec<String>,
 }
 
 impl JJDiff {
@@ -628,6 +1124,8 @@ impl JJDiff {
             additions: 0,
             deletions: 0,
             content: String::new(),
+            files: Vec::new(),
+            hunks: Vec::new(),
         }
     }
 
@@ -650,6 +1148,111 @@ impl Default for JJDiff {
     }
 }
 
+/// Line statistics for a single file in a diff
+///
+/// # Examples
+///
+/// ```rust
+/// use agentic_jujutsu::types::{ChangeStatus, FileDiffStat};
+///
+/// let stat = FileDiffStat::new("src/lib.rs".to_string(), ChangeStatus::Modified);
+/// assert_eq!(stat.total_changes(), 0);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[napi(object)]
+pub struct FileDiffStat {
+    /// File path (new path for renames)
+    pub path: String,
+
+    /// Previous path (renames only)
+    pub old_path: Option<String>,
+
+    /// Number of added lines
+    pub additions: u32,
+
+    /// Number of deleted lines
+    pub deletions: u32,
+
+    /// Kind of change
+    pub kind: ChangeStatus,
+
+    /// Whether the file is binary (line counts are zero)
+    pub is_binary: bool,
+}
+
+impl FileDiffStat {
+    /// Create a new file stat with zero line counts
+    pub fn new(path: String, kind: ChangeStatus) -> Self {
+        Self {
+            path,
+            old_path: None,
+            additions: 0,
+            deletions: 0,
+            kind,
+            is_binary: false,
+        }
+    }
+
+    /// Total number of changed lines
+    #[inline]
+    pub fn total_changes(&self) -> u32 {
+        self.additions + self.deletions
+    }
+}
+
+/// Aggregate counts from a `jj diff --stat` summary line
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[napi(object)]
+pub struct DiffSummary {
+    /// Number of files changed
+    pub files_changed: u32,
+
+    /// Number of inserted lines
+    pub insertions: u32,
+
+    /// Number of deleted lines
+    pub deletions: u32,
+}
+
+impl DiffSummary {
+    /// Check if the summary reports no changes
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files_changed == 0 && self.insertions == 0 && self.deletions == 0
+    }
+}
+
+impl std::fmt::Display for DiffSummary {
+    /// Formats like jj's summary line, e.g. `3 files changed, 10 insertions(+), 2 deletions(-)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+            self.files_changed,
+            if self.files_changed == 1 { "" } else { "s" },
+            self.insertions,
+            if self.insertions == 1 { "" } else { "s" },
+            self.deletions,
+            if self.deletions == 1 { "" } else { "s" },
+        )
+    }
+}
+
+/// Parsed `jj diff --stat` output
+///
+/// Per-file line counts are derived from the `+`/`-` histogram, which jj scales
+/// down for large changes, so they are approximate when the histogram is scaled.
+/// The `summary` totals are always exact.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[napi(object)]
+pub struct JJDiffStat {
+    /// Per-file statistics
+    pub files: Vec<FileDiffStat>,
+
+    /// Totals from the summary line
+    pub summary: DiffSummary,
+}
+
 /// Working copy change
 ///
 /// Represents a change in the working copy that hasn't been committed yet.
@@ -694,6 +1297,216 @@ impl JJChange {
     }
 }
 
+/// A file listed under "Working copy changes:" in `jj status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[napi(object)]
+pub struct FileChange {
+    /// File path (new path for renames and copies)
+    pub path: String,
+
+    /// Previous path (renames and copies only)
+    pub old_path: Option<String>,
+
+    /// Kind of change; `Conflicted` for files that are only listed as
+    /// conflicted, e.g. conflicts inherited from a parent
+    pub kind: ChangeStatus,
+
+    /// Whether the file has unresolved conflicts
+    pub conflicted: bool,
+}
+
+impl FileChange {
+    /// Create an unconflicted change
+    pub fn new(path: String, kind: ChangeStatus) -> Self {
+        Self {
+            path,
+            old_path: None,
+            kind,
+            conflicted: false,
+        }
+    }
+}
+
+/// Installed jj version, parsed from `jj --version`
+///
+/// Used to adapt commands to the capabilities of the installed binary.
+///
+/// # Examples
+///
+/// ```rust
+/// use agentic_jujutsu::types::JJVersion;
+///
+/// let version: JJVersion = "jj 0.23.0-1a2b3c4d5e6f".parse().unwrap();
+/// assert_eq!(version.minor, 23);
+/// assert!(version.supports_bookmarks());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[napi(object)]
+pub struct JJVersion {
+    /// Major version
+    pub major: u32,
+
+    /// Minor version
+    pub minor: u32,
+
+    /// Patch version
+    pub patch: u32,
+
+    /// Pre-release identifier (e.g. `rc.1`)
+    pub pre_release: Option<String>,
+
+    /// Git commit hash for development builds
+    pub commit_hash: Option<String>,
+}
+
+impl JJVersion {
+    /// Create a release version
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre_release: None,
+            commit_hash: None,
+        }
+    }
+
+    /// Check if this version is at least `major.minor.patch`
+    ///
+    /// Pre-release and commit suffixes are ignored.
+    pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+
+    /// Check if the `jj bookmark` subcommand is available (>= 0.21)
+    pub fn supports_bookmarks(&self) -> bool {
+        self.at_least(0, 21, 0)
+    }
+
+    /// Check if the deprecated `jj branch` subcommand is still available (< 0.26)
+    pub fn supports_branch_command(&self) -> bool {
+        !self.at_least(0, 26, 0)
+    }
+
+    /// Check if `jj git push` accepts `--allow-new` (>= 0.25)
+    pub fn supports_push_allow_new(&self) -> bool {
+        self.at_least(0, 25, 0)
+    }
+
+    /// Check if `jj file show` / `jj file list` replace `jj cat` / `jj files` (>= 0.19)
+    pub fn supports_file_commands(&self) -> bool {
+        self.at_least(0, 19, 0)
+    }
+
+    /// Check if `jj evolog` replaces `jj obslog` (>= 0.19)
+    pub fn supports_evolog(&self) -> bool {
+        self.at_least(0, 19, 0)
+    }
+}
+
+impl std::fmt::Display for JJVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre_release {
+            write!(f, "-{}", pre)?;
+        }
+        if let Some(hash) = &self.commit_hash {
+            write!(f, "-{}", hash)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for JJVersion {
+    type Err = JJError;
+
+    /// Parse `jj --version` output such as `jj 0.12.0`, `jj 0.25.0-rc.1` or
+    /// `jj 0.23.0-1a2b3c4d5e6f` (development build with commit hash)
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || JJError::ParseError(format!("Invalid jj version: {}", s.trim()));
+
+        let text = s.trim();
+        let text = text.strip_prefix("jj").map(str::trim_start).unwrap_or(text);
+        let token = text.split_whitespace().next().ok_or_else(invalid)?;
+        let token = token.trim_start_matches('v');
+
+        // Build metadata (`+...`) carries no ordering information
+        let token = token.split_once('+').map_or(token, |(core, _)| core);
+        let (core, suffix) = match token.split_once('-') {
+            Some((core, suffix)) => (core, Some(suffix)),
+            None => (token, None),
+        };
+
+        let mut parts = core.split('.');
+        let mut next = || -> Result<u32> {
+            parts
+                .next()
+                .ok_or_else(invalid)?
+                .parse()
+                .map_err(|_| invalid())
+        };
+        let mut version = JJVersion::new(next()?, next()?, next()?);
+
+        if let Some(suffix) = suffix {
+            for part in suffix.split('-').filter(|p| !p.is_empty()) {
+                let is_hash = part.len() >= 7 && part.chars().all(|c| c.is_ascii_hexdigit());
+                if is_hash {
+                    version.commit_hash = Some(part.to_string());
+                } else {
+                    version.pre_release = Some(match version.pre_release.take() {
+                        Some(pre) => format!("{}-{}", pre, part),
+                        None => part.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(version)
+    }
+}
+
+/// A single step in a `JJWrapper::batch` run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BatchOp {
+    /// `jj abandon <revset>`
+    Abandon(String),
+    /// `jj describe <revision> -m <message>`
+    Describe {
+        /// Revision to describe
+        revision: String,
+        /// New description
+        message: String,
+    },
+    /// `jj new [-m <message>]`
+    New(Option<String>),
+    /// `jj edit <revision>`
+    Edit(String),
+    /// Arbitrary jj arguments
+    Raw(Vec<String>),
+}
+
+impl BatchOp {
+    /// Convert to jj command arguments
+    pub fn into_args(self) -> Vec<String> {
+        match self {
+            BatchOp::Abandon(revset) => vec!["abandon".to_string(), revset],
+            BatchOp::Describe { revision, message } => {
+                vec!["describe".to_string(), revision, "-m".to_string(), message]
+            }
+            BatchOp::New(message) => {
+                let mut args = vec!["new".to_string()];
+                if let Some(message) = message {
+                    args.push("-m".to_string());
+                    args.push(message);
+                }
+                args
+            }
+            BatchOp::Edit(revision) => vec!["edit".to_string(), revision],
+            BatchOp::Raw(args) => args,
+        }
+    }
+}
+
 /// Status of a file change
 #[derive(Debug, Serialize, Deserialize)]
 #[napi(string_enum)]
@@ -723,6 +1536,60 @@ mod tests {
         assert_eq!(result.output(), "output");
     }
 
+    #[test]
+    fn test_jj_result_accessors() {
+        let result = JJResult::new("  first\nsecond\n".to_string(), String::new(), 0, 5);
+        assert_eq!(result.lines().collect::<Vec<_>>(), vec!["  first", "second"]);
+        assert_eq!(result.trimmed_stdout(), "first\nsecond");
+    }
+
+    #[test]
+    fn test_jj_result_ok_or_err() {
+        let ok = JJResult::new("done".to_string(), String::new(), 0, 5);
+        assert_eq!(ok.ok_or_err().unwrap().stdout, "done");
+
+        let failed = JJResult::new(String::new(), "Error: no such revision".to_string(), 1, 5);
+        assert_eq!(
+            failed.ok_or_err().unwrap_err(),
+            JJError::CommandFailed("Error: no such revision".to_string())
+        );
+    }
+
+    #[test]
+    fn test_result_types_to_json() {
+        let result = JJResult::new("done\n".into(), String::new(), 0, 12).to_json();
+        assert_eq!(result["stdout"], "done\n");
+        assert_eq!(result["exit_code"], 0);
+        assert_eq!(result["execution_time_ms"], 12);
+        assert_eq!(result["success"], true);
+        assert_eq!(JJResult::new(String::new(), "boom".into(), 1, 0).to_json()["success"], false);
+
+        let mut commit = JJCommit::builder().id("abc123".into()).change_id("xyz789".into()).build();
+        commit.is_working_copy = true;
+        commit.signed = Some(SignatureStatus::Good);
+        let commit = serde_json::to_value(&commit).unwrap();
+        assert_eq!(commit["id"], "abc123");
+        assert_eq!(commit["change_id"], "xyz789");
+        assert_eq!(commit["is_working_copy"], true);
+        assert_eq!(commit["signed"], "Good");
+
+        let mut diff = JJDiff::new();
+        diff.added.push("new.rs".into());
+        let diff = serde_json::to_value(&diff).unwrap();
+        assert_eq!(diff["added"][0], "new.rs");
+        assert!(diff["hunks"].is_array());
+
+        let conflict = JJConflict::builder().path("src/lib.rs".into()).num_conflicts(1).build();
+        let conflict = serde_json::to_value(&conflict).unwrap();
+        assert_eq!(conflict["path"], "src/lib.rs");
+        assert_eq!(conflict["num_conflicts"], 1);
+
+        let branch = serde_json::to_value(JJBranch::new("main".into(), "abc123".into(), false)).unwrap();
+        assert_eq!(branch["name"], "main");
+        assert_eq!(branch["target"], "abc123");
+        assert_eq!(branch["is_remote"], false);
+    }
+
     #[test]
     fn test_commit_builder() {
         let commit = JJCommit::builder()
@@ -812,4 +1679,181 @@ mod tests {
         let conflict2 = JJConflict::new("file1.rs".to_string(), 1, "content".to_string());
         assert_ne!(conflict1.id, conflict2.id);
     }
+
+    #[test]
+    fn test_version_parse_release() {
+        let version: JJVersion = "jj 0.12.0\n".parse().unwrap();
+        assert_eq!(version, JJVersion::new(0, 12, 0));
+        assert!(!version.supports_bookmarks());
+        assert!(version.supports_branch_command());
+    }
+
+    #[test]
+    fn test_version_parse_commit_hash() {
+        let version: JJVersion = "jj 0.23.0-1a2b3c4d5e6f7a8b".parse().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (0, 23, 0));
+        assert_eq!(version.commit_hash.as_deref(), Some("1a2b3c4d5e6f7a8b"));
+        assert!(version.pre_release.is_none());
+        assert!(version.supports_bookmarks());
+    }
+
+    #[test]
+    fn test_version_parse_pre_release() {
+        let version: JJVersion = "jj 0.26.0-rc.1".parse().unwrap();
+        assert_eq!(version.pre_release.as_deref(), Some("rc.1"));
+        assert!(version.commit_hash.is_none());
+        assert!(!version.supports_branch_command());
+
+        let version: JJVersion = "jj 1.0.0-beta-0123abcd".parse().unwrap();
+        assert_eq!(version.pre_release.as_deref(), Some("beta"));
+        assert_eq!(version.commit_hash.as_deref(), Some("0123abcd"));
+        assert_eq!(version.to_string(), "1.0.0-beta-0123abcd");
+    }
+
+    #[test]
+    fn test_version_parse_invalid() {
+        assert!("".parse::<JJVersion>().is_err());
+        assert!("jj".parse::<JJVersion>().is_err());
+        assert!("jj 0.12".parse::<JJVersion>().is_err());
+        assert!("jj x.y.z".parse::<JJVersion>().is_err());
+    }
+
+    fn conflict(path: &str, sides: usize, hunks: u32) -> JJConflict {
+        let mut builder = JJConflict::builder().path(path.to_string()).num_conflicts(hunks);
+        for i in 0..sides {
+            builder = builder.side(format!("side-{}", i));
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_conflict_prompt_json_three_sides() {
+        let content = "\
+fn main() {
+<<<<<<< Conflict 1 of 1
+%%%%%%% Changes from base #1 to side #1
+-    run(1);
++    run(2);
+     done();
++++++++ Contents of side #2
+    run(3);
+    done();
+%%%%%%% Changes from base #2 to side #3
+-    run(1);
++    run(4);
+     done();
+>>>>>>> Conflict 1 of 1 ends
+}
+";
+        let conflict = JJConflict::builder()
+            .path("src/main.rs".to_string())
+            .num_conflicts(1)
+            .side("side #1".to_string())
+            .side("side #2".to_string())
+            .side("side #3".to_string())
+            .conflict_type("content".to_string())
+            .build();
+
+        let json: serde_json::Value = serde_json::from_str(&conflict.to_prompt_json(content)).unwrap();
+
+        assert_eq!(json["path"], "src/main.rs");
+        assert_eq!(json["num_sides"], 3);
+        let hunks = json["hunks"].as_array().unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0]["start_line"].as_u64(), hunks[0]["end_line"].as_u64()), (Some(2), Some(14)));
+        let sides: Vec<(&str, &str)> = hunks[0]["sides"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| (s["label"].as_str().unwrap(), s["content"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            sides,
+            vec![
+                ("side #1", "    run(2);\n    done();"),
+                ("side #2", "    run(3);\n    done();"),
+                ("side #3", "    run(4);\n    done();"),
+            ]
+        );
+        let bases = hunks[0]["bases"].as_array().unwrap();
+        assert_eq!(bases.len(), 2);
+        assert_eq!(bases[1]["label"], "base #2");
+        assert_eq!(bases[1]["content"], "    run(1);\n    done();");
+    }
+
+    #[test]
+    fn test_conflict_prompt_json_binary_is_summarized() {
+        let conflict = JJConflict::builder()
+            .path("logo.png".to_string())
+            .is_binary(true)
+            .build();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&conflict.to_prompt_json("\u{89}PNG\0\0")).unwrap();
+
+        assert_eq!(json["binary"]["size_bytes"], 7);
+        assert!(json.get("hunks").is_none());
+    }
+
+    #[test]
+    fn test_conflict_severity() {
+        assert_eq!(conflict("a", 2, 1).severity(), ConflictSeverity::Low);
+        assert_eq!(conflict("b", 2, 4).severity(), ConflictSeverity::Medium);
+        assert_eq!(conflict("c", 3, 5).severity(), ConflictSeverity::High);
+
+        let mut resolved = conflict("d", 3, 5);
+        resolved.is_resolved = true;
+        assert_eq!(resolved.severity(), ConflictSeverity::Low);
+    }
+
+    #[test]
+    fn test_diff_deserializes_without_files() {
+        let json = r#"{"added":["a.rs"],"modified":[],"deleted":[],"renamed":[],"additions":3,"deletions":0,"content":""}"#;
+
+        let diff: JJDiff = serde_json::from_str(json).unwrap();
+
+        assert_eq!(diff.added, vec!["a.rs"]);
+        assert!(diff.files.is_empty());
+        assert!(diff.hunks.is_empty());
+    }
+
+    #[test]
+    fn test_three_sided_single_hunk_severity() {
+        let content = "\
+fn main() {
+<<<<<<< Conflict 1 of 1
+%%%%%%% Changes from base to side #1
+-    old();
++    one();
++++++++ Contents of side #2
+    two();
++++++++ Contents of side #3
+    three();
+>>>>>>> Conflict 1 of 1 ends
+}
+";
+        let mut conflict = conflict("main.rs", 3, 0);
+        conflict.count_hunks(content);
+
+        assert_eq!(conflict.num_conflicts, 1);
+        assert_eq!(conflict.severity_score(), 2);
+        assert_eq!(conflict.severity(), ConflictSeverity::Low);
+    }
+
+    #[test]
+    fn test_rank_conflicts() {
+        let ranked = rank_conflicts(vec![
+            conflict("small.rs", 2, 1),
+            conflict("messy.rs", 3, 5),
+            conflict("medium.rs", 2, 4),
+            conflict("also_small.rs", 2, 1),
+            conflict("worse.rs", 4, 3),
+        ]);
+
+        let paths: Vec<&str> = ranked.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["messy.rs", "worse.rs", "medium.rs", "also_small.rs", "small.rs"]
+        );
+    }
 }
@@ -2,19 +2,29 @@
 
 use crate::{
     agent_coordination::AgentCoordination,
-    config::JJConfig,
+    config::{BranchCommand, ConfigScope, JJConfig},
     error::{JJError, Result},
-    operations::{JJOperation, JJOperationLog, OperationType},
+    hooks::{
+        commit_message_validator, AsyncHook, HookContext, HookEventType, JJHookEvent,
+        OperationHooks,
+    },
+    operations::{
+        JJOperation, JJOperationLog, OperationCategory, OperationMetrics, OperationType, TaggingRule,
+    },
     reasoning_bank::{ReasoningBank, Trajectory},
-    types::{JJBranch, JJCommit, JJConflict, JJDiff, JJResult},
-    native::execute_jj_command,
+    types::{
+        rank_conflicts, AbandonPolicy, BatchOp, CommitGraph, BranchStatus, ChangeStatus, DiffHunk, DiffSummary, FileChange, FileDiffStat, JJBranch, JJCommit, JJConflict,
+        JJDiff, JJDiffStat, JJResult, JJVersion, RebaseMode, SignatureStatus,
+    },
+    native::{CommandExecutor, NativeExecutor},
 };
 use chrono::Utc;
 use napi_derive::napi;
 use std::collections::HashMap;
+use std::ops::ControlFlow;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::Write;
 
@@ -96,6 +106,252 @@ fn validate_command_args(args: &[&str]) -> Result<()> {
     Ok(())
 }
 
+/// Editor command that exits successfully without changing the file
+///
+/// jj splits the command on whitespace and appends the file to edit.
+const KEEP_DESCRIPTION_EDITOR: &str = if cfg!(windows) { "cmd /c rem" } else { "true" };
+
+/// Commit fields shared by the JSON commit templates, without the closing brace
+macro_rules! commit_json_fields {
+    () => {
+        concat!(
+            r#"'{"commit_id":' ++ stringify(commit_id).escape_json()"#,
+            r#" ++ ',"change_id":' ++ stringify(change_id).escape_json()"#,
+            r#" ++ ',"description":' ++ description.escape_json()"#,
+            r#" ++ ',"author":' ++ author.name().escape_json()"#,
+            r#" ++ ',"author_email":' ++ stringify(author.email()).escape_json()"#,
+            r#" ++ ',"timestamp":' ++ author.timestamp().format("%Y-%m-%dT%H:%M:%S%:z").escape_json()"#,
+            r#" ++ ',"empty":' ++ if(empty, "true", "false")"#,
+            r#" ++ ',"conflict":' ++ if(conflict, "true", "false")"#,
+            r#" ++ ',"working_copy":' ++ if(current_working_copy, "true", "false")"#,
+        )
+    };
+}
+
+/// `jj log` template emitting one JSON object per commit
+///
+/// Avoids `|` lambdas because `validate_command_args` rejects pipe characters.
+const COMMIT_JSON_TEMPLATE: &str = concat!(commit_json_fields!(), r#" ++ "}\n""#);
+
+/// [`COMMIT_JSON_TEMPLATE`] plus the signature status ("none" when unsigned)
+///
+/// Kept separate because verifying signatures can be slow (it may invoke
+/// gpg/ssh-keygen per commit) and needs a jj with signature templates.
+const SIGNED_COMMIT_JSON_TEMPLATE: &str = concat!(
+    commit_json_fields!(),
+    r#" ++ ',"signature":"' ++ if(signature, signature.status(), "none") ++ '"'"#,
+    r#" ++ "}\n""#,
+);
+
+/// [`COMMIT_JSON_TEMPLATE`] plus whether the commit is hidden, for `jj evolog`
+const EVOLOG_JSON_TEMPLATE: &str = concat!(
+    commit_json_fields!(),
+    r#" ++ ',"hidden":' ++ if(hidden, "true", "false")"#,
+    r#" ++ "}\n""#,
+);
+
+/// `jj op log` template emitting one JSON object per operation
+const OP_JSON_TEMPLATE: &str = concat!(
+    r#"'{"id":' ++ stringify(id).escape_json()"#,
+    r#" ++ ',"description":' ++ description.escape_json()"#,
+    r#" ++ ',"user":' ++ user.escape_json()"#,
+    r#" ++ ',"start":' ++ time.start().format("%Y-%m-%dT%H:%M:%S%.3f%:z").escape_json()"#,
+    r#" ++ ',"end":' ++ time.end().format("%Y-%m-%dT%H:%M:%S%.3f%:z").escape_json()"#,
+    r#" ++ "}\n""#,
+);
+
+/// `jj bookmark list` template emitting one JSON object per local or remote bookmark
+///
+/// Tracking counts are only defined for tracked remote bookmarks, hence the
+/// `if(tracked, ...)` guards; divergent bookmarks have no `normal_target`.
+const BOOKMARK_JSON_TEMPLATE: &str = concat!(
+    r#"'{"name":' ++ name.escape_json()"#,
+    r#" ++ ',"remote":' ++ stringify(remote).escape_json()"#,
+    r#" ++ ',"present":' ++ if(present, "true", "false")"#,
+    r#" ++ ',"conflict":' ++ if(conflict, "true", "false")"#,
+    r#" ++ ',"target":' ++ stringify(if(normal_target, normal_target.commit_id(), "")).escape_json()"#,
+    r#" ++ ',"tracked":' ++ if(tracked, "true", "false")"#,
+    r#" ++ ',"remote_ahead":' ++ if(tracked, tracking_ahead_count().lower(), "0")"#,
+    r#" ++ ',"remote_behind":' ++ if(tracked, tracking_behind_count().lower(), "0")"#,
+    r#" ++ "}\n""#,
+);
+
+/// Bookmark record produced by [`BOOKMARK_JSON_TEMPLATE`]
+#[derive(Debug, serde::Deserialize)]
+struct BookmarkJson {
+    name: String,
+    #[serde(default)]
+    remote: String,
+    #[serde(default = "default_true")]
+    present: bool,
+    #[serde(default)]
+    conflict: bool,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    tracked: bool,
+    /// Commits on the remote bookmark missing from the local one
+    #[serde(default)]
+    remote_ahead: u32,
+    /// Commits on the local bookmark missing from the remote one
+    #[serde(default)]
+    remote_behind: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Number of jj operations read per [`JJWrapper::watch`] poll
+pub const WATCH_POLL_LIMIT: usize = 100;
+
+/// Operation record produced by [`OP_JSON_TEMPLATE`]
+#[derive(Debug, serde::Deserialize)]
+struct OpJson {
+    id: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    user: String,
+    start: String,
+    #[serde(default)]
+    end: String,
+}
+
+impl From<OpJson> for JJOperation {
+    fn from(json: OpJson) -> Self {
+        // jj records the user as "name@host"
+        let (user, hostname) = match json.user.rsplit_once('@') {
+            Some((user, host)) => (user.to_string(), host.to_string()),
+            None => (json.user.clone(), String::new()),
+        };
+        let mut operation = JJOperation::new(json.id, json.description, user, hostname);
+        operation.operation_type = OperationType::from_description(&operation.command).as_string();
+        let start = chrono::DateTime::parse_from_rfc3339(&json.start);
+        let end = chrono::DateTime::parse_from_rfc3339(&json.end);
+        if let (Ok(start), Ok(end)) = (start, end) {
+            operation.duration_ms = (end - start).num_milliseconds().max(0) as u32;
+        }
+        operation.timestamp = json.start;
+        operation
+    }
+}
+
+/// Owned argument list for a jj invocation
+///
+/// Takes `String` and `&str` alike and appends optional flags only when set,
+/// so methods don't need to build up a `Vec` by hand.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Args(Vec<String>);
+
+impl Args {
+    /// Start from a subcommand (possibly several words, e.g. `["git", "push"]`)
+    fn new<S: Into<String>>(subcommand: impl IntoIterator<Item = S>) -> Self {
+        Args(subcommand.into_iter().map(Into::into).collect())
+    }
+
+    /// Append a single argument
+    fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.0.push(arg.into());
+        self
+    }
+
+    /// Append several arguments in order
+    fn args<S: Into<String>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.0.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Append `arg` if present
+    fn opt_arg(self, arg: Option<impl Into<String>>) -> Self {
+        match arg {
+            Some(arg) => self.arg(arg),
+            None => self,
+        }
+    }
+
+    /// Append `flag value`
+    fn flag(self, flag: &str, value: impl Into<String>) -> Self {
+        self.arg(flag).arg(value)
+    }
+
+    /// Append `flag value` if `value` is present
+    fn opt_flag(self, flag: &str, value: Option<impl Into<String>>) -> Self {
+        match value {
+            Some(value) => self.flag(flag, value),
+            None => self,
+        }
+    }
+
+    /// Append a value-less flag when `enabled`
+    fn switch(self, flag: &str, enabled: bool) -> Self {
+        if enabled {
+            self.arg(flag)
+        } else {
+            self
+        }
+    }
+}
+
+impl From<Args> for Vec<String> {
+    fn from(args: Args) -> Self {
+        args.0
+    }
+}
+
+/// Quote `value` as a TOML basic string, as `--config` values are parsed as TOML
+fn toml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Commit record produced by [`COMMIT_JSON_TEMPLATE`]
+#[derive(Debug, serde::Deserialize)]
+struct CommitJson {
+    /// Only present with [`SIGNED_COMMIT_JSON_TEMPLATE`]
+    #[serde(default)]
+    signature: Option<String>,
+    commit_id: String,
+    change_id: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    author_email: String,
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    empty: bool,
+    #[serde(default)]
+    conflict: bool,
+    #[serde(default)]
+    working_copy: bool,
+    /// Only present with [`EVOLOG_JSON_TEMPLATE`]
+    #[serde(default)]
+    hidden: bool,
+}
+
+impl From<CommitJson> for JJCommit {
+    fn from(json: CommitJson) -> Self {
+        let mut commit = JJCommit::new(
+            json.commit_id,
+            json.change_id,
+            json.description.trim_end().to_string(),
+            json.author,
+            json.author_email,
+        );
+        if !json.timestamp.is_empty() {
+            commit.timestamp = json.timestamp;
+        }
+        commit.is_empty = json.empty;
+        commit.has_conflicts = json.conflict;
+        commit.is_working_copy = json.working_copy;
+        commit.is_hidden = json.hidden;
+        commit.signed = json.signature.as_deref().map(SignatureStatus::from_jj);
+        commit
+    }
+}
+
 /// Main wrapper for Jujutsu operations
 #[napi]
 #[derive(Clone)]
@@ -105,8 +361,19 @@ pub struct JJWrapper {
     reasoning_bank: Arc<ReasoningBank>,
     current_trajectory: Arc<Mutex<Option<Trajectory>>>,
     agent_coordination: Arc<tokio::sync::Mutex<Option<AgentCoordination>>>,
+    hooks: Arc<Mutex<OperationHooks>>,
+    executor: Arc<dyn CommandExecutor>,
+    detected_version: Arc<tokio::sync::OnceCell<JJVersion>>,
+    repo_root: Arc<tokio::sync::OnceCell<PathBuf>>,
+    tagging_rules: Arc<Mutex<Vec<TaggingRule>>>,
+    output_cache: Arc<Mutex<OutputCache>>,
+    op_permits: Arc<tokio::sync::Semaphore>,
+    current_task: Arc<Mutex<Option<String>>>,
 }
 
+/// Output of read-only commands keyed by their arguments, with the time it was stored
+type OutputCache = HashMap<Vec<String>, (Instant, String)>;
+
 #[napi]
 impl JJWrapper {
     /// Create a new JJWrapper with default configuration
@@ -127,10 +394,22 @@ impl JJWrapper {
     /// Create a new JJWrapper with custom configuration
     #[napi]
     pub fn with_config(config: JJConfig) -> napi::Result<JJWrapper> {
-        let operation_log = Arc::new(Mutex::new(JJOperationLog::new(config.max_log_entries as usize)));
+        let operation_log = Self::load_operation_log(&config)
+            .map_err(|e| napi::Error::from_reason(format!("Failed to load operation log: {}", e)))?;
+        let operation_log = Arc::new(Mutex::new(operation_log));
         let reasoning_bank = Arc::new(ReasoningBank::new(1000)); // Store up to 1000 trajectories
         let current_trajectory = Arc::new(Mutex::new(None));
         let agent_coordination = Arc::new(tokio::sync::Mutex::new(None));
+        let hooks = Self::config_hooks(&config)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid configuration: {}", e)))?;
+        let hooks = Arc::new(Mutex::new(hooks));
+        let executor: Arc<dyn CommandExecutor> = Arc::new(NativeExecutor);
+        let detected_version = Arc::new(tokio::sync::OnceCell::new());
+        let repo_root = Arc::new(tokio::sync::OnceCell::new());
+        let tagging_rules = Arc::new(Mutex::new(Vec::new()));
+        let output_cache = Arc::new(Mutex::new(HashMap::new()));
+        let op_permits = Arc::new(tokio::sync::Semaphore::new(config.max_concurrent_ops.max(1) as usize));
+        let current_task = Arc::new(Mutex::new(None));
 
         Ok(JJWrapper {
             config,
@@ -138,6 +417,14 @@ impl JJWrapper {
             reasoning_bank,
             current_trajectory,
             agent_coordination,
+            hooks,
+            executor,
+            detected_version,
+            repo_root,
+            tagging_rules,
+            output_cache,
+            op_permits,
+            current_task,
         })
     }
 
@@ -148,62 +435,36 @@ impl JJWrapper {
     }
 
     /// Get operation log statistics as JSON string
+    ///
+    /// Serialized form of [`get_metrics`](Self::get_metrics), kept for backward compatibility.
     #[napi(js_name = "getStats")]
     pub fn get_stats(&self) -> String {
-        let log = self.operation_log.lock().unwrap();
-        serde_json::json!({
-            "total_operations": log.count(),
-            "avg_duration_ms": log.avg_duration_ms(),
-            "success_rate": log.success_rate(),
-        })
-        .to_string()
+        serde_json::to_string(&self.get_metrics()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Get a typed metrics snapshot of the operation log
+    #[napi(js_name = "getMetrics")]
+    pub fn get_metrics(&self) -> OperationMetrics {
+        self.operation_log.lock().unwrap().metrics()
+    }
+
+    /// Render operation metrics in Prometheus text exposition format
+    ///
+    /// Serve the returned text from a `/metrics` endpoint to let a sidecar
+    /// scrape agent VCS activity.
+    #[napi(js_name = "exportPrometheus")]
+    pub fn export_prometheus(&self) -> String {
+        self.get_metrics().to_prometheus()
     }
 
     /// Execute a jj command and return the result
     #[napi]
     pub async fn execute(&self, args: Vec<String>) -> napi::Result<JJResult> {
-        // Convert Vec<String> to Vec<&str> for internal processing
-        let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-
-        // Validate arguments for security
-        validate_command_args(&args_refs)
-            .map_err(|e| napi::Error::from_reason(format!("Invalid arguments: {}", e)))?;
-
-        let start = Instant::now();
-        let command = format!("jj {}", args.join(" "));
-        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
-        let username = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
-
-        // Execute command with timeout
-        let timeout = std::time::Duration::from_millis(self.config.timeout_ms as u64);
-        let result = execute_jj_command(&self.config.jj_path, &args_refs, timeout).await;
-
-        // Log the operation (ALWAYS, even if failed)
-        let duration_ms = start.elapsed().as_millis() as u64;
-        let mut operation = JJOperation::new(
-            format!("{}@{}", Utc::now().timestamp(), hostname),
-            command.clone(),
-            username.clone(),
-            hostname.clone(),
-        );
-
-        operation.operation_type = Self::detect_operation_type(&args_refs).as_string();
-        operation.duration_ms = duration_ms as u32;
-
-        match &result {
-            Ok(output) => {
-                operation.success = true;
-                let jj_result = JJResult::new(output.clone(), String::new(), 0, duration_ms);
-                self.operation_log.lock().unwrap().add_operation(operation);
-                Ok(jj_result)
-            }
-            Err(e) => {
-                operation.success = false;
-                operation.error = Some(e.to_string());
-                self.operation_log.lock().unwrap().add_operation(operation);
-                Err(napi::Error::from_reason(format!("Command failed: {}", e)))
-            }
-        }
+        self.execute_checked(args).await.map_err(|e| match e {
+            JJError::InvalidConfig(_) => napi::Error::from_reason(format!("Invalid arguments: {}", e)),
+            JJError::HookVetoed(_) => napi::Error::from_reason(e.to_string()),
+            _ => napi::Error::from_reason(format!("Command failed: {}", e)),
+        })
     }
 
     /// Detect operation type from command arguments
@@ -214,31 +475,83 @@ impl JJWrapper {
 
         match args[0] {
             "describe" => OperationType::Describe,
+            "new" if Self::positional_arg_count(&args[1..]) > 1 => OperationType::Merge,
             "new" => OperationType::New,
             "edit" => OperationType::Edit,
             "abandon" => OperationType::Abandon,
             "rebase" => OperationType::Rebase,
             "squash" => OperationType::Squash,
+            "absorb" => OperationType::Absorb,
+            "workspace" => OperationType::Workspace,
+            "file" => OperationType::File,
+            "sign" | "unsign" => OperationType::Sign,
+            "fix" => OperationType::Fix,
+            "split" => OperationType::Split,
+            "duplicate" => OperationType::Duplicate,
             "resolve" => OperationType::Resolve,
             "branch" => OperationType::Branch,
             "bookmark" => OperationType::Bookmark,
+            "tag" => OperationType::Tag,
             "git" if args.len() > 1 && args[1] == "fetch" => OperationType::GitFetch,
             "git" if args.len() > 1 && args[1] == "push" => OperationType::GitPush,
+            "git" if args.len() > 1 && args[1] == "clone" => OperationType::Clone,
+            "git" if args.len() > 1 && args[1] == "init" => OperationType::Init,
+            "init" => OperationType::Init,
             "undo" => OperationType::Undo,
             "restore" => OperationType::Restore,
             "status" => OperationType::Status,
-            "log" => OperationType::Log,
-            "diff" => OperationType::Diff,
+            "log" | "evolog" | "obslog" => OperationType::Log,
+            "diff" | "interdiff" => OperationType::Diff,
             _ => OperationType::Unknown,
         }
     }
 
+    /// Count positional arguments, skipping flags and the values they take
+    fn positional_arg_count(args: &[&str]) -> usize {
+        const VALUE_FLAGS: &[&str] = &[
+            "-m", "--message", "-r", "-A", "--insert-after", "--after", "-B", "--insert-before",
+            "--before", "--config", "--config-toml", "-R", "--repository",
+        ];
+        let mut count = 0;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if VALUE_FLAGS.contains(arg) {
+                iter.next();
+            } else if !arg.starts_with('-') {
+                count += 1;
+            }
+        }
+        count
+    }
+
     /// Get operations from the operation log
     #[napi(js_name = "getOperations")]
     pub fn get_operations(&self, limit: u32) -> napi::Result<Vec<JJOperation>> {
         Ok(self.operation_log.lock().unwrap().get_recent(limit as usize))
     }
 
+    /// Get a page of operations, newest first
+    #[napi(js_name = "getOperationsPage")]
+    pub fn get_operations_page(&self, offset: u32, limit: u32) -> napi::Result<Vec<JJOperation>> {
+        Ok(self
+            .operation_log
+            .lock()
+            .unwrap()
+            .get_page(offset as usize, limit as usize))
+    }
+
+    /// Get operations logged after the given operation, oldest first
+    ///
+    /// Useful for delta-syncing the log; fails if the operation is unknown.
+    #[napi(js_name = "getOperationsAfter")]
+    pub fn get_operations_after(&self, operation_id: String) -> napi::Result<Vec<JJOperation>> {
+        self.operation_log
+            .lock()
+            .unwrap()
+            .operations_after(&operation_id)
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+
     /// Get user-initiated operations (exclude snapshots)
     #[napi(js_name = "getUserOperations")]
     pub fn get_user_operations(&self, limit: u32) -> napi::Result<Vec<JJOperation>> {
@@ -263,6 +576,24 @@ impl JJWrapper {
             .map_err(|e| napi::Error::from_reason(format!("Failed to parse conflicts: {}", e)))
     }
 
+    /// Get conflicts ranked worst first, for triaging a large merge
+    ///
+    /// `jj resolve --list` only reports sides, so each conflicted file is read
+    /// to count its hunks; files that can't be read keep an unknown count.
+    #[napi(js_name = "conflictSummary")]
+    pub async fn conflict_summary(&self, commit: Option<String>) -> napi::Result<Vec<JJConflict>> {
+        let revision = commit.clone().unwrap_or_else(|| "@".to_string());
+        let mut conflicts = self.get_conflicts(commit).await?;
+        for conflict in &mut conflicts {
+            match self.file_at(&revision, &conflict.path).await {
+                Ok(content) => conflict.count_hunks(&content),
+                Err(JJError::BinaryFile(_)) => conflict.is_binary = true,
+                Err(_) => {}
+            }
+        }
+        Ok(rank_conflicts(conflicts))
+    }
+
     /// Parse conflict list output
     fn parse_conflicts(output: &str) -> Result<Vec<JJConflict>> {
         let mut conflicts = Vec::new();
@@ -279,17 +610,18 @@ impl JJWrapper {
                 let path = parts[0].to_string();
                 let conflict_info = parts[1..].join(" ");
 
-                let num_conflicts = conflict_info
+                let num_sides = conflict_info
                     .split('-')
                     .next()
                     .and_then(|s| s.trim().parse::<usize>().ok())
-                    .unwrap_or(1);
+                    .unwrap_or(2);
 
-                let mut conflict = JJConflict::new(path, num_conflicts as u32, "content".to_string());
+                // The listing doesn't say how many hunks conflict
+                let mut conflict = JJConflict::new(path, 0, "content".to_string());
 
                 // Extract number of sides
                 if conflict_info.contains("sided") {
-                    for _ in 0..num_conflicts {
+                    for _ in 0..num_sides {
                         conflict.add_side(format!("side-{}", conflicts.len()));
                     }
                 }
@@ -305,11 +637,10 @@ impl JJWrapper {
     #[napi]
     pub async fn describe(&self, message: String) -> napi::Result<JJOperation> {
         let args = vec!["describe".to_string(), "-m".to_string(), message];
-        let result = self.execute(args).await?;
-
-        if !result.success() {
-            return Err(napi::Error::from_reason(format!("Command failed: {}", result.stderr)));
-        }
+        self.execute(args)
+            .await?
+            .ok_or_err()
+            .map_err(|e| napi::Error::from_reason(format!("Command failed: {}", e)))?;
 
         // Return the most recent operation
         self.get_operations(1)?
@@ -327,54 +658,310 @@ impl JJWrapper {
     /// Get diff between two commits
     #[napi]
     pub async fn diff(&self, from: String, to: String) -> napi::Result<JJDiff> {
-        let args = vec!["diff".to_string(), "--from".to_string(), from, "--to".to_string(), to];
+        let args = vec![
+            "diff".to_string(),
+            "--git".to_string(),
+            "--from".to_string(),
+            from,
+            "--to".to_string(),
+            to,
+        ];
         let result = self.execute(args).await?;
 
         Self::parse_diff(&result.stdout)
             .map_err(|e| napi::Error::from_reason(format!("Failed to parse diff: {}", e)))
     }
 
-    /// Parse diff output
+    /// Parse git-format diff output
+    ///
+    /// Tracks the current file header while scanning hunks so line counts can be
+    /// attributed per file. Hunk line counts from `@@` headers are used to tell
+    /// content lines apart from the next file's `---`/`+++` header.
     fn parse_diff(output: &str) -> Result<JJDiff> {
         let mut diff = JJDiff::new();
         diff.content = output.to_string();
 
+        let mut files: Vec<FileDiffStat> = Vec::new();
+        let mut hunks: Vec<DiffHunk> = Vec::new();
+        // Whether the current file's `+++` header has been seen
+        let mut header_done = true;
+        let mut old_remaining = 0u32;
+        let mut new_remaining = 0u32;
+
         for line in output.lines() {
-            if line.starts_with("+++") {
-                // Added file
-                if let Some(path) = line.strip_prefix("+++ ") {
-                    let path = path.trim_start_matches("b/");
-                    if path != "/dev/null" {
-                        diff.added.push(path.to_string());
+            if old_remaining > 0 || new_remaining > 0 {
+                if let Some(hunk) = hunks.last_mut() {
+                    hunk.lines.push(line.to_string());
+                }
+                let current = files.last_mut();
+                if line.starts_with('+') {
+                    new_remaining = new_remaining.saturating_sub(1);
+                    diff.additions += 1;
+                    if let Some(file) = current {
+                        file.additions += 1;
+                    }
+                } else if line.starts_with('-') {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    diff.deletions += 1;
+                    if let Some(file) = current {
+                        file.deletions += 1;
+                    }
+                } else if !line.starts_with('\\') {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                }
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix("diff --git ") {
+                let path = rest
+                    .rsplit_once(" b/")
+                    .map(|(_, p)| p)
+                    .unwrap_or(rest)
+                    .to_string();
+                files.push(FileDiffStat::new(path, ChangeStatus::Modified));
+                header_done = false;
+            } else if line.starts_with("new file mode") {
+                if let Some(file) = files.last_mut() {
+                    file.kind = ChangeStatus::Added;
+                }
+            } else if line.starts_with("deleted file mode") {
+                if let Some(file) = files.last_mut() {
+                    file.kind = ChangeStatus::Deleted;
+                }
+            } else if let Some(from) = line.strip_prefix("rename from ") {
+                if let Some(file) = files.last_mut() {
+                    file.kind = ChangeStatus::Renamed;
+                    file.old_path = Some(from.to_string());
+                }
+            } else if let Some(to) = line.strip_prefix("rename to ") {
+                if let Some(file) = files.last_mut() {
+                    file.path = to.to_string();
+                }
+            } else if line.starts_with("Binary files") && line.ends_with("differ") {
+                if files.is_empty() || header_done {
+                    let path = line
+                        .split_once(" and ")
+                        .map(|(_, p)| p.trim_end_matches(" differ"))
+                        .unwrap_or("")
+                        .trim_start_matches("b/")
+                        .to_string();
+                    files.push(FileDiffStat::new(path, ChangeStatus::Modified));
+                }
+                if let Some(file) = files.last_mut() {
+                    file.is_binary = true;
+                    file.additions = 0;
+                    file.deletions = 0;
+                }
+                header_done = true;
+            } else if let Some(path) = line.strip_prefix("--- ") {
+                let path = path.trim_start_matches("a/");
+                if files.is_empty() || header_done {
+                    files.push(FileDiffStat::new(path.to_string(), ChangeStatus::Modified));
+                    header_done = false;
+                }
+                if path == "/dev/null" {
+                    if let Some(file) = files.last_mut() {
+                        file.kind = ChangeStatus::Added;
                     }
                 }
-            } else if line.starts_with("---") {
-                // Deleted file
-                if let Some(path) = line.strip_prefix("--- ") {
-                    let path = path.trim_start_matches("a/");
-                    if path != "/dev/null" {
-                        diff.deleted.push(path.to_string());
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                let path = path.trim_start_matches("b/");
+                if files.is_empty() || header_done {
+                    files.push(FileDiffStat::new(path.to_string(), ChangeStatus::Modified));
+                }
+                if let Some(file) = files.last_mut() {
+                    if path == "/dev/null" {
+                        file.kind = ChangeStatus::Deleted;
+                    } else {
+                        file.path = path.to_string();
                     }
                 }
-            } else if line.starts_with("+") && !line.starts_with("+++") {
+                header_done = true;
+            } else if let Some(header) = line.strip_prefix("@@ ") {
+                let path = files.last().map(|file| file.path.as_str()).unwrap_or_default();
+                let hunk = Self::parse_hunk_header(path, header);
+                old_remaining = hunk.old_lines;
+                new_remaining = hunk.new_lines;
+                hunks.push(hunk);
+            } else if line.starts_with('+') {
                 diff.additions += 1;
-            } else if line.starts_with("-") && !line.starts_with("---") {
+                if let Some(file) = files.last_mut() {
+                    file.additions += 1;
+                }
+            } else if line.starts_with('-') {
                 diff.deletions += 1;
+                if let Some(file) = files.last_mut() {
+                    file.deletions += 1;
+                }
+            }
+        }
+
+        for file in &files {
+            match file.kind {
+                ChangeStatus::Added => diff.added.push(file.path.clone()),
+                ChangeStatus::Deleted => diff.deleted.push(file.path.clone()),
+                ChangeStatus::Renamed => diff.renamed.push(format!(
+                    "{}:{}",
+                    file.old_path.as_deref().unwrap_or_default(),
+                    file.path
+                )),
+                _ => diff.modified.push(file.path.clone()),
             }
         }
+        diff.files = files;
+        diff.hunks = hunks;
 
         Ok(diff)
     }
 
+    /// Parse a hunk header (`-a,b +c,d @@ section`) into an empty hunk
+    ///
+    /// A range without a count (`-a`) covers one line.
+    fn parse_hunk_header(path: &str, header: &str) -> DiffHunk {
+        let range = |range: &str| {
+            let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+            (start.parse().unwrap_or(0), len.parse().unwrap_or(0))
+        };
+
+        let (ranges, section) = header.split_once("@@").unwrap_or((header, ""));
+        let mut hunk = DiffHunk {
+            path: path.to_string(),
+            section: section.trim().to_string(),
+            ..DiffHunk::default()
+        };
+        for part in ranges.split_whitespace() {
+            if let Some(old) = part.strip_prefix('-') {
+                (hunk.old_start, hunk.old_lines) = range(old);
+            } else if let Some(new) = part.strip_prefix('+') {
+                (hunk.new_start, hunk.new_lines) = range(new);
+            }
+        }
+        hunk
+    }
+
+    /// Get per-file change statistics between two commits
+    #[napi(js_name = "diffStat")]
+    pub async fn diff_stat(&self, from: String, to: String) -> napi::Result<JJDiffStat> {
+        let args = vec![
+            "diff".to_string(),
+            "--stat".to_string(),
+            "--from".to_string(),
+            from,
+            "--to".to_string(),
+            to,
+        ];
+        let result = self.execute(args).await?;
+
+        Self::parse_diff_stat(&result.stdout)
+            .map_err(|e| napi::Error::from_reason(format!("Failed to parse diff stat: {}", e)))
+    }
+
+    /// Parse `jj diff --stat` output
+    ///
+    /// Each file line has the form `path | N +++--`; renames use `old => new` or
+    /// `dir/{old => new}`. The trailing summary line provides exact totals.
+    fn parse_diff_stat(output: &str) -> Result<JJDiffStat> {
+        let mut stat = JJDiffStat::default();
+
+        for line in output.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(summary) = Self::parse_diff_stat_summary(line) {
+                stat.summary = summary;
+                continue;
+            }
+
+            let Some((path, change)) = line.rsplit_once('|') else {
+                continue;
+            };
+
+            let mut file = match Self::parse_stat_rename(path.trim()) {
+                Some((old_path, new_path)) => {
+                    let mut file = FileDiffStat::new(new_path, ChangeStatus::Renamed);
+                    file.old_path = Some(old_path);
+                    file
+                }
+                None => FileDiffStat::new(path.trim().to_string(), ChangeStatus::Modified),
+            };
+
+            let change = change.trim();
+            if change.starts_with("Bin") || change.contains("(binary)") {
+                file.is_binary = true;
+            } else {
+                let mut parts = change.split_whitespace();
+                let total: u32 = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+                let graph = parts.next().unwrap_or("");
+                let plus = graph.chars().filter(|c| *c == '+').count() as u32;
+                let minus = graph.chars().filter(|c| *c == '-').count() as u32;
+
+                // The graph is scaled down for large changes; split the exact
+                // total proportionally.
+                if plus + minus > 0 {
+                    let additions =
+                        (f64::from(total) * f64::from(plus) / f64::from(plus + minus)).round() as u32;
+                    file.additions = additions;
+                    file.deletions = total - additions;
+                }
+            }
+
+            stat.files.push(file);
+        }
+
+        Ok(stat)
+    }
+
+    /// Parse a summary line such as `3 files changed, 8 insertions(+), 5 deletions(-)`
+    fn parse_diff_stat_summary(line: &str) -> Option<DiffSummary> {
+        let mut summary = DiffSummary::default();
+        let mut matched = false;
+
+        for part in line.split(',') {
+            let mut words = part.split_whitespace();
+            let (Some(count), Some(label)) = (words.next(), words.next()) else {
+                return None;
+            };
+            let count: u32 = count.parse().ok()?;
+            if label.starts_with("file") && words.next() == Some("changed") {
+                summary.files_changed = count;
+                matched = true;
+            } else if label.starts_with("insertion") {
+                summary.insertions = count;
+            } else if label.starts_with("deletion") {
+                summary.deletions = count;
+            } else {
+                return None;
+            }
+        }
+
+        matched.then_some(summary)
+    }
+
+    /// Expand a `--stat` rename path into `(old, new)`
+    fn parse_stat_rename(path: &str) -> Option<(String, String)> {
+        if let (Some(open), Some(close)) = (path.find('{'), path.rfind('}')) {
+            if open < close {
+                let (old, new) = path[open + 1..close].split_once(" => ")?;
+                let prefix = &path[..open];
+                let suffix = &path[close + 1..];
+                let join = |middle: &str| {
+                    format!("{}{}{}", prefix, middle, suffix).replace("//", "/")
+                };
+                return Some((join(old), join(new)));
+            }
+        }
+
+        path.split_once(" => ")
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+    }
+
     /// Create a new commit (renamed from 'new' to avoid confusion with constructor)
     #[napi(js_name = "newCommit")]
     pub async fn new_commit(&self, message: Option<String>) -> napi::Result<JJResult> {
-        let mut args = vec!["new".to_string()];
-        if let Some(msg) = message {
-            args.push("-m".to_string());
-            args.push(msg);
-        }
-        self.execute(args).await
+        self.execute(Args::new(["new"]).opt_flag("-m", message).into()).await
     }
 
     /// Edit a commit
@@ -384,32 +971,59 @@ impl JJWrapper {
     }
 
     /// Abandon a commit
+    ///
+    /// `policy` controls whether descendants keep the abandoned changes;
+    /// defaults to jj's behaviour of rebasing them without those changes.
     #[napi]
-    pub async fn abandon(&self, revision: String) -> napi::Result<JJResult> {
-        self.execute(vec!["abandon".to_string(), revision]).await
+    pub async fn abandon(
+        &self,
+        revision: String,
+        policy: Option<AbandonPolicy>,
+    ) -> napi::Result<JJResult> {
+        let args = Args::new(["abandon"])
+            .args(policy.unwrap_or_default().args().iter().copied())
+            .arg(revision);
+        self.execute(args.into()).await
     }
 
     /// Squash commits
+    ///
+    /// Never opens an editor: `message` becomes the resulting description,
+    /// otherwise the destination's description is kept.
     #[napi]
-    pub async fn squash(&self, from: Option<String>, to: Option<String>) -> napi::Result<JJResult> {
-        let mut args = vec!["squash".to_string()];
-        if let Some(f) = from {
-            args.push("-r".to_string());
-            args.push(f);
-        }
-        if let Some(t) = to {
-            args.push("--into".to_string());
-            args.push(t);
-        }
-        self.execute(args).await
+    pub async fn squash(
+        &self,
+        from: Option<String>,
+        to: Option<String>,
+        message: Option<String>,
+    ) -> napi::Result<JJResult> {
+        let args = Args::new(["squash"])
+            .opt_flag("-r", from)
+            .opt_flag("--into", to)
+            .switch("--use-destination-message", message.is_none())
+            .opt_flag("-m", message);
+        self.execute(args.into()).await
     }
 
     /// Rebase commits
+    ///
+    /// `mode` selects what moves with `source`: its descendants too
+    /// (default, `-s`), the whole branch (`-b`) or just that revision (`-r`).
     #[napi]
-    pub async fn rebase(&self, source: String, destination: String) -> napi::Result<JJResult> {
+    pub async fn rebase(
+        &self,
+        source: String,
+        destination: String,
+        mode: Option<RebaseMode>,
+    ) -> napi::Result<JJResult> {
+        if source.trim().is_empty() || destination.trim().is_empty() {
+            return Err(napi::Error::from_reason(
+                "rebase needs non-empty source and destination revsets",
+            ));
+        }
         self.execute(vec![
             "rebase".to_string(),
-            "-s".to_string(),
+            mode.unwrap_or_default().flag().to_string(),
             source,
             "-d".to_string(),
             destination,
@@ -420,34 +1034,27 @@ impl JJWrapper {
     /// Resolve conflicts
     #[napi]
     pub async fn resolve(&self, path: Option<String>) -> napi::Result<JJResult> {
-        let mut args = vec!["resolve".to_string()];
-        if let Some(p) = path {
-            args.push(p);
-        }
-        self.execute(args).await
+        self.execute(Args::new(["resolve"]).opt_arg(path).into()).await
     }
 
     /// Create a branch
     #[napi(js_name = "branchCreate")]
     pub async fn branch_create(&self, name: String, revision: Option<String>) -> napi::Result<JJResult> {
-        let mut args = vec!["branch".to_string(), "create".to_string(), name];
-        if let Some(rev) = revision {
-            args.push("-r".to_string());
-            args.push(rev);
-        }
-        self.execute(args).await
+        let args = Args::new([self.branch_subcommand().await, "create".to_string(), name])
+            .opt_flag("-r", revision);
+        self.execute(args.into()).await
     }
 
     /// Delete a branch
     #[napi(js_name = "branchDelete")]
     pub async fn branch_delete(&self, name: String) -> napi::Result<JJResult> {
-        self.execute(vec!["branch".to_string(), "delete".to_string(), name]).await
+        self.execute(vec![self.branch_subcommand().await, "delete".to_string(), name]).await
     }
 
     /// List branches
     #[napi(js_name = "branchList")]
     pub async fn branch_list(&self) -> napi::Result<Vec<JJBranch>> {
-        let result = self.execute(vec!["branch".to_string(), "list".to_string()]).await?;
+        let result = self.execute(vec![self.branch_subcommand().await, "list".to_string()]).await?;
         Self::parse_branches(&result.stdout)
             .map_err(|e| napi::Error::from_reason(format!("Failed to parse branches: {}", e)))
     }
@@ -497,44 +1104,48 @@ impl JJWrapper {
     /// Restore files
     #[napi]
     pub async fn restore(&self, paths: Vec<String>) -> napi::Result<JJResult> {
-        let mut args = vec!["restore".to_string()];
-        args.extend(paths);
-        self.execute(args).await
+        self.execute(Args::new(["restore"]).args(paths).into()).await
     }
 
     /// Show commit log
+    ///
+    /// Records that don't parse into a commit are skipped; with `verbose` set,
+    /// the number skipped is reported on stderr.
     #[napi]
     pub async fn log(&self, limit: Option<u32>) -> napi::Result<Vec<JJCommit>> {
-        let mut args = vec!["log".to_string()];
-        if let Some(l) = limit {
-            args.push("--limit".to_string());
-            args.push(l.to_string());
+        let args = Args::new(["log"]).opt_flag("--limit", limit.map(|l| l.to_string()));
+        let result = self.execute(args.into()).await?;
+        let (commits, parse_warnings) = Self::parse_log(&result.stdout);
+        if parse_warnings > 0 && self.config.verbose {
+            eprintln!("[jj-wrapper] skipped {} unparseable log record(s)", parse_warnings);
         }
-        let result = self.execute(args).await?;
-        Self::parse_log(&result.stdout)
-            .map_err(|e| napi::Error::from_reason(format!("Failed to parse log: {}", e)))
+        Ok(commits)
     }
 
-    /// Parse log output
-    fn parse_log(output: &str) -> Result<Vec<JJCommit>> {
+    /// Parse log output into commits and the number of records skipped
+    ///
+    /// Records are blank-line separated blocks of `Commit ID:`, `Change ID:`
+    /// and `Author:` lines. A block without a hexadecimal commit ID is
+    /// dropped rather than returned as a placeholder commit.
+    fn parse_log(output: &str) -> (Vec<JJCommit>, usize) {
         let mut commits = Vec::new();
+        let mut parse_warnings = 0;
 
         // Simple parser - in production, use `jj log --template` with JSON output
         for block in output.split("\n\n") {
-            let lines: Vec<&str> = block.lines().collect();
-            if lines.is_empty() {
+            if block.trim().is_empty() {
                 continue;
             }
 
             let mut commit = JJCommit::new(
-                "unknown".to_string(),
+                String::new(),
                 "unknown".to_string(),
                 String::new(),
                 "unknown".to_string(),
                 "unknown@example.com".to_string(),
             );
 
-            for line in lines {
+            for line in block.lines() {
                 if let Some(id) = line.strip_prefix("Commit ID: ") {
                     commit.id = id.trim().to_string();
                 } else if let Some(change) = line.strip_prefix("Change ID: ") {
@@ -548,10 +1159,14 @@ impl JJWrapper {
                 }
             }
 
+            if commit.id.is_empty() || !commit.id.chars().all(|c| c.is_ascii_hexdigit()) {
+                parse_warnings += 1;
+                continue;
+            }
             commits.push(commit);
         }
 
-        Ok(commits)
+        (commits, parse_warnings)
     }
 
     /// Clear operation log
@@ -1208,10 +1823,18 @@ impl JJWrapper {
 impl JJWrapper {
     /// Create wrapper with config (Rust-only, returns Result<JJWrapper>)
     pub fn with_config_checked(config: JJConfig) -> Result<JJWrapper> {
-        let operation_log = Arc::new(Mutex::new(JJOperationLog::new(config.max_log_entries as usize)));
+        let operation_log = Arc::new(Mutex::new(Self::load_operation_log(&config)?));
         let reasoning_bank = Arc::new(ReasoningBank::new(1000));
         let current_trajectory = Arc::new(Mutex::new(None));
         let agent_coordination = Arc::new(tokio::sync::Mutex::new(None));
+        let hooks = Arc::new(Mutex::new(Self::config_hooks(&config)?));
+        let executor: Arc<dyn CommandExecutor> = Arc::new(NativeExecutor);
+        let detected_version = Arc::new(tokio::sync::OnceCell::new());
+        let repo_root = Arc::new(tokio::sync::OnceCell::new());
+        let tagging_rules = Arc::new(Mutex::new(Vec::new()));
+        let output_cache = Arc::new(Mutex::new(HashMap::new()));
+        let op_permits = Arc::new(tokio::sync::Semaphore::new(config.max_concurrent_ops.max(1) as usize));
+        let current_task = Arc::new(Mutex::new(None));
 
         Ok(JJWrapper {
             config,
@@ -1219,70 +1842,4318 @@ impl JJWrapper {
             reasoning_bank,
             current_trajectory,
             agent_coordination,
+            hooks,
+            executor,
+            detected_version,
+            repo_root,
+            tagging_rules,
+            output_cache,
+            op_permits,
+            current_task,
         })
     }
-}
 
-impl Default for JJWrapper {
-    fn default() -> Self {
-        Self::new().expect("Failed to create default JJWrapper")
+    /// Create the operation log, loading history from `log_persist_path` if set
+    fn load_operation_log(config: &JJConfig) -> Result<JJOperationLog> {
+        let mut log = JJOperationLog::new(config.max_log_entries as usize).with_dedup(config.dedup_operations);
+        if let Some(max_bytes) = config.max_log_bytes {
+            log = log.with_max_bytes(max_bytes as usize);
+        }
+        if let Some(path) = config.log_persist_path.as_ref().filter(|_| config.enable_operation_log) {
+            log.load_jsonl(Path::new(path))?;
+        }
+        Ok(log)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Built-in hooks enabled by the configuration
+    fn config_hooks(config: &JJConfig) -> Result<OperationHooks> {
+        let mut hooks = OperationHooks::new();
+        if let Some(pattern) = &config.commit_message_pattern {
+            hooks.add_pre(commit_message_validator(pattern)?);
+        }
+        Ok(hooks)
+    }
 
-    #[test]
-    fn test_wrapper_creation() {
-        let wrapper = JJWrapper::new();
-        assert!(wrapper.is_ok());
+    // ========== Command Execution ==========
 
-        let config = JJConfig::default().with_verbose(true);
-        let wrapper = JJWrapper::with_config_checked(config);
-        assert!(wrapper.is_ok());
+    /// Execute a jj command (Rust-only, returns Result<JJResult>)
+    ///
+    /// Fires `PreOperation` hooks (sync, then async) before the command runs
+    /// and `PostOperation` hooks after it has been logged. A pre-operation hook
+    /// error aborts the command with [`JJError::HookVetoed`].
+    pub async fn execute_checked(&self, args: Vec<String>) -> Result<JJResult> {
+        self.execute_with_input(args, None).await
     }
 
-    #[test]
-    fn test_detect_operation_type() {
-        assert_eq!(
-            JJWrapper::detect_operation_type(&["describe", "-m", "test"]),
-            OperationType::Describe
-        );
-        assert_eq!(
-            JJWrapper::detect_operation_type(&["new"]),
-            OperationType::New
-        );
-        assert_eq!(
-            JJWrapper::detect_operation_type(&["git", "fetch"]),
-            OperationType::GitFetch
-        );
+    /// Execute a jj command with `stdin` written to its input (Rust-only)
+    ///
+    /// For text that can't be passed as an argument, e.g. `describe --stdin`
+    /// with a multi-line message. The input is not logged or cached.
+    pub async fn execute_checked_with_stdin(&self, args: Vec<String>, stdin: &str) -> Result<JJResult> {
+        self.execute_with_input(args, Some(stdin)).await
     }
 
-    #[test]
-    fn test_parse_conflicts() {
-        let output = "file1.txt    2-sided conflict\nfile2.rs    3-sided conflict";
-        let conflicts = JJWrapper::parse_conflicts(output).unwrap();
+    async fn execute_with_input(&self, args: Vec<String>, stdin: Option<&str>) -> Result<JJResult> {
+        // Validate arguments for security
+        Self::validate_args(&args)?;
 
-        assert_eq!(conflicts.len(), 2);
-        assert_eq!(conflicts[0].path, "file1.txt");
-        assert_eq!(conflicts[0].num_conflicts, 2);
-        assert_eq!(conflicts[1].path, "file2.rs");
-        assert_eq!(conflicts[1].num_conflicts, 3);
-    }
+        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+        let username = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
 
-    #[test]
-    fn test_parse_diff() {
-        let output = r#"
-+++ b/new.txt
---- a/deleted.txt
-+Added line
--Removed line
-        "#;
+        // Snapshot hooks so handlers may use the wrapper without deadlocking
+        let hooks = self.hooks.lock().unwrap().clone();
+        let mut context = HookContext::new(username.clone(), String::new(), format!("jj {}", args.join(" ")))
+            .with_args(args);
+
+        if !hooks.is_empty() {
+            let mut operation = JJOperation::new(
+                Self::synthetic_operation_id(&hostname),
+                context.task_description.clone(),
+                username.clone(),
+                hostname.clone(),
+            );
+            let operation_type = Self::detect_args_operation_type(&context.args);
+            operation.operation_type = operation_type.as_string();
+
+            let mut event = JJHookEvent::new(HookEventType::PreOperation, Some(operation), context)
+                .with_operation_type(operation_type);
+            hooks.run_pre(&mut event).map_err(Self::into_veto)?;
+            hooks
+                .run_async_pre(&event.context)
+                .await
+                .map_err(Self::into_veto)?;
+
+            // Hooks may have rewritten the arguments; re-validate what will run
+            context = event.context;
+            Self::validate_args(&context.args)?;
+        }
 
-        let diff = JJWrapper::parse_diff(output).unwrap();
-        assert_eq!(diff.additions, 1);
-        assert_eq!(diff.deletions, 1);
+        let args = context.args.clone();
+        let command = format!("jj {}", args.join(" "));
+        let operation_type = Self::detect_args_operation_type(&args);
+
+        // Execute command with timeout
+        let timeout = std::time::Duration::from_millis(self.config.timeout_ms as u64);
+        let repo_path = Self::targets_existing_repo(&args).then(|| self.repo_path()).flatten();
+        let run_args = self.with_config_overrides(&args);
+        let cached = stdin.is_none().then(|| self.cached_output(&args)).flatten();
+        let cache_hit = cached.is_some();
+        // Only spawning jj needs a permit; time spent waiting isn't logged as duration
+        let _permit = match cache_hit {
+            true => None,
+            false => Some(
+                self.op_permits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| JJError::Unknown(format!("Concurrency limiter closed: {}", e)))?,
+            ),
+        };
+        let start = Instant::now();
+        let result = match cached {
+            Some(output) => Ok(output),
+            None => {
+                let output = match stdin {
+                    Some(input) => {
+                        self.executor
+                            .execute_with_stdin(&self.config.jj_path, repo_path, &run_args, input, timeout)
+                            .await
+                    }
+                    None => {
+                        self.executor
+                            .execute(&self.config.jj_path, repo_path, &run_args, timeout)
+                            .await
+                    }
+                };
+                output.map_err(Self::classify_failure)
+            }
+        };
+        if !cache_hit && stdin.is_none() {
+            self.update_output_cache(&args, &result);
+        }
+
+        // Log the operation (ALWAYS, even if failed), unless logging is off
+        // and no post-operation hook needs to see it
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let logging = self.config.enable_operation_log;
+        let operation = (logging || !hooks.is_empty()).then(|| {
+            let mut operation = JJOperation::new(
+                Self::synthetic_operation_id(&hostname),
+                command.clone(),
+                username,
+                hostname,
+            );
+            operation.operation_type = operation_type.as_string();
+            operation.duration_ms = duration_ms as u32;
+            operation.set_metadata("repo_path".to_string(), self.config.repo_path.clone());
+            if cache_hit {
+                operation.set_metadata("cached".to_string(), "true".to_string());
+            }
+            if let Some(task_id) = self.current_task.lock().unwrap().clone() {
+                operation.set_metadata("task_id".to_string(), task_id);
+            }
+            match &result {
+                Ok(_) => operation.success = true,
+                Err(e) => {
+                    operation.success = false;
+                    operation.error = Some(e.to_string());
+                }
+            }
+            for rule in self.tagging_rules.lock().unwrap().iter() {
+                rule.apply(&mut operation);
+            }
+            operation
+        });
+        if let Some(operation) = operation.as_ref().filter(|_| logging) {
+            self.operation_log.lock().unwrap().add_operation(operation.clone());
+            // The file is append-only; max_log_entries only trims memory
+            if let Some(path) = &self.config.log_persist_path {
+                if let Err(e) = JJOperationLog::append_jsonl(Path::new(path), operation) {
+                    eprintln!("[jj-wrapper] failed to persist operation: {}", e);
+                }
+            }
+        }
+
+        if !hooks.is_empty() {
+            context.task_description = command;
+            let mut event = JJHookEvent::new(HookEventType::PostOperation, operation, context)
+                .with_operation_type(operation_type);
+            hooks.run_post(&mut event);
+            hooks.run_async_post(&event.context).await;
+        }
+
+        result.map(|output| JJResult::new(output, String::new(), 0, duration_ms))
+    }
+
+    /// ID for an operation the wrapper records itself: `<nanos>-<uuid>@<host>`
+    ///
+    /// Unique per call, so operations logged in the same second stay distinct.
+    fn synthetic_operation_id(hostname: &str) -> String {
+        format!(
+            "{}-{}@{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+            uuid::Uuid::new_v4().simple(),
+            hostname
+        )
+    }
+
+    /// Repository to pass to the executor, if not the current directory
+    fn repo_path(&self) -> Option<&str> {
+        match self.config.repo_path.as_str() {
+            "" | "." => None,
+            path => Some(path),
+        }
+    }
+
+    /// Wrap a pre-operation hook error, keeping hooks' own vetoes as-is
+    fn into_veto(error: JJError) -> JJError {
+        match error {
+            JJError::HookVetoed(_) => error,
+            other => JJError::HookVetoed(other.to_string()),
+        }
+    }
+
+    /// Check if a command runs against an existing repository
+    ///
+    /// Commands that create a repository take their destination as an
+    /// argument instead of `--repository`.
+    fn targets_existing_repo(args: &[String]) -> bool {
+        let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        !matches!(
+            args_refs.as_slice(),
+            ["init", ..]
+                | ["git", "init", ..]
+                | ["git", "clone", ..]
+                | ["--version", ..]
+                | ["version", ..]
+        )
+    }
+
+    /// Cached output for a read-only command, if caching is enabled and fresh
+    fn cached_output(&self, args: &[String]) -> Option<String> {
+        let ttl = std::time::Duration::from_millis(self.config.cache_ttl_ms? as u64);
+        if !Self::is_read_only_command(args) {
+            return None;
+        }
+        let cache = self.output_cache.lock().unwrap();
+        cache
+            .get(args)
+            .filter(|(stored_at, _)| stored_at.elapsed() < ttl)
+            .map(|(_, output)| output.clone())
+    }
+
+    /// Store read-only output, or drop everything after any other command
+    ///
+    /// Any command that is not read-only may have changed the repository, so
+    /// the whole cache is cleared whether or not it succeeded.
+    fn update_output_cache(&self, args: &[String], result: &Result<String>) {
+        if self.config.cache_ttl_ms.is_none() {
+            return;
+        }
+        let mut cache = self.output_cache.lock().unwrap();
+        if !Self::is_read_only_command(args) {
+            cache.clear();
+        } else if let Ok(output) = result {
+            cache.insert(args.to_vec(), (Instant::now(), output.clone()));
+        }
+    }
+
+    /// Check if a command only reads repository state
+    ///
+    /// jj snapshots the working copy even for these, so cached output can
+    /// miss file edits made within the TTL.
+    fn is_read_only_command(args: &[String]) -> bool {
+        let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        match args_refs.as_slice() {
+            ["status" | "st" | "log" | "diff" | "interdiff" | "show" | "cat" | "files", ..] => true,
+            ["evolog" | "obslog", ..] => true,
+            ["file", "show" | "list", ..] => true,
+            ["branch" | "bookmark" | "tag", "list" | "l", ..] => true,
+            ["op" | "operation", "log" | "show" | "diff", ..] => true,
+            ["root" | "version", ..] => true,
+            ["config", "get" | "list" | "path", ..] => true,
+            ["workspace", "list" | "root", ..] => true,
+            ["resolve", rest @ ..] => rest.contains(&"--list") || rest.contains(&"-l"),
+            _ => false,
+        }
+    }
+
+    /// Add the `--config` overrides implied by the configuration
+    ///
+    /// The overrides go right after the subcommand and are not part of the
+    /// logged command.
+    fn with_config_overrides(&self, args: &[String]) -> Vec<String> {
+        let mut overrides = Vec::new();
+        if self.config.non_interactive {
+            overrides.extend(Self::editor_overrides_for(args));
+        }
+        let mut aliases: Vec<_> = self.config.revset_aliases.iter().collect();
+        aliases.sort();
+        for (name, value) in aliases {
+            overrides.push(format!(
+                "revset-aliases.{}={}",
+                toml_string(name),
+                toml_string(value)
+            ));
+        }
+
+        let mut result = args.to_vec();
+        let at = result.len().min(1);
+        result.splice(
+            at..at,
+            overrides
+                .into_iter()
+                .flat_map(|value| ["--config".to_string(), value]),
+        );
+        result
+    }
+
+    /// Editor overrides for `args`, except keys the command sets itself
+    fn editor_overrides_for(args: &[String]) -> Vec<String> {
+        let explicit: Vec<&str> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "--config")
+            .filter_map(|pair| pair[1].split_once('=').map(|(key, _)| key))
+            .collect();
+        Self::editor_overrides(args)
+            .into_iter()
+            .filter(|value| !value.split_once('=').is_some_and(|(key, _)| explicit.contains(&key)))
+            .collect()
+    }
+
+    /// Point any editor a command may open at the `false` command
+    ///
+    /// jj parses `--config` values as TOML, so the command name is quoted; a
+    /// bare `false` would be a boolean and fail as a config type error. jj then
+    /// fails with "Editor 'false' exited with exit status: 1" instead of
+    /// waiting on a terminal that a headless agent will never attach.
+    fn editor_overrides(args: &[String]) -> Vec<String> {
+        let keys: &[&str] = match args.first().map(|s| s.as_str()) {
+            Some("describe" | "desc" | "commit" | "ci" | "squash") => &["ui.editor"],
+            Some("split") => &["ui.editor", "ui.diff-editor"],
+            Some("diffedit") => &["ui.diff-editor"],
+            Some("resolve") => &["ui.merge-editor"],
+            _ => &[],
+        };
+        keys.iter().map(|key| format!("{}={}", key, toml_string("false"))).collect()
+    }
+
+    /// Validate owned command arguments
+    fn validate_args(args: &[String]) -> Result<()> {
+        let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        validate_command_args(&args_refs)
+    }
+
+    /// Detect operation type from owned command arguments
+    fn detect_args_operation_type(args: &[String]) -> OperationType {
+        let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        Self::detect_operation_type(&args_refs)
+    }
+
+    /// Record operations into `log` (Rust-only)
+    ///
+    /// `JJOperationLog` clones share storage, so several wrappers can log into
+    /// one history; operations are told apart by their `repo_path` metadata.
+    pub fn with_operation_log(mut self, log: JJOperationLog) -> Self {
+        self.operation_log = Arc::new(Mutex::new(log));
+        self
+    }
+
+    /// Replace the command executor (Rust-only)
+    ///
+    /// Intended for tests and embedders that run jj somewhere other than a
+    /// local child process.
+    pub fn with_executor(mut self, executor: Arc<dyn CommandExecutor>) -> Self {
+        self.executor = executor;
+        // The cached version, root and output belonged to the previous executor
+        self.detected_version = Arc::new(tokio::sync::OnceCell::new());
+        self.repo_root = Arc::new(tokio::sync::OnceCell::new());
+        self.output_cache = Arc::new(Mutex::new(HashMap::new()));
+        self
+    }
+
+    /// Detect the installed jj version by running `jj --version`
+    ///
+    /// The result is cached, so jj is queried at most once per wrapper (failed
+    /// detections are retried). Not recorded in the operation log.
+    pub async fn version(&self) -> Result<JJVersion> {
+        self.detected_version
+            .get_or_try_init(|| async {
+                let timeout = std::time::Duration::from_millis(self.config.timeout_ms as u64);
+                let output = self
+                    .executor
+                    .execute(&self.config.jj_path, None, &["--version".to_string()], timeout)
+                    .await?;
+                output.parse::<JJVersion>()
+            })
+            .await
+            .cloned()
+    }
+
+    /// Absolute path of the repository root (`jj root`)
+    ///
+    /// Useful when `repo_path` is relative or points into a subdirectory. The
+    /// result is cached after the first successful call; outside a repository
+    /// this fails with [`JJError::NotARepository`].
+    pub async fn root(&self) -> Result<PathBuf> {
+        self.repo_root
+            .get_or_try_init(|| async {
+                let result = self.execute_checked(vec!["root".to_string()]).await?;
+                let root = result.stdout.trim_end_matches(['\r', '\n']);
+                if root.is_empty() {
+                    return Err(JJError::ParseError("jj root printed no path".to_string()));
+                }
+                Ok(PathBuf::from(root))
+            })
+            .await
+            .cloned()
+    }
+
+    /// Resolve the subcommand for branch operations (`branch` or `bookmark`)
+    ///
+    /// Uses `JJConfig::branch_command` when set, otherwise prefers `bookmark`
+    /// whenever the installed jj supports it. Falls back to `branch` if the
+    /// version cannot be detected.
+    async fn branch_subcommand(&self) -> String {
+        let command = match self.config.branch_command {
+            Some(command) => command,
+            None => match self.version().await {
+                Ok(version) if version.supports_bookmarks() => BranchCommand::Bookmark,
+                _ => BranchCommand::Branch,
+            },
+        };
+
+        match command {
+            BranchCommand::Branch => "branch".to_string(),
+            BranchCommand::Bookmark => "bookmark".to_string(),
+        }
+    }
+
+    // ========== Change Resolution ==========
+
+    /// Resolve a change ID to the commit it currently points at
+    ///
+    /// Commit IDs change on every rewrite while change IDs are stable. Fails if
+    /// the change was abandoned (no visible commit) or is divergent.
+    pub async fn resolve_change(&self, change_id: &str) -> Result<JJCommit> {
+        let mut commits = self.query_commits(change_id).await?;
+        match commits.len() {
+            0 => Err(JJError::CommandFailed(format!(
+                "Change {} has no visible commit (it may have been abandoned)",
+                change_id
+            ))),
+            1 => Ok(commits.remove(0)),
+            n => Err(JJError::CommandFailed(format!(
+                "Change {} is divergent ({} visible commits)",
+                change_id, n
+            ))),
+        }
+    }
+
+    /// Get the working-copy commit (`@`)
+    ///
+    /// Always present, even in a freshly initialised repository.
+    pub async fn current_commit(&self) -> Result<JJCommit> {
+        self.query_commits("@")
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| JJError::ParseError("jj log -r @ returned no commit".to_string()))
+    }
+
+    /// Get the stable change ID of the working-copy commit
+    pub async fn current_change_id(&self) -> Result<String> {
+        Ok(self.current_commit().await?.change_id)
+    }
+
+    /// Look up the change ID of a commit
+    pub async fn commit_to_change(&self, commit_id: &str) -> Result<String> {
+        self.query_commits(commit_id)
+            .await?
+            .into_iter()
+            .next()
+            .map(|commit| commit.change_id)
+            .ok_or_else(|| JJError::CommandFailed(format!("Commit {} not found", commit_id)))
+    }
+
+    /// Run `jj log` for `revset` with the JSON commit template
+    async fn query_commits(&self, revset: &str) -> Result<Vec<JJCommit>> {
+        self.query_commits_with(revset, COMMIT_JSON_TEMPLATE).await
+    }
+
+    /// Run `jj log` for `revset` with one of the JSON commit templates
+    async fn query_commits_with(&self, revset: &str, template: &str) -> Result<Vec<JJCommit>> {
+        let result = self
+            .execute_checked(vec![
+                "log".to_string(),
+                "-r".to_string(),
+                revset.to_string(),
+                "--no-graph".to_string(),
+                "-T".to_string(),
+                template.to_string(),
+            ])
+            .await?;
+        Self::parse_commit_json(&result.stdout)
+    }
+
+    /// Commits in `revset` (at most `limit`) with their parent edges
+    ///
+    /// The commit template can't list parents without a `|` lambda, which
+    /// argument validation rejects, so each commit's parents are looked up
+    /// with a `<id>-` query: one extra jj call per commit.
+    pub async fn log_graph(&self, revset: &str, limit: Option<usize>) -> Result<CommitGraph> {
+        let args = Args::new(["log"])
+            .flag("-r", revset)
+            .opt_flag("--limit", limit.map(|l| l.to_string()))
+            .args(["--no-graph", "-T", COMMIT_JSON_TEMPLATE]);
+        let mut commits = Self::parse_commit_json(&self.execute_checked(args.into()).await?.stdout)?;
+
+        for commit in &mut commits {
+            let parents = self.query_commits(&format!("{}-", commit.id)).await?;
+            commit.parents = parents.into_iter().map(|parent| parent.id).collect();
+            commit.is_merge = commit.parents.len() > 1;
+        }
+        Ok(CommitGraph::from_commits(commits))
+    }
+
+    /// Read-only view of the repository as it was after operation `op_id`
+    ///
+    /// Every command run through the view gets `--at-operation <op_id>`.
+    pub fn at_op(&self, op_id: &str) -> JJWrapperView {
+        JJWrapperView {
+            wrapper: self.clone(),
+            op_id: op_id.to_string(),
+        }
+    }
+
+    /// Commits visible now that were not visible at operation `op_id`
+    ///
+    /// Reads the visible heads as of `op_id` (`--at-operation`) and returns
+    /// everything reachable from today's heads but not from those, newest
+    /// first: "what's new since I last looked". Rewritten commits count as
+    /// new. Returns [`JJError::OperationNotFound`] if `op_id` no longer exists,
+    /// e.g. after `jj op abandon`.
+    pub async fn commits_since(&self, op_id: &str) -> Result<Vec<JJCommit>> {
+        let args = Args::new(["log"])
+            .flag("-r", "visible_heads()")
+            .args(["--no-graph", "-T", r#"commit_id ++ "\n""#]);
+        let heads = self.at_op(op_id).execute(args.into()).await?;
+
+        // `|` is rejected by argument validation, so exclude each old head in turn
+        let mut revset = "::visible_heads()".to_string();
+        for head in heads.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            revset.push_str(" ~ ::");
+            revset.push_str(head);
+        }
+        self.query_commits(&revset).await
+    }
+
+    /// Evolution of a change across rewrites, newest version first
+    ///
+    /// Runs `jj evolog` (`jj obslog` before jj 0.19). Every entry but the
+    /// current one is an obsolete version with `is_hidden` set, so an agent
+    /// can tell whether a change was amended after review.
+    pub async fn evolog(&self, change_id: &str) -> Result<Vec<JJCommit>> {
+        let subcommand = match self.version().await {
+            Ok(version) if !version.supports_evolog() => "obslog",
+            _ => "evolog",
+        };
+        let result = self
+            .execute_checked(vec![
+                subcommand.to_string(),
+                "-r".to_string(),
+                change_id.to_string(),
+                "--no-graph".to_string(),
+                "-T".to_string(),
+                EVOLOG_JSON_TEMPLATE.to_string(),
+            ])
+            .await?;
+        Self::parse_commit_json(&result.stdout)
+    }
+
+    /// Verify the GPG/SSH signatures of every commit in `revset`
+    ///
+    /// Returns `(commit_id, status)` pairs in `jj log` order. Unsigned
+    /// commits report [`SignatureStatus::Unsigned`], which is distinct from a
+    /// signature that fails to verify ([`SignatureStatus::Bad`]).
+    pub async fn verify_signatures(&self, revset: &str) -> Result<Vec<(String, SignatureStatus)>> {
+        Ok(self
+            .query_commits_with(revset, SIGNED_COMMIT_JSON_TEMPLATE)
+            .await?
+            .into_iter()
+            .map(|commit| (commit.id, commit.signed.unwrap_or(SignatureStatus::Unsigned)))
+            .collect())
+    }
+
+    /// Parse newline-delimited commit JSON from [`COMMIT_JSON_TEMPLATE`]
+    fn parse_commit_json(output: &str) -> Result<Vec<JJCommit>> {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<CommitJson>(line)
+                    .map(JJCommit::from)
+                    .map_err(|e| JJError::ParseError(format!("Invalid commit JSON: {}", e)))
+            })
+            .collect()
+    }
+
+    // ========== Diffs ==========
+
+    /// Diff two revisions, restricted to `paths`
+    ///
+    /// Only the filtered output is produced and parsed, which keeps reviews
+    /// of a single module cheap on large changes. An empty `paths` diffs the
+    /// whole revision like [`JJWrapper::diff`].
+    pub async fn diff_paths(&self, from: &str, to: &str, paths: &[&str]) -> Result<JJDiff> {
+        let args = Args::new(["diff", "--git"])
+            .flag("--from", from)
+            .flag("--to", to)
+            .args(paths.iter().copied());
+        let result = self.execute_checked(args.into()).await?;
+        Self::parse_diff(&result.stdout)
+    }
+
+    /// Diff of diffs: what changed between two versions of a change
+    ///
+    /// Runs `jj interdiff`, which temporarily rebases `from` onto the parents
+    /// of `to` and diffs the result against `to`, so changes that only come
+    /// from a different base are left out: this answers "what did they change
+    /// since my review". That also makes unrelated bases work; where the
+    /// rebase can't apply cleanly, the diff shows jj's conflict markers in
+    /// the affected files.
+    pub async fn interdiff(&self, from: &str, to: &str) -> Result<JJDiff> {
+        if from.trim().is_empty() || to.trim().is_empty() {
+            return Err(JJError::InvalidConfig(
+                "interdiff needs non-empty from and to revisions".to_string(),
+            ));
+        }
+
+        let args = Args::new(["interdiff", "--git"]).flag("--from", from).flag("--to", to);
+        let result = self.execute_checked(args.into()).await?;
+        Self::parse_diff(&result.stdout)
+    }
+
+    /// Count files, insertions and deletions between two revisions
+    ///
+    /// The cheapest "how big is this change" query: only the totals line at
+    /// the end of `jj diff --stat` is parsed, no per-file stats are built.
+    /// An empty diff yields an all-zero summary.
+    pub async fn diff_summary(&self, from: &str, to: &str) -> Result<DiffSummary> {
+        let args = Args::new(["diff", "--stat"]).flag("--from", from).flag("--to", to);
+        let result = self.execute_checked(args.into()).await?;
+        Ok(Self::parse_diff_summary(&result.stdout))
+    }
+
+    /// Count files, insertions and deletions in one revision (`jj diff --stat -r`)
+    ///
+    /// The revision is compared with all of its parents merged, so unlike
+    /// `diff_summary("<rev>-", rev)` this also works for merge commits.
+    pub async fn revision_summary(&self, revision: &str) -> Result<DiffSummary> {
+        let args = Args::new(["diff", "--stat"]).flag("-r", revision);
+        let result = self.execute_checked(args.into()).await?;
+        Ok(Self::parse_diff_summary(&result.stdout))
+    }
+
+    /// Find the totals line of `jj diff --stat` output, scanning from the end
+    fn parse_diff_summary(output: &str) -> DiffSummary {
+        output
+            .lines()
+            .rev()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .find_map(Self::parse_diff_stat_summary)
+            .unwrap_or_default()
+    }
+
+    // ========== jj Operation Log ==========
+
+    /// Read jj's own operation log (`jj op log`), newest first
+    ///
+    /// Unlike the in-memory log this is the repository's authoritative
+    /// history: it survives restarts and includes operations run outside this
+    /// wrapper. Operation types are inferred from jj's descriptions.
+    pub async fn op_log(&self, limit: Option<usize>) -> Result<Vec<JJOperation>> {
+        let result = self.execute_checked(Self::op_log_args(limit)).await?;
+        Self::parse_op_json(&result.stdout)
+    }
+
+    /// Arguments for reading jj's operation log as JSON lines
+    fn op_log_args(limit: Option<usize>) -> Vec<String> {
+        Args::new(["op", "log", "--no-graph"])
+            .flag("-T", OP_JSON_TEMPLATE)
+            .opt_flag("--limit", limit.map(|l| l.to_string()))
+            .into()
+    }
+
+    /// List the working-copy snapshots in jj's operation log, newest first
+    ///
+    /// jj snapshots the working copy before most commands, so these are the
+    /// points [`restore_snapshot`](Self::restore_snapshot) can jump back to.
+    pub async fn list_snapshots(&self) -> Result<Vec<JJOperation>> {
+        let mut ops = self.op_log(None).await?;
+        ops.retain(JJOperation::is_snapshot);
+        Ok(ops)
+    }
+
+    /// Restore the repository to a working-copy snapshot (`jj op restore`)
+    ///
+    /// `op_id` may be a prefix and must name a snapshot operation: anything
+    /// else is rejected with [`JJError::InvalidConfig`] before jj runs, and an
+    /// unknown ID with [`JJError::OperationNotFound`]. Like any `op restore`,
+    /// this resets the whole repository view (commits and bookmarks too) to
+    /// that point, and is itself undoable.
+    pub async fn restore_snapshot(&self, op_id: &str) -> Result<JJResult> {
+        if op_id.trim().is_empty() {
+            return Err(JJError::InvalidConfig("restore_snapshot needs an operation ID".to_string()));
+        }
+
+        let ops = self.op_log(None).await?;
+        let matches: Vec<&JJOperation> =
+            ops.iter().filter(|op| op.operation_id.starts_with(op_id)).collect();
+        let op = match matches.as_slice() {
+            [op] => *op,
+            [] => return Err(JJError::OperationNotFound(op_id.to_string())),
+            _ => {
+                return Err(JJError::InvalidConfig(format!(
+                    "Operation ID prefix {} is ambiguous",
+                    op_id
+                )))
+            }
+        };
+        if !op.is_snapshot() {
+            return Err(JJError::InvalidConfig(format!(
+                "Operation {} is not a snapshot ({})",
+                op.operation_id, op.command
+            )));
+        }
+
+        let args = Args::new(["op", "restore"]).arg(op.operation_id.as_str());
+        self.execute_checked(args.into()).await
+    }
+
+    /// Undo the last operation, unless it reached a remote
+    ///
+    /// Looks at the newest successful operation this wrapper logged for the
+    /// repository, skipping read-only commands. If it was a remote operation (e.g. a push), the
+    /// remote keeps its copy, so undoing locally only desynchronises the two:
+    /// this returns [`JJError::NotUndoable`] without running jj. Call
+    /// [`undo`](Self::undo) to force it. Operations run outside the wrapper
+    /// aren't seen.
+    pub async fn undo_safe(&self) -> Result<JJResult> {
+        let last = self
+            .operation_log
+            .lock()
+            .unwrap()
+            .get_all()
+            .into_iter()
+            .rev()
+            .find(|op| {
+                let args: Vec<String> = op
+                    .command
+                    .strip_prefix("jj ")
+                    .unwrap_or(&op.command)
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect();
+                op.success
+                    && op.get_operation_type().category() != OperationCategory::Read
+                    && !Self::is_read_only_command(&args)
+                    && op
+                        .get_metadata("repo_path")
+                        .is_none_or(|path| path == self.config.repo_path)
+            });
+
+        if let Some(op) = last.filter(|op| op.is_remote_operation()) {
+            return Err(JJError::NotUndoable(format!("{} ({})", op.operation_type, op.command)));
+        }
+        self.execute_checked(vec!["undo".to_string()]).await
+    }
+
+    /// Poll `jj op log` every `interval` and report new operations
+    ///
+    /// The first poll only records where the log stands; after that,
+    /// `callback` receives operations (oldest first) that appeared since the
+    /// previous poll, including ones made outside this wrapper. Return
+    /// `ControlFlow::Break` from the callback to stop, or drop the future
+    /// (e.g. abort its task) to cancel between polls. At most
+    /// [`WATCH_POLL_LIMIT`] operations are read per poll. Polls bypass hooks
+    /// and the output cache, and are not added to the operation log.
+    pub async fn watch<F>(&self, interval: std::time::Duration, mut callback: F) -> Result<()>
+    where
+        F: FnMut(Vec<JJOperation>) -> ControlFlow<()>,
+    {
+        let mut last_seen = self.poll_op_log().await?.get_recent(1).into_iter().next().map(|op| op.operation_id);
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let polled = self.poll_op_log().await?;
+            let new_ops = match &last_seen {
+                Some(id) => polled.operations_after(id).unwrap_or_else(|_| polled.get_all()),
+                None => polled.get_all(),
+            };
+            let Some(newest) = new_ops.last() else {
+                continue;
+            };
+            last_seen = Some(newest.operation_id.clone());
+
+            if callback(new_ops).is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read the newest [`WATCH_POLL_LIMIT`] jj operations into a log, oldest first
+    async fn poll_op_log(&self) -> Result<JJOperationLog> {
+        let stdout = self.execute_unlogged(Self::op_log_args(Some(WATCH_POLL_LIMIT))).await?;
+        let log = JJOperationLog::new(WATCH_POLL_LIMIT);
+        for op in Self::parse_op_json(&stdout)?.into_iter().rev() {
+            log.add_operation(op);
+        }
+        Ok(log)
+    }
+
+    /// Run a command without hooks, the output cache or operation logging
+    ///
+    /// For the wrapper's own background reads, which would otherwise fill the
+    /// operation log with themselves.
+    async fn execute_unlogged(&self, args: Vec<String>) -> Result<String> {
+        Self::validate_args(&args)?;
+        let timeout = std::time::Duration::from_millis(self.config.timeout_ms as u64);
+        let run_args = self.with_config_overrides(&args);
+        let _permit = self
+            .op_permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| JJError::Unknown(format!("Concurrency limiter closed: {}", e)))?;
+        self.executor
+            .execute(&self.config.jj_path, self.repo_path(), &run_args, timeout)
+            .await
+            .map_err(Self::classify_failure)
+    }
+
+    /// Give untyped executor failures a typed error where jj's stderr allows
+    ///
+    /// Custom executors may report raw stderr as `CommandFailed`; this maps
+    /// e.g. "There is no jj repo in ..." to [`JJError::NotARepository`] the
+    /// same way the native executor does.
+    fn classify_failure(error: JJError) -> JJError {
+        match error {
+            JJError::CommandFailed(stderr) => JJError::classify(&stderr),
+            other => other,
+        }
+    }
+
+    /// Parse output produced with [`OP_JSON_TEMPLATE`]
+    fn parse_op_json(output: &str) -> Result<Vec<JJOperation>> {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<OpJson>(line)
+                    .map(JJOperation::from)
+                    .map_err(|e| JJError::ParseError(format!("Invalid operation JSON: {}", e)))
+            })
+            .collect()
+    }
+
+    // ========== File Access ==========
+
+    /// Read a file as of `revision` without checking it out
+    ///
+    /// Uses `jj file show`, or `jj cat` on jj older than 0.19. Output that
+    /// is not UTF-8 text (NUL bytes or undecodable sequences) is rejected
+    /// with [`JJError::BinaryFile`] instead of being returned lossily.
+    pub async fn file_at(&self, revision: &str, path: &str) -> Result<String> {
+        let args = Args::new(self.file_subcommand("show", "cat").await)
+            .flag("-r", revision)
+            .arg(path);
+
+        let content = self.execute_checked(args.into()).await?.stdout;
+        if content.contains('\0') || content.contains(char::REPLACEMENT_CHARACTER) {
+            return Err(JJError::BinaryFile(path.to_string()));
+        }
+        Ok(content)
+    }
+
+    /// List the files in `revision`, sorted, as paths relative to the repository root
+    ///
+    /// `path_prefix` restricts the listing to a file or directory. Uses
+    /// `jj file list`, or `jj files` on jj older than 0.19.
+    pub async fn list_files(
+        &self,
+        revision: &str,
+        path_prefix: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let args = Args::new(self.file_subcommand("list", "files").await)
+            .flag("-r", revision)
+            .opt_arg(path_prefix);
+
+        let mut files: Vec<String> = self
+            .execute_checked(args.into())
+            .await?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Arguments for a `jj file` subcommand, or its pre-0.19 top-level name
+    ///
+    /// Assumes the current names if the version cannot be detected.
+    async fn file_subcommand(&self, current: &str, legacy: &str) -> Vec<String> {
+        match self.version().await {
+            Ok(version) if !version.supports_file_commands() => vec![legacy.to_string()],
+            _ => vec!["file".to_string(), current.to_string()],
+        }
+    }
+
+    // ========== Tags ==========
+
+    /// Point tag `name` at `revision` (`jj tag set <name> -r <revision>`)
+    ///
+    /// Needs a jj with `jj tag set`; older releases only read tags imported
+    /// from git and fail this command.
+    pub async fn tag_create(&self, name: &str, revision: &str) -> Result<JJResult> {
+        if name.trim().is_empty() {
+            return Err(JJError::InvalidConfig("tag_create needs a tag name".to_string()));
+        }
+        self.execute_checked(Args::new(["tag", "set", name]).flag("-r", revision).into())
+            .await
+    }
+
+    /// Names of all tags, in `jj tag list` order
+    pub async fn tag_list(&self) -> Result<Vec<String>> {
+        let result = self.execute_checked(vec!["tag".to_string(), "list".to_string()]).await?;
+        Ok(Self::parse_tag_list(&result.stdout))
+    }
+
+    /// Parse `jj tag list` output
+    ///
+    /// Each tag starts an unindented `name: <change> <commit> <description>`
+    /// line; names that need it are quoted, and conflicted tags end in
+    /// ` (conflicted):`. Indented lines (remote or conflicting targets) are
+    /// skipped.
+    fn parse_tag_list(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with(char::is_whitespace))
+            .filter_map(|line| match line.strip_prefix('"') {
+                Some(quoted) => quoted.split_once('"').map(|(name, _)| name),
+                None => line
+                    .split_once(':')
+                    .map(|(name, _)| name.trim_end_matches(" (conflicted)")),
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    // ========== Working Copy ==========
+
+    /// Check whether the working copy has no changes and no conflicts
+    ///
+    /// The usual gate before committing or switching revisions.
+    pub async fn is_clean(&self) -> Result<bool> {
+        let result = self.execute_checked(vec!["status".to_string()]).await?;
+        Ok(Self::status_is_clean(&result.stdout))
+    }
+
+    /// Interpret `jj status` output
+    ///
+    /// Dirty when jj lists "Working copy changes:"; conflicted when it reports
+    /// unresolved conflicts or marks the working-copy commit `(conflict)`.
+    fn status_is_clean(output: &str) -> bool {
+        let mut clean = true;
+        for line in output.lines() {
+            let line = line.trim();
+            if line.starts_with("Working copy changes:")
+                || line.contains("There are unresolved conflicts")
+                || (line.starts_with("Working copy") && line.contains("(conflict)"))
+            {
+                clean = false;
+            }
+        }
+        clean
+    }
+
+    /// List the files changed in the working copy, per `jj status`
+    ///
+    /// Unlike [`diff`](Self::diff), which compares arbitrary revisions, this
+    /// is exactly what `@` changes relative to its parent, plus conflict
+    /// state: the list to format, commit or review.
+    pub async fn working_copy_changes(&self) -> Result<Vec<FileChange>> {
+        let result = self.execute_checked(vec!["status".to_string()]).await?;
+        Ok(Self::parse_status_changes(&result.stdout))
+    }
+
+    /// Parse the file sections of `jj status` output
+    ///
+    /// Change lines under "Working copy changes:" are `<M|A|D|R|C> <path>`,
+    /// with renames and copies written `old => new` or `dir/{old => new}`.
+    /// Files under "There are unresolved conflicts at these paths:" are
+    /// marked conflicted, and added if they weren't changed in `@` itself.
+    fn parse_status_changes(output: &str) -> Vec<FileChange> {
+        let mut changes: Vec<FileChange> = Vec::new();
+        let mut in_changes = false;
+        let mut in_conflicts = false;
+
+        for line in output.lines() {
+            if line.starts_with("Working copy changes:") {
+                (in_changes, in_conflicts) = (true, false);
+                continue;
+            }
+            if line.starts_with("There are unresolved conflicts at these paths:") {
+                (in_changes, in_conflicts) = (false, true);
+                continue;
+            }
+
+            if in_changes {
+                let Some((code, path)) = line.split_once(' ') else {
+                    in_changes = false;
+                    continue;
+                };
+                let kind = match code {
+                    "M" => ChangeStatus::Modified,
+                    "A" | "C" => ChangeStatus::Added,
+                    "D" => ChangeStatus::Deleted,
+                    "R" => ChangeStatus::Renamed,
+                    _ => {
+                        in_changes = false;
+                        continue;
+                    }
+                };
+                let mut change = FileChange::new(path.to_string(), kind);
+                if code == "R" || code == "C" {
+                    if let Some((old_path, new_path)) = Self::parse_stat_rename(path) {
+                        change.path = new_path;
+                        change.old_path = Some(old_path);
+                    }
+                }
+                changes.push(change);
+            } else if in_conflicts {
+                // "<path>    2-sided conflict[ including ...]"
+                let Some(path) = line
+                    .find("-sided conflict")
+                    .and_then(|end| line[..end].trim_end().rsplit_once(char::is_whitespace))
+                    .map(|(path, _count)| path.trim())
+                else {
+                    in_conflicts = false;
+                    continue;
+                };
+                match changes.iter_mut().find(|change| change.path == path) {
+                    Some(change) => change.conflicted = true,
+                    None => {
+                        let mut change = FileChange::new(path.to_string(), ChangeStatus::Conflicted);
+                        change.conflicted = true;
+                        changes.push(change);
+                    }
+                }
+            }
+        }
+
+        changes
+    }
+
+    // ========== Bookmark Sync Status ==========
+
+    /// Compare a local bookmark with its remote tracking counterpart
+    ///
+    /// Counts commits in `remote..local` (ahead) and `local..remote` (behind).
+    /// Returns an `Untracked` status when no remote bookmark exists.
+    pub async fn branch_status(&self, name: &str) -> Result<BranchStatus> {
+        let (local_fn, remote_fn) = if self.branch_subcommand().await == "bookmark" {
+            ("bookmarks", "remote_bookmarks")
+        } else {
+            ("branches", "remote_branches")
+        };
+        let local = format!("{}(exact:\"{}\")", local_fn, name);
+        let remote = format!("{}(exact:\"{}\")", remote_fn, name);
+
+        if self.count_revset(&remote).await? == 0 {
+            return Ok(BranchStatus::untracked(name.to_string()));
+        }
+
+        let ahead = self.count_revset(&format!("{}..{}", remote, local)).await?;
+        let behind = self.count_revset(&format!("{}..{}", local, remote)).await?;
+        Ok(BranchStatus::new(name.to_string(), ahead, behind))
+    }
+
+    /// List local and remote bookmarks with tracking and conflict state
+    ///
+    /// Runs `jj bookmark list -a` with a JSON template, unlike the plain
+    /// `name: target` output read by `branch_list`. Remote bookmarks come
+    /// back with `is_remote` and their `remote`; a local bookmark tracked by a
+    /// remote gets `is_tracking`, `tracking_remote` and the ahead/behind hints
+    /// of the first such remote. Conflicted (divergent) bookmarks have `conflicted` set and an
+    /// empty `target`. jj's internal `@git` refs and deleted local bookmarks
+    /// are left out.
+    pub async fn bookmark_list_all(&self) -> Result<Vec<JJBranch>> {
+        let args = Args::new([self.branch_subcommand().await, "list".to_string(), "-a".to_string()])
+            .flag("-T", BOOKMARK_JSON_TEMPLATE);
+        let result = self.execute_checked(args.into()).await?;
+        Self::parse_bookmark_json(&result.stdout)
+    }
+
+    /// Parse output produced with [`BOOKMARK_JSON_TEMPLATE`]
+    fn parse_bookmark_json(output: &str) -> Result<Vec<JJBranch>> {
+        let records = output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<BookmarkJson>(line)
+                    .map_err(|e| JJError::ParseError(format!("Invalid bookmark JSON: {}", e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut branches: Vec<JJBranch> = Vec::new();
+        for record in records.into_iter().filter(|r| r.remote != "git") {
+            let is_remote = !record.remote.is_empty();
+            if !is_remote && !record.present {
+                continue;
+            }
+
+            let mut branch = JJBranch::new(record.name, record.target, is_remote);
+            branch.conflicted = record.conflict;
+            if !is_remote {
+                branches.push(branch);
+                continue;
+            }
+
+            branch.set_remote(record.remote);
+            branch.is_tracking = record.tracked;
+            if record.tracked {
+                branch.ahead = Some(record.remote_behind);
+                branch.behind = Some(record.remote_ahead);
+                // jj lists a bookmark's remotes right after its local entry
+                if let Some(local) = branches
+                    .iter_mut()
+                    .rev()
+                    .find(|b| !b.is_remote && b.name == branch.name && !b.is_tracking)
+                {
+                    local.is_tracking = true;
+                    local.tracking_remote = branch.remote.clone();
+                    local.ahead = branch.ahead;
+                    local.behind = branch.behind;
+                }
+            }
+            branches.push(branch);
+        }
+        Ok(branches)
+    }
+
+    /// Track `name@remote` so the local bookmark follows the remote one
+    ///
+    /// Tracking must be set up before [`branch_status`](Self::branch_status)
+    /// can report meaningful ahead/behind counts.
+    pub async fn bookmark_track(&self, name: &str, remote: &str) -> Result<JJResult> {
+        let target = Self::remote_bookmark_arg(name, remote)?;
+        self.execute_checked(vec![self.branch_subcommand().await, "track".to_string(), target])
+            .await
+    }
+
+    /// Stop tracking `name@remote`
+    pub async fn bookmark_untrack(&self, name: &str, remote: &str) -> Result<JJResult> {
+        let target = Self::remote_bookmark_arg(name, remote)?;
+        self.execute_checked(vec![self.branch_subcommand().await, "untrack".to_string(), target])
+            .await
+    }
+
+    /// Build the `name@remote` argument, rejecting parts jj would misparse
+    fn remote_bookmark_arg(name: &str, remote: &str) -> Result<String> {
+        for (part, value) in [("bookmark name", name), ("remote", remote)] {
+            if value.is_empty() || value.contains('@') || value.contains(char::is_whitespace) {
+                return Err(JJError::InvalidConfig(format!(
+                    "Invalid {} {:?}: must be non-empty without '@' or whitespace",
+                    part, value
+                )));
+            }
+        }
+        Ok(format!("{}@{}", name, remote))
+    }
+
+    /// Count the commits in a revset
+    async fn count_revset(&self, revset: &str) -> Result<u32> {
+        let result = self
+            .execute_checked(vec![
+                "log".to_string(),
+                "-r".to_string(),
+                revset.to_string(),
+                "--no-graph".to_string(),
+                "-T".to_string(),
+                r#"commit_id ++ "\n""#.to_string(),
+            ])
+            .await?;
+        Ok(result.lines().filter(|l| !l.trim().is_empty()).count() as u32)
+    }
+
+    // ========== History Editing ==========
+
+    /// Set the description of `revision` (`jj describe -r <rev> -m <msg>`)
+    ///
+    /// Unlike [`describe`](Self::describe) this can amend any commit, e.g. to
+    /// fill in messages across a stack. Returns the logged operation.
+    pub async fn describe_revision(&self, revision: &str, message: &str) -> Result<JJOperation> {
+        if revision.trim().is_empty() {
+            return Err(JJError::InvalidConfig("describe_revision needs a revision".to_string()));
+        }
+
+        self.execute_checked(vec![
+            "describe".to_string(),
+            "-r".to_string(),
+            revision.to_string(),
+            "-m".to_string(),
+            message.to_string(),
+        ])
+        .await?;
+
+        self.operation_log
+            .lock()
+            .unwrap()
+            .get_recent(1)
+            .into_iter()
+            .next()
+            .ok_or_else(|| JJError::OperationNotFound("describe".to_string()))
+    }
+
+    /// Describe the working copy with `lines` joined by line breaks
+    ///
+    /// The message is passed on stdin (`jj describe --stdin`), so it is not
+    /// subject to argument validation and can hold any text.
+    pub async fn describe_multiline(&self, lines: &[&str]) -> Result<JJResult> {
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            return Err(JJError::InvalidConfig("describe_multiline needs a non-empty message".to_string()));
+        }
+        self.describe_stdin(&lines.join("\n")).await
+    }
+
+    /// Describe the working copy with the contents of the file at `path`
+    ///
+    /// The file is passed to `jj describe --stdin` byte for byte.
+    pub async fn describe_from_file(&self, path: &Path) -> Result<JJResult> {
+        if !path.is_file() {
+            return Err(JJError::InvalidConfig(format!(
+                "Commit message file not found: {}",
+                path.display()
+            )));
+        }
+
+        let message = fs::read_to_string(path)?;
+        self.describe_stdin(&message).await
+    }
+
+    /// Run `jj describe --stdin` with `message` as its input
+    async fn describe_stdin(&self, message: &str) -> Result<JJResult> {
+        self.execute_checked_with_stdin(vec!["describe".to_string(), "--stdin".to_string()], message)
+            .await
+    }
+
+    /// Split `paths` out of a revision (default `@`) into a first commit
+    ///
+    /// The rest of the changes stay in a second commit. jj opens an editor
+    /// for the first commit's description, pre-filled with the revision's
+    /// current one; the editor is pointed at a command that exits without
+    /// touching the file, so the description is kept as-is, line breaks
+    /// included.
+    pub async fn split(&self, revision: Option<&str>, paths: &[&str]) -> Result<JJResult> {
+        if paths.is_empty() {
+            return Err(JJError::InvalidConfig(
+                "split needs at least one path; without paths jj opens the diff editor".to_string(),
+            ));
+        }
+
+        let args = Args::new(["split"])
+            .flag("--config", format!("ui.editor={}", toml_string(KEEP_DESCRIPTION_EDITOR)))
+            .flag("-r", revision.unwrap_or("@"))
+            .args(paths.iter().copied());
+
+        self.execute_checked(args.into()).await
+    }
+
+    /// Start a new commit on top of `parents` (`jj new <parents>... [-m msg]`)
+    ///
+    /// Unlike [`new_commit`](Self::new_commit), which builds on the working copy,
+    /// this can start work on any commit; several parents make a merge.
+    pub async fn new_on(&self, parents: &[&str], message: Option<&str>) -> Result<JJResult> {
+        if parents.is_empty() {
+            return Err(JJError::InvalidConfig("new_on needs at least one parent".to_string()));
+        }
+
+        let args = Args::new(["new"]).args(parents.iter().copied()).opt_flag("-m", message);
+        self.execute_checked(args.into()).await
+    }
+
+    /// Create a merge commit with `revisions` as parents (`jj new <rev>...`)
+    pub async fn merge(&self, revisions: &[&str], message: Option<&str>) -> Result<JJResult> {
+        if revisions.len() < 2 {
+            return Err(JJError::InvalidConfig(format!(
+                "merge needs at least two revisions, got {}",
+                revisions.len()
+            )));
+        }
+        self.new_on(revisions, message).await
+    }
+
+    /// Copy a revision, optionally onto `destination`, and return the copy's commit ID
+    ///
+    /// The original is left untouched. jj only reports the new commit on
+    /// stderr, so the copy is looked up afterwards as the newest child of its
+    /// base (`destination`, or the original's parents).
+    pub async fn duplicate(&self, revision: &str, destination: Option<&str>) -> Result<String> {
+        let args = Args::new(["duplicate", revision]).opt_flag("-d", destination);
+        self.execute_checked(args.into()).await?;
+
+        let base = match destination {
+            Some(destination) => format!("({})", destination),
+            None => format!("({})-", revision),
+        };
+        let revset = format!("latest({}+ ~ ({}))", base, revision);
+        self.query_commits(&revset)
+            .await?
+            .into_iter()
+            .next()
+            .map(|commit| commit.id)
+            .ok_or_else(|| {
+                JJError::ParseError(format!("Could not find the duplicate of {}", revision))
+            })
+    }
+
+    /// Restore `paths` in the working copy to their state in `from`
+    ///
+    /// Unlike [`restore`](Self::restore), which restores from the parent, this
+    /// recovers files from any earlier revision. Paths are required: a bare
+    /// `jj restore --from` would reset the whole working copy.
+    pub async fn restore_from(&self, from: &str, paths: &[&str]) -> Result<JJResult> {
+        if paths.is_empty() {
+            return Err(JJError::InvalidConfig(
+                "restore_from needs at least one path".to_string(),
+            ));
+        }
+
+        let args = Args::new(["restore"]).flag("--from", from).args(paths.iter().copied());
+        self.execute_checked(args.into()).await
+    }
+
+    /// Move only `paths` from `from` into `into` (`jj squash --from --into <paths>`)
+    ///
+    /// The rest of `from` stays put, which suits moving a single file's fix
+    /// into an earlier commit of a stack. `into` keeps its description, so no
+    /// editor is opened.
+    pub async fn squash_paths(&self, from: &str, into: &str, paths: &[&str]) -> Result<JJResult> {
+        if paths.is_empty() {
+            return Err(JJError::InvalidConfig(
+                "squash_paths needs at least one path; use squash to move the whole commit".to_string(),
+            ));
+        }
+
+        let args = Args::new(["squash"])
+            .flag("--from", from)
+            .flag("--into", into)
+            .arg("--use-destination-message")
+            .args(paths.iter().copied());
+        self.execute_checked(args.into()).await
+    }
+
+    /// Move working-copy changes into the ancestors that last touched those lines
+    ///
+    /// Runs `jj absorb`, optionally limited to `paths`. Use
+    /// [`absorbed_revisions`](Self::absorbed_revisions) on the result to see
+    /// which commits were rewritten.
+    pub async fn absorb(&self, paths: &[&str]) -> Result<JJResult> {
+        self.execute_checked(Args::new(["absorb"]).args(paths.iter().copied()).into())
+            .await
+    }
+
+    /// Change ids of the commits an [`absorb`](Self::absorb) rewrote
+    ///
+    /// jj lists them, indented, under "Absorbed changes into these revisions:"
+    /// (on stderr in current releases, so both streams are checked).
+    pub fn absorbed_revisions(result: &JJResult) -> Vec<String> {
+        let output = format!("{}\n{}", result.stdout, result.stderr);
+        let mut revisions = Vec::new();
+        let mut in_list = false;
+        for line in output.lines() {
+            if line.starts_with("Absorbed changes into") {
+                in_list = true;
+            } else if in_list && line.starts_with(char::is_whitespace) {
+                if let Some(change_id) = line.split_whitespace().next() {
+                    revisions.push(change_id.to_string());
+                }
+            } else {
+                in_list = false;
+            }
+        }
+        revisions
+    }
+
+    /// Run the configured code formatters over `revset` (`jj fix -s <revset>`)
+    ///
+    /// Without a revset jj fixes its default set (mutable commits touched by
+    /// the working copy's ancestry). Formatters come from `fix.tools.*` config.
+    /// See [`fixed_commits`](Self::fixed_commits) for what changed.
+    pub async fn fix(&self, revset: Option<&str>) -> Result<JJResult> {
+        self.execute_checked(Args::new(["fix"]).opt_flag("-s", revset).into()).await
+    }
+
+    /// `(fixed, checked)` commit counts reported by [`fix`](Self::fix)
+    ///
+    /// jj prints "Fixed N commits of M checked." (on stderr in current
+    /// releases); it does not name the files it rewrote. `None` if the line
+    /// is missing.
+    pub fn fixed_commits(result: &JJResult) -> Option<(u32, u32)> {
+        result.stdout.lines().chain(result.stderr.lines()).find_map(|line| {
+            let rest = line.trim().strip_prefix("Fixed ")?;
+            let mut words = rest.split_whitespace();
+            let fixed = words.next()?.parse().ok()?;
+            let checked = words.nth(2)?.parse().ok()?;
+            Some((fixed, checked))
+        })
+    }
+
+    // ========== Batch Operations ==========
+
+    /// Abandon every commit in `revset` with a single `jj abandon`
+    pub async fn abandon_many(&self, revset: &str) -> Result<JJResult> {
+        self.execute_checked(vec!["abandon".to_string(), revset.to_string()]).await
+    }
+
+    /// Run operations in order, stopping at the first failure
+    ///
+    /// Each operation is logged individually.
+    pub async fn batch(&self, ops: Vec<BatchOp>) -> Result<Vec<JJResult>> {
+        self.batch_with_options(ops, false).await
+    }
+
+    /// Run operations in order
+    ///
+    /// With `continue_on_error`, failures are recorded as results with a
+    /// non-zero exit code and the error in `stderr`, and the remaining
+    /// operations still run. Otherwise the first failure is returned.
+    pub async fn batch_with_options(
+        &self,
+        ops: Vec<BatchOp>,
+        continue_on_error: bool,
+    ) -> Result<Vec<JJResult>> {
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            match self.execute_checked(op.into_args()).await {
+                Ok(result) => results.push(result),
+                Err(e) if continue_on_error => {
+                    results.push(JJResult::new(String::new(), e.to_string(), 1, 0));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(results)
+    }
+
+    // ========== Remote Operations ==========
+
+    /// Fetch from a git remote (`jj git fetch [--remote <remote>]`)
+    ///
+    /// Without a remote, jj uses its configured default (usually `origin`).
+    pub async fn git_fetch(&self, remote: Option<&str>) -> Result<JJResult> {
+        self.execute_checked(Args::new(["git", "fetch"]).opt_flag("--remote", remote).into())
+            .await
+    }
+
+    /// Push to a git remote (`jj git push [--remote <remote>] [-b <bookmark>] [--allow-new]`)
+    ///
+    /// Without a bookmark, jj pushes all tracked bookmarks that point into the
+    /// working copy's ancestry.
+    pub async fn git_push(
+        &self,
+        remote: Option<&str>,
+        bookmark: Option<&str>,
+        allow_new: bool,
+    ) -> Result<JJResult> {
+        let args = Args::new(["git", "push"])
+            .opt_flag("--remote", remote)
+            .opt_flag("-b", bookmark)
+            .switch("--allow-new", allow_new);
+        self.execute_checked(args.into()).await
+    }
+
+    // ========== jj Configuration ==========
+
+    /// Read a jj config value (`jj config get <key>`)
+    ///
+    /// Returns [`JJError::ConfigKeyNotFound`] when the key is unset.
+    pub async fn config_get(&self, key: &str) -> Result<String> {
+        let result = self
+            .execute_checked(vec!["config".to_string(), "get".to_string(), key.to_string()])
+            .await?;
+        Ok(result.stdout.trim_end_matches('\n').to_string())
+    }
+
+    /// Set a jj config value in the repo or user config file
+    pub async fn config_set(&self, key: &str, value: &str, scope: ConfigScope) -> Result<JJResult> {
+        self.execute_checked(vec![
+            "config".to_string(),
+            "set".to_string(),
+            scope.flag().to_string(),
+            key.to_string(),
+            value.to_string(),
+        ])
+        .await
+    }
+
+    // ========== Repository Setup ==========
+
+    /// Clone a git repository into `dest` (`jj git clone <source> <dest>`)
+    ///
+    /// Returns a wrapper with the same settings and executor whose `repo_path`
+    /// is `dest`.
+    pub async fn git_clone(&self, source: &str, dest: &Path) -> Result<JJWrapper> {
+        let config = self.config_for_new_repo(dest)?;
+        self.execute_checked(vec![
+            "git".to_string(),
+            "clone".to_string(),
+            source.to_string(),
+            config.repo_path.clone(),
+        ])
+        .await?;
+        Ok(Self::with_config_checked(config)?.with_executor(self.executor.clone()))
+    }
+
+    /// Initialize a new repository at `path`
+    ///
+    /// Runs `jj git init` when `git_backed`, otherwise `jj init` (native
+    /// backend; only available on older jj releases). Returns a wrapper with
+    /// the same settings and executor whose `repo_path` is `path`.
+    pub async fn init(&self, path: &Path, git_backed: bool) -> Result<JJWrapper> {
+        let config = self.config_for_new_repo(path)?;
+        let args = Args::default()
+            .switch("git", git_backed)
+            .arg("init")
+            .arg(config.repo_path.clone());
+        self.execute_checked(args.into()).await?;
+        Ok(Self::with_config_checked(config)?.with_executor(self.executor.clone()))
+    }
+
+    /// Build the config for a repository about to be created at `path`
+    fn config_for_new_repo(&self, path: &Path) -> Result<JJConfig> {
+        if path.join(".jj").exists() {
+            return Err(JJError::InvalidConfig(format!(
+                "{} already contains a jj repository",
+                path.display()
+            )));
+        }
+
+        let repo_path = path.to_string_lossy().to_string();
+        let config = self.config.clone().with_repo_path(repo_path.clone());
+        if config.repo_path != repo_path {
+            return Err(JJError::InvalidConfig(format!(
+                "Invalid repository path: {}",
+                path.display()
+            )));
+        }
+        Ok(config)
+    }
+
+    // ========== Tagging Rules ==========
+
+    /// Add a rule that tags matching operations before they are logged
+    pub fn add_tagging_rule(&self, rule: TaggingRule) {
+        self.tagging_rules.lock().unwrap().push(rule);
+    }
+
+    // ========== Task Correlation ==========
+
+    /// Stamp `task_id` on every operation logged until [`end_task`](Self::end_task)
+    ///
+    /// The ID is stored in the operation's `task_id` metadata, so the commands
+    /// behind one agent goal can be pulled back out with
+    /// [`JJOperationLog::filter_by_task`]. Tasks don't nest: beginning a new
+    /// one replaces the current one. Clones of the wrapper share the task.
+    pub fn begin_task(&self, task_id: &str) {
+        *self.current_task.lock().unwrap() = Some(task_id.to_string());
+    }
+
+    /// Stop stamping operations, returning the task that was active
+    pub fn end_task(&self) -> Option<String> {
+        self.current_task.lock().unwrap().take()
+    }
+
+    // ========== Operation Hooks ==========
+
+    /// Register a hook fired before each command
+    ///
+    /// Hooks run in registration order and may rewrite `event.context.args`;
+    /// later hooks see earlier edits. Returning an error vetoes the command.
+    pub fn register_pre_operation_hook<F>(&self, hook: F)
+    where
+        F: Fn(&mut JJHookEvent) -> Result<()> + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().add_pre(Arc::new(hook));
+    }
+
+    /// Register a hook fired after each command
+    pub fn register_post_operation_hook<F>(&self, hook: F)
+    where
+        F: Fn(&mut JJHookEvent) -> Result<()> + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().add_post(Arc::new(hook));
+    }
+
+    /// Register an async hook awaited before each command, after the sync hooks
+    pub fn register_async_pre_operation_hook<H: AsyncHook + 'static>(&self, hook: H) {
+        self.hooks.lock().unwrap().add_async_pre(Arc::new(hook));
+    }
+
+    /// Register an async hook awaited after each command, after the sync hooks
+    ///
+    /// Failures are reported but do not fail the command.
+    pub fn register_async_post_operation_hook<H: AsyncHook + 'static>(&self, hook: H) {
+        self.hooks.lock().unwrap().add_async_post(Arc::new(hook));
+    }
+}
+
+impl Default for JJWrapper {
+    fn default() -> Self {
+        Self::new().expect("Failed to create default JJWrapper")
+    }
+}
+
+/// A [`JJWrapper`] pinned to a historical operation
+///
+/// Created by [`JJWrapper::at_op`]. Reads (`log`, `status`, `diff`, ...) are
+/// evaluated as of that operation; commands that would modify the repository
+/// are rejected with [`JJError::InvalidConfig`] before reaching jj.
+#[derive(Clone)]
+pub struct JJWrapperView {
+    wrapper: JJWrapper,
+    op_id: String,
+}
+
+impl JJWrapperView {
+    /// The operation this view is pinned to
+    pub fn operation_id(&self) -> &str {
+        &self.op_id
+    }
+
+    /// Run a read-only jj command as of the view's operation
+    pub async fn execute(&self, args: Vec<String>) -> Result<JJResult> {
+        if !JJWrapper::is_read_only_command(&args) {
+            return Err(JJError::InvalidConfig(format!(
+                "jj {} would modify the repository; views at operation {} are read-only",
+                args.join(" "),
+                self.op_id
+            )));
+        }
+
+        let mut args = args;
+        let at = args.len().min(1);
+        args.splice(at..at, ["--at-operation".to_string(), self.op_id.clone()]);
+        self.wrapper.execute_checked(args).await
+    }
+
+    /// Commits in `revset` (default `@`), newest first
+    pub async fn log(&self, revset: Option<&str>, limit: Option<usize>) -> Result<Vec<JJCommit>> {
+        let args = Args::new(["log"])
+            .opt_flag("-r", revset)
+            .opt_flag("--limit", limit.map(|l| l.to_string()))
+            .args(["--no-graph", "-T", COMMIT_JSON_TEMPLATE]);
+        JJWrapper::parse_commit_json(&self.execute(args.into()).await?.stdout)
+    }
+
+    /// `jj status` as of the view's operation
+    pub async fn status(&self) -> Result<JJResult> {
+        self.execute(vec!["status".to_string()]).await
+    }
+
+    /// Diff two revisions as they were at the view's operation
+    pub async fn diff(&self, from: &str, to: &str) -> Result<JJDiff> {
+        let args = Args::new(["diff", "--git"]).flag("--from", from).flag("--to", to);
+        JJWrapper::parse_diff(&self.execute(args.into()).await?.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::BoxFuture;
+    use std::time::Duration;
+
+    /// Executor that records arguments and returns canned output
+    #[derive(Default)]
+    struct MockExecutor {
+        calls: Mutex<Vec<Vec<String>>>,
+        repo_paths: Mutex<Vec<Option<String>>>,
+        /// Input passed with `execute_with_stdin`, in call order
+        stdin: Mutex<Vec<String>>,
+        outputs: HashMap<String, String>,
+        fail_on: Option<String>,
+        /// Raw stderr returned as `CommandFailed` for every command
+        error: Option<String>,
+        delay: Option<Duration>,
+    }
+
+    impl MockExecutor {
+        /// Return `output` for commands containing the argument `command`
+        fn with_output(command: &str, output: &str) -> Self {
+            let mut executor = Self::default();
+            executor.outputs.insert(command.to_string(), output.to_string());
+            executor
+        }
+
+        /// Fail any command whose first argument is `command`
+        fn failing_on(command: &str) -> Self {
+            Self {
+                fail_on: Some(command.to_string()),
+                ..Self::default()
+            }
+        }
+
+        fn with_version(version: &str) -> Self {
+            Self::with_output("--version", version)
+        }
+    }
+
+    impl CommandExecutor for MockExecutor {
+        fn execute<'a>(
+            &'a self,
+            _jj_path: &'a str,
+            repo_path: Option<&'a str>,
+            args: &'a [String],
+            _command_timeout: Duration,
+        ) -> BoxFuture<'a, Result<String>> {
+            self.calls.lock().unwrap().push(args.to_vec());
+            self.repo_paths.lock().unwrap().push(repo_path.map(str::to_string));
+            if args.first() == self.fail_on.as_ref() {
+                let stderr = format!("Error: {} failed", args[0]);
+                return Box::pin(async move { Err(JJError::CommandFailed(stderr)) });
+            }
+            if let Some(stderr) = self.error.clone() {
+                return Box::pin(async move { Err(JJError::CommandFailed(stderr)) });
+            }
+            // Canned output is keyed by any argument (command name, revset, ...)
+            let output = args
+                .iter()
+                .find_map(|arg| self.outputs.get(arg))
+                .cloned()
+                .unwrap_or_default();
+            let delay = self.delay;
+            Box::pin(async move {
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                Ok(output)
+            })
+        }
+
+        fn execute_with_stdin<'a>(
+            &'a self,
+            jj_path: &'a str,
+            repo_path: Option<&'a str>,
+            args: &'a [String],
+            stdin: &'a str,
+            command_timeout: Duration,
+        ) -> BoxFuture<'a, Result<String>> {
+            self.stdin.lock().unwrap().push(stdin.to_string());
+            self.execute(jj_path, repo_path, args, command_timeout)
+        }
+    }
+
+    fn mock_wrapper() -> (JJWrapper, Arc<MockExecutor>) {
+        let executor = Arc::new(MockExecutor::default());
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+        (wrapper, executor)
+    }
+
+    #[test]
+    fn test_wrapper_creation() {
+        let wrapper = JJWrapper::new();
+        assert!(wrapper.is_ok());
+
+        let config = JJConfig::default().with_verbose(true);
+        let wrapper = JJWrapper::with_config_checked(config);
+        assert!(wrapper.is_ok());
+    }
+
+    #[test]
+    fn test_detect_operation_type() {
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["absorb", "src/lib.rs"]),
+            OperationType::Absorb
+        );
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["workspace", "add", "../agent-2"]),
+            OperationType::Workspace
+        );
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["file", "chmod", "x", "run.sh"]),
+            OperationType::File
+        );
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["sign", "-r", "@-"]),
+            OperationType::Sign
+        );
+        assert_eq!(JJWrapper::detect_operation_type(&["fix", "-s", "@"]), OperationType::Fix);
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["describe", "-m", "test"]),
+            OperationType::Describe
+        );
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["new"]),
+            OperationType::New
+        );
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["git", "fetch"]),
+            OperationType::GitFetch
+        );
+    }
+
+    #[test]
+    fn test_parse_conflicts() {
+        let output = "file1.txt    2-sided conflict\nfile2.rs    3-sided conflict";
+        let conflicts = JJWrapper::parse_conflicts(output).unwrap();
+
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].path, "file1.txt");
+        assert_eq!(conflicts[0].num_sides(), 2);
+        assert_eq!(conflicts[1].path, "file2.rs");
+        assert_eq!(conflicts[1].num_sides(), 3);
+        assert!(conflicts.iter().all(|c| c.num_conflicts == 0));
+    }
+
+    #[tokio::test]
+    async fn test_conflict_summary_counts_hunks() {
+        let hunk = "<<<<<<< Conflict 1 of 1\n+++++++ Contents of side #1\na\n------- Contents of base\nb\n+++++++ Contents of side #2\nc\n>>>>>>> Conflict 1 of 1 ends\n";
+        let mut executor = MockExecutor::with_version("jj 0.35.0");
+        executor.outputs.insert(
+            "--list".to_string(),
+            "three.rs    3-sided conflict\nfour.rs    2-sided conflict\n".to_string(),
+        );
+        executor.outputs.insert(
+            "three.rs".to_string(),
+            hunk.replace("side #2\nc", "side #2\nc\n+++++++ Contents of side #3\nd"),
+        );
+        executor.outputs.insert("four.rs".to_string(), hunk.repeat(4));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(Arc::new(executor));
+
+        let ranked = wrapper.conflict_summary(None).await.unwrap();
+
+        let summary: Vec<(&str, u32, u32)> = ranked
+            .iter()
+            .map(|c| (c.path.as_str(), c.num_sides(), c.num_conflicts))
+            .collect();
+        assert_eq!(summary, vec![("four.rs", 2, 4), ("three.rs", 3, 1)]);
+        assert_eq!(ranked[1].severity(), crate::types::ConflictSeverity::Low);
+    }
+
+    #[test]
+    fn test_parse_diff() {
+        let output = r#"
++++ b/new.txt
+--- a/deleted.txt
++Added line
+-Removed line
+        "#;
+
+        let diff = JJWrapper::parse_diff(output).unwrap();
+        assert_eq!(diff.additions, 1);
+        assert_eq!(diff.deletions, 1);
+    }
+
+    #[test]
+    fn test_parse_diff_per_file() {
+        let output = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1234567..89abcde 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ use std::fmt;
+-use std::io;
++use std::io::{self, Write};
++use std::fs;
+ 
+@@ -10,2 +11,2 @@ fn main() {
+--- old comment
++++ new comment
+ }
+diff --git a/README.md b/README.md
+new file mode 100644
+index 0000000..1111111
+--- /dev/null
++++ b/README.md
+@@ -0,0 +1,2 @@
++# Title
++Body
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 2222222..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+diff --git a/logo.png b/logo.png
+index 3333333..4444444 100644
+Binary files a/logo.png and b/logo.png differ
+diff --git a/a.txt b/b.txt
+similarity index 100%
+rename from a.txt
+rename to b.txt
+";
+
+        let diff = JJWrapper::parse_diff(output).unwrap();
+        assert_eq!(diff.files.len(), 5);
+        assert_eq!(diff.additions, 5);
+        assert_eq!(diff.deletions, 3);
+
+        let lib = &diff.files[0];
+        assert_eq!(lib.path, "src/lib.rs");
+        assert!(matches!(lib.kind, ChangeStatus::Modified));
+        assert_eq!((lib.additions, lib.deletions), (3, 2));
+
+        let readme = &diff.files[1];
+        assert!(matches!(readme.kind, ChangeStatus::Added));
+        assert_eq!((readme.additions, readme.deletions), (2, 0));
+
+        let old = &diff.files[2];
+        assert_eq!(old.path, "old.txt");
+        assert!(matches!(old.kind, ChangeStatus::Deleted));
+        assert_eq!((old.additions, old.deletions), (0, 1));
+
+        let logo = &diff.files[3];
+        assert!(logo.is_binary);
+        assert_eq!(logo.total_changes(), 0);
+
+        let renamed = &diff.files[4];
+        assert!(matches!(renamed.kind, ChangeStatus::Renamed));
+        assert_eq!(renamed.path, "b.txt");
+        assert_eq!(renamed.old_path.as_deref(), Some("a.txt"));
+
+        assert_eq!(diff.modified, vec!["src/lib.rs", "logo.png"]);
+        assert_eq!(diff.added, vec!["README.md"]);
+        assert_eq!(diff.deleted, vec!["old.txt"]);
+        assert_eq!(diff.renamed, vec!["a.txt:b.txt"]);
+    }
+
+    #[test]
+    fn test_parse_diff_hunks() {
+        let output = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ use std::fmt;
+-use std::io;
++use std::io::{self, Write};
++use std::fs;
+ 
+@@ -10,2 +11,2 @@ fn main() {
+--- old comment
++++ new comment
+ }
+diff --git a/logo.png b/logo.png
+Binary files a/logo.png and b/logo.png differ
+diff --git a/empty.txt b/empty.txt
+new file mode 100644
+index 0000000..e69de29
+";
+
+        let diff = JJWrapper::parse_diff(output).unwrap();
+
+        assert_eq!(diff.hunks.len(), 2);
+        let first = &diff.hunks[0];
+        assert_eq!(first.path, "src/lib.rs");
+        assert_eq!((first.old_start, first.old_lines, first.new_start, first.new_lines), (1, 3, 1, 4));
+        assert_eq!(first.section, "");
+        assert_eq!(first.lines, vec![" use std::fmt;", "-use std::io;", "+use std::io::{self, Write};", "+use std::fs;", " "]);
+
+        let second = &diff.hunks[1];
+        assert_eq!((second.old_start, second.old_lines, second.new_start, second.new_lines), (10, 2, 11, 2));
+        assert_eq!(second.section, "fn main() {");
+        assert_eq!(second.lines, vec!["--- old comment", "+++ new comment", " }"]);
+
+        // Binary and empty new files have no hunks
+        assert_eq!(diff.files.len(), 3);
+        assert!(diff.hunks.iter().all(|hunk| hunk.path == "src/lib.rs"));
+    }
+
+    #[test]
+    fn test_parse_hunk_header_single_line_ranges() {
+        let hunk = JJWrapper::parse_hunk_header("old.txt", "-1 +0,0 @@");
+        assert_eq!((hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines), (1, 1, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_diff_stat() {
+        let output = "\
+README.md                      |   2 ++
+src/lib.rs                     |  10 +++++-----
+src/{old_name.rs => new_name.rs} |   4 ++--
+docs/a.md => docs/b.md         |   0
+assets/logo.png                | Bin 0 -> 1234 bytes
+src/big.rs                     | 120 +++++++++++++++++++++++++++++---------
+6 files changed, 97 insertions(+), 39 deletions(-)
+";
+        let stat = JJWrapper::parse_diff_stat(output).unwrap();
+
+        assert_eq!(stat.files.len(), 6);
+        assert_eq!(stat.files[0].path, "README.md");
+        assert_eq!(stat.files[0].additions, 2);
+        assert_eq!(stat.files[0].deletions, 0);
+        assert_eq!(stat.files[1].additions, 5);
+        assert_eq!(stat.files[1].deletions, 5);
+
+        assert!(matches!(stat.files[2].kind, ChangeStatus::Renamed));
+        assert_eq!(stat.files[2].old_path.as_deref(), Some("src/old_name.rs"));
+        assert_eq!(stat.files[2].path, "src/new_name.rs");
+        assert_eq!(stat.files[3].old_path.as_deref(), Some("docs/a.md"));
+        assert_eq!(stat.files[3].path, "docs/b.md");
+        assert_eq!(stat.files[3].total_changes(), 0);
+
+        assert!(stat.files[4].is_binary);
+        // Scaled graph: 29 '+' and 9 '-' split the exact total of 120
+        assert_eq!(stat.files[5].total_changes(), 120);
+        assert_eq!(stat.files[5].additions, 92);
+
+        assert_eq!(
+            stat.summary,
+            DiffSummary { files_changed: 6, insertions: 97, deletions: 39 }
+        );
+    }
+
+    #[test]
+    fn test_parse_diff_stat_summary_variants() {
+        let single = JJWrapper::parse_diff_stat_summary("1 file changed, 1 insertion(+)").unwrap();
+        assert_eq!(single, DiffSummary { files_changed: 1, insertions: 1, deletions: 0 });
+
+        let deletions = JJWrapper::parse_diff_stat_summary("2 files changed, 3 deletions(-)").unwrap();
+        assert_eq!(deletions.deletions, 3);
+        assert_eq!(deletions.insertions, 0);
+
+        assert!(JJWrapper::parse_diff_stat_summary("src/lib.rs | 2 ++").is_none());
+    }
+
+    #[test]
+    fn test_parse_diff_summary() {
+        let output = "\
+src/lib.rs | 10 +++++-----
+README.md  |  2 ++
+2 files changed, 7 insertions(+), 5 deletions(-)
+";
+        assert_eq!(
+            JJWrapper::parse_diff_summary(output),
+            DiffSummary { files_changed: 2, insertions: 7, deletions: 5 }
+        );
+
+        let zero = JJWrapper::parse_diff_summary("0 files changed, 0 insertions(+), 0 deletions(-)\n");
+        assert!(zero.is_empty());
+        assert!(JJWrapper::parse_diff_summary("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_diff_summary_args() {
+        let executor = Arc::new(MockExecutor::with_output(
+            "--stat",
+            "a.rs | 1 +\n1 file changed, 1 insertion(+), 0 deletions(-)\n",
+        ));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let summary = wrapper.diff_summary("@-", "@").await.unwrap();
+
+        assert_eq!(summary, DiffSummary { files_changed: 1, insertions: 1, deletions: 0 });
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["diff", "--stat", "--from", "@-", "--to", "@"]
+        );
+
+        let summary = wrapper.revision_summary("@-").await.unwrap();
+        assert_eq!(summary.files_changed, 1);
+        assert_eq!(executor.calls.lock().unwrap()[1], vec!["diff", "--stat", "-r", "@-"]);
+    }
+
+    #[test]
+    fn test_parse_diff_stat_empty() {
+        let stat = JJWrapper::parse_diff_stat("").unwrap();
+        assert!(stat.files.is_empty());
+        assert!(stat.summary.is_empty());
+
+        let stat =
+            JJWrapper::parse_diff_stat("0 files changed, 0 insertions(+), 0 deletions(-)\n").unwrap();
+        assert!(stat.files.is_empty());
+        assert!(stat.summary.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pre_operation_hooks_rewrite_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        // Enforce a signed description on bare `describe` calls
+        wrapper.register_pre_operation_hook(|event| {
+            let args = &mut event.context.args;
+            if args.len() == 1 && args[0] == "describe" {
+                args.push("-m".to_string());
+                args.push("Signed-off-by: agent".to_string());
+            }
+            Ok(())
+        });
+        // Later hooks see earlier edits
+        wrapper.register_pre_operation_hook(|event| {
+            assert!(event.context.args.contains(&"-m".to_string()));
+            event.context.args.push("--no-edit".to_string());
+            Ok(())
+        });
+
+        wrapper.execute_checked(vec!["describe".to_string()]).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(
+            calls[0],
+            vec![
+                "describe",
+                "--config",
+                r#"ui.editor="false""#,
+                "-m",
+                "Signed-off-by: agent",
+                "--no-edit"
+            ]
+        );
+        drop(calls);
+
+        let ops = wrapper.get_operations(1).unwrap();
+        assert_eq!(ops[0].command, "jj describe -m Signed-off-by: agent --no-edit");
+    }
+
+    #[tokio::test]
+    async fn test_version_detection() {
+        let executor = Arc::new(MockExecutor::with_version("jj 0.24.0-0123abcd4567\n"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let version = wrapper.version().await.unwrap();
+
+        assert_eq!(version.minor, 24);
+        assert!(version.supports_bookmarks());
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["--version"]);
+        assert!(wrapper.get_operations(10).unwrap().is_empty());
+    }
+
+    fn file_executor(version: &str, content: &str) -> Arc<MockExecutor> {
+        let mut executor = MockExecutor::with_version(version);
+        for key in ["file", "cat"] {
+            executor.outputs.insert(key.to_string(), content.to_string());
+        }
+        Arc::new(executor)
+    }
+
+    #[tokio::test]
+    async fn test_file_at() {
+        let executor = file_executor("jj 0.28.0", "fn main() {}\n");
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let content = wrapper.file_at("@-", "src/main.rs").await.unwrap();
+
+        assert_eq!(content, "fn main() {}\n");
+        assert_eq!(
+            executor.calls.lock().unwrap()[1],
+            vec!["file", "show", "-r", "@-", "src/main.rs"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_at_legacy_cat() {
+        let executor = file_executor("jj 0.18.0", "hello\n");
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.file_at("main", "README.md").await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[1], vec!["cat", "-r", "main", "README.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_file_at_rejects_binary() {
+        // What the native executor produces from PNG bytes after lossy decoding
+        let lossy = String::from_utf8_lossy(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").to_string();
+        let executor = file_executor("jj 0.28.0", &lossy);
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+
+        let err = wrapper.file_at("@", "logo.png").await.unwrap_err();
+
+        assert_eq!(err, JJError::BinaryFile("logo.png".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_op_log() {
+        let output = concat!(
+            r#"{"id":"e1f2a3b4c5d6","description":"rebase commit 0123456789abcdef","user":"alice@laptop","start":"2024-05-01T10:00:00.000+00:00","end":"2024-05-01T10:00:00.250+00:00"}"#,
+            "\n",
+            r#"{"id":"a1b2c3d4e5f6","description":"snapshot working copy","user":"alice@laptop","start":"2024-05-01T09:59:00.000+00:00","end":"2024-05-01T09:59:00.010+00:00"}"#,
+            "\n",
+        );
+        let executor = Arc::new(MockExecutor::with_output("op", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let ops = wrapper.op_log(Some(2)).await.unwrap();
+
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].operation_id, "e1f2a3b4c5d6");
+        assert_eq!(ops[0].command, "rebase commit 0123456789abcdef");
+        assert_eq!(ops[0].operation_type, OperationType::Rebase.as_string());
+        assert_eq!(ops[0].user, "alice");
+        assert_eq!(ops[0].hostname, "laptop");
+        assert_eq!(ops[0].timestamp, "2024-05-01T10:00:00.000+00:00");
+        assert_eq!(ops[0].duration_ms, 250);
+        assert!(ops[1].is_snapshot());
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0][..3], ["op", "log", "--no-graph"]);
+        assert_eq!(calls[0][4], OP_JSON_TEMPLATE);
+        assert_eq!(calls[0][5..], ["--limit", "2"]);
+        drop(calls);
+        JJWrapper::validate_args(&[OP_JSON_TEMPLATE.to_string()]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_root() {
+        let executor = Arc::new(MockExecutor::with_output("root", "/home/agent/project\n"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        assert_eq!(wrapper.root().await.unwrap(), PathBuf::from("/home/agent/project"));
+        assert_eq!(wrapper.root().await.unwrap(), PathBuf::from("/home/agent/project"));
+        assert_eq!(*executor.calls.lock().unwrap(), vec![vec!["root".to_string()]]);
+
+        let executor = MockExecutor {
+            error: Some("Error: There is no jj repo in \".\"\n".to_string()),
+            ..Default::default()
+        };
+        let wrapper = wrapper.with_executor(Arc::new(executor));
+        assert_eq!(wrapper.root().await.unwrap_err(), JJError::NotARepository(".".into()));
+    }
+
+    #[tokio::test]
+    async fn test_not_a_repository_is_typed() {
+        let executor = Arc::new(MockExecutor {
+            error: Some("Error: There is no jj repo in \"/tmp/not-a-repo\"\n".to_string()),
+            ..Default::default()
+        });
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+
+        let err = wrapper
+            .execute_checked(vec!["status".to_string()])
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, JJError::NotARepository("/tmp/not-a-repo".into()));
+        assert!(!err.is_recoverable());
+        let ops = wrapper.operation_log.lock().unwrap().get_recent(1);
+        assert!(!ops[0].success);
+    }
+
+    /// Returns the next canned `jj op log` output on each call
+    #[derive(Default)]
+    struct OpLogSequence {
+        outputs: Mutex<Vec<String>>,
+    }
+
+    impl CommandExecutor for OpLogSequence {
+        fn execute<'a>(
+            &'a self,
+            _jj_path: &'a str,
+            _repo_path: Option<&'a str>,
+            _args: &'a [String],
+            _command_timeout: Duration,
+        ) -> BoxFuture<'a, Result<String>> {
+            let mut outputs = self.outputs.lock().unwrap();
+            let output = if outputs.len() > 1 { outputs.remove(0) } else { outputs[0].clone() };
+            Box::pin(async move { Ok(output) })
+        }
+    }
+
+    fn op_json(id: &str, description: &str) -> String {
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "id": id,
+                "description": description,
+                "user": "bob@desktop",
+                "start": "2024-05-01T10:00:00.000+00:00",
+                "end": "2024-05-01T10:00:00.100+00:00",
+            })
+        )
+    }
+
+    /// Tracks how many commands run at the same time
+    #[derive(Default)]
+    struct ConcurrencyProbe {
+        running: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    impl CommandExecutor for ConcurrencyProbe {
+        fn execute<'a>(
+            &'a self,
+            _jj_path: &'a str,
+            _repo_path: Option<&'a str>,
+            _args: &'a [String],
+            _command_timeout: Duration,
+        ) -> BoxFuture<'a, Result<String>> {
+            use std::sync::atomic::Ordering;
+            Box::pin(async move {
+                let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(running, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                Ok(String::new())
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_max_concurrent_ops() {
+        let probe = Arc::new(ConcurrencyProbe::default());
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default().with_max_concurrent_ops(2))
+            .unwrap()
+            .with_executor(probe.clone());
+
+        let tasks: Vec<_> = (0..10)
+            .map(|i| {
+                let wrapper = wrapper.clone();
+                tokio::spawn(async move { wrapper.execute_checked(vec!["new".to_string(), format!("r{}", i)]).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(probe.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(wrapper.get_operations(20).unwrap().len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_new_operations() {
+        // jj op log lists newest first
+        let first = op_json("aa01", "snapshot working copy");
+        let second = format!("{}{}", op_json("bb02", "commit 0123"), first);
+        let third = format!("{}{}{}", op_json("dd04", "push to git remote origin"), op_json("cc03", "new empty commit"), second);
+        let executor = Arc::new(OpLogSequence {
+            outputs: Mutex::new(vec![first, second.clone(), second, third]),
+        });
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+
+        let mut batches = Vec::new();
+        wrapper
+            .watch(Duration::from_millis(1), |ops| {
+                batches.push(ops.into_iter().map(|op| op.operation_id).collect::<Vec<_>>());
+                if batches.len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(batches, vec![vec!["bb02"], vec!["cc03", "dd04"]]);
+    }
+
+    #[tokio::test]
+    async fn test_watch_polls_are_not_logged() {
+        let executor = Arc::new(OpLogSequence {
+            outputs: Mutex::new(vec![op_json("aa01", "snapshot working copy")]),
+        });
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+        wrapper.execute_checked(vec!["status".into()]).await.unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(wrapper.poll_op_log().await.unwrap().len(), 1);
+        }
+
+        assert_eq!(wrapper.operation_log.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watch_is_cancellable() {
+        let executor = Arc::new(OpLogSequence {
+            outputs: Mutex::new(vec![op_json("aa01", "snapshot working copy")]),
+        });
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+
+        let handle = tokio::spawn(async move {
+            wrapper
+                .watch(Duration::from_millis(1), |_| ControlFlow::Continue(()))
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        handle.abort();
+
+        assert!(handle.await.unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_diff_paths() {
+        let output = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,1 +1,2 @@\n line\n+added\n";
+        let executor = Arc::new(MockExecutor::with_output("diff", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let diff = wrapper.diff_paths("main", "@", &["src/lib.rs"]).await.unwrap();
+        wrapper.diff_paths("main", "@", &[]).await.unwrap();
+
+        assert_eq!(diff.additions, 1);
+        assert_eq!(diff.modified, vec!["src/lib.rs"]);
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(
+            calls[0],
+            vec!["diff", "--git", "--from", "main", "--to", "@", "src/lib.rs"]
+        );
+        assert_eq!(calls[1], vec!["diff", "--git", "--from", "main", "--to", "@"]);
+    }
+
+    #[tokio::test]
+    async fn test_interdiff() {
+        // Review feedback: the fix was reworded and a test was added
+        let output = "\
+diff --git a/src/parser.rs b/src/parser.rs
+index 1111111..2222222 100644
+--- a/src/parser.rs
++++ b/src/parser.rs
+@@ -10,3 +10,3 @@
+ fn parse(input: &str) -> Result<Ast> {
+-    let tokens = lex(input).unwrap();
++    let tokens = lex(input)?;
+     build(tokens)
+diff --git a/tests/parser.rs b/tests/parser.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/tests/parser.rs
+@@ -0,0 +1,2 @@
++#[test]
++fn rejects_bad_input() {}
+";
+        let executor = Arc::new(MockExecutor::with_output("interdiff", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let diff = wrapper.interdiff("xyz/1", "xyz").await.unwrap();
+
+        assert_eq!(diff.modified, vec!["src/parser.rs"]);
+        assert_eq!(diff.added, vec!["tests/parser.rs"]);
+        assert_eq!((diff.additions, diff.deletions), (3, 1));
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["interdiff", "--git", "--from", "xyz/1", "--to", "xyz"]
+        );
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Diff.as_string());
+
+        assert!(matches!(wrapper.interdiff("", "@").await, Err(JJError::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_files() {
+        let executor = file_executor("jj 0.28.0", "src/wrapper.rs\nCargo.toml\nsrc/lib.rs\n");
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let files = wrapper.list_files("@", None).await.unwrap();
+
+        assert_eq!(files, vec!["Cargo.toml", "src/lib.rs", "src/wrapper.rs"]);
+        assert_eq!(executor.calls.lock().unwrap()[1], vec!["file", "list", "-r", "@"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_files_with_prefix() {
+        let mut executor = MockExecutor::with_version("jj 0.18.0");
+        executor
+            .outputs
+            .insert("files".to_string(), "src/wrapper.rs\nsrc/lib.rs\n".to_string());
+        let executor = Arc::new(executor);
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let files = wrapper.list_files("main", Some("src")).await.unwrap();
+
+        assert_eq!(files, vec!["src/lib.rs", "src/wrapper.rs"]);
+        assert_eq!(executor.calls.lock().unwrap()[1], vec!["files", "-r", "main", "src"]);
+    }
+
+    #[tokio::test]
+    async fn test_branch_commands_follow_detected_version() {
+        for (version, expected) in [("jj 0.12.0", "branch"), ("jj 0.28.2", "bookmark")] {
+            let executor = Arc::new(MockExecutor::with_version(version));
+            let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+                .unwrap()
+                .with_executor(executor.clone());
+
+            wrapper.branch_create("feature".to_string(), None).await.unwrap();
+            wrapper.branch_delete("feature".to_string()).await.unwrap();
+            wrapper.branch_list().await.unwrap();
+
+            let calls = executor.calls.lock().unwrap();
+            // Version is detected once, then cached
+            assert_eq!(calls.iter().filter(|c| c[0] == "--version").count(), 1);
+            assert_eq!(calls[1], vec![expected, "create", "feature"]);
+            assert_eq!(calls[2], vec![expected, "delete", "feature"]);
+            assert_eq!(calls[3], vec![expected, "list"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tag_create_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.tag_create("v1.2.0", "main").await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["tag", "set", "v1.2.0", "-r", "main"]);
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Tag.as_string());
+        assert!(matches!(
+            wrapper.tag_create(" ", "main").await,
+            Err(JJError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_tag_list() {
+        let output = "\
+v1.0.0: qpvuntsm 1e4f0a2b Release 1.0
+v1.1.0: kkmpptxz 8a1c44d0 Release 1.1: bug fixes
+\"odd:name\": zsuskuln 3027ffe6 Quoted
+moved (conflicted):
+  - rlvkpnrz 9c1d2e3f old
+  + yqosqzyt 5d3c8a11 new
+";
+
+        assert_eq!(
+            JJWrapper::parse_tag_list(output),
+            vec!["v1.0.0", "v1.1.0", "odd:name", "moved"]
+        );
+        assert!(JJWrapper::parse_tag_list("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_is_clean() {
+        let clean = "The working copy has no changes.\n\
+            Working copy  (@) : qpvuntsm 1e4f0a2b (empty) (no description set)\n\
+            Parent commit (@-): zzzzzzzz 00000000 (empty) (no description set)\n";
+        let dirty = "Working copy changes:\n\
+            M src/lib.rs\n\
+            A src/new.rs\n\
+            Working copy  (@) : qpvuntsm 5d3c8a11 (no description set)\n\
+            Parent commit (@-): zzzzzzzz 00000000 (empty) (no description set)\n";
+        let conflicted = "The working copy has no changes.\n\
+            Working copy  (@) : qpvuntsm 7b2e9f40 (conflict) (empty) (no description set)\n\
+            Parent commit (@-): rlvkpnrz 9c1d2e3f (conflict) merge\n\
+            Warning: There are unresolved conflicts at these paths:\n\
+            src/lib.rs    2-sided conflict\n";
+
+        for (output, expected) in [(clean, true), (dirty, false), (conflicted, false)] {
+            let executor = Arc::new(MockExecutor::with_output("status", output));
+            let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+                .unwrap()
+                .with_executor(executor.clone());
+
+            assert_eq!(wrapper.is_clean().await.unwrap(), expected, "{}", output);
+            assert_eq!(executor.calls.lock().unwrap()[0], vec!["status"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bookmark_track_untrack() {
+        let executor = Arc::new(MockExecutor::with_version("jj 0.28.2"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.bookmark_track("main", "origin").await.unwrap();
+        wrapper.bookmark_untrack("feature", "upstream").await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[1], vec!["bookmark", "track", "main@origin"]);
+        assert_eq!(calls[2], vec!["bookmark", "untrack", "feature@upstream"]);
+    }
+
+    #[tokio::test]
+    async fn test_bookmark_track_rejects_invalid_names() {
+        let (wrapper, executor) = mock_wrapper();
+
+        for (name, remote) in [("", "origin"), ("main", ""), ("main@origin", "origin"), ("main", "a b")] {
+            let err = wrapper.bookmark_track(name, remote).await.unwrap_err();
+            assert!(matches!(err, JJError::InvalidConfig(_)), "{}@{}", name, remote);
+        }
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_branch_command_override() {
+        let executor = Arc::new(MockExecutor::with_version("jj 0.28.2"));
+        let config = JJConfig::default().with_branch_command(BranchCommand::Branch);
+        let wrapper = JJWrapper::with_config_checked(config)
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.branch_list().await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(*calls, vec![vec!["branch".to_string(), "list".to_string()]]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_change() {
+        let output = r#"{"commit_id":"9f8e7d6c5b4a","change_id":"kmnopqrs","description":"Add parser\n","author":"Alice","author_email":"alice@example.com","timestamp":"2024-05-01T10:00:00+00:00","empty":false,"conflict":false}
+"#;
+        let executor = Arc::new(MockExecutor::with_output("log", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let commit = wrapper.resolve_change("kmnopqrs").await.unwrap();
+        assert_eq!(commit.id, "9f8e7d6c5b4a");
+        assert_eq!(commit.message, "Add parser");
+        assert_eq!(commit.author_email, "alice@example.com");
+        assert_eq!(commit.timestamp, "2024-05-01T10:00:00+00:00");
+
+        let change = wrapper.commit_to_change("9f8e7d6c5b4a").await.unwrap();
+        assert_eq!(change, "kmnopqrs");
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(&calls[0][..4], ["log", "-r", "kmnopqrs", "--no-graph"]);
+        assert_eq!(calls[0][5], COMMIT_JSON_TEMPLATE);
+    }
+
+    fn commit_json(description: &str) -> String {
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "commit_id": "9f8e7d6c5b4a",
+                "change_id": "kmnopqrs",
+                "description": description,
+                "author": "Alice",
+                "author_email": "alice@example.com",
+                "timestamp": "2024-05-01T10:00:00+00:00",
+                "empty": false,
+                "conflict": false,
+            })
+        )
+    }
+
+    #[tokio::test]
+    async fn test_split_current_revision() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.split(None, &["src/parser.rs"]).await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec![
+                "split",
+                "--config",
+                r#"ui.diff-editor="false""#,
+                "--config",
+                &format!("ui.editor={}", toml_string(KEEP_DESCRIPTION_EDITOR)),
+                "-r",
+                "@",
+                "src/parser.rs"
+            ]
+        );
+
+        let ops = wrapper.get_operations(1).unwrap();
+        assert_eq!(ops[0].operation_type, OperationType::Split.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_split_explicit_revision() {
+        let executor = Arc::new(MockExecutor::default());
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default().with_non_interactive(false))
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.split(Some("kmnopqrs"), &["src/read.rs", "src/write.rs"]).await.unwrap();
+
+        assert_eq!(
+            *executor.calls.lock().unwrap(),
+            vec![vec![
+                "split".to_string(),
+                "--config".to_string(),
+                format!("ui.editor={}", toml_string(KEEP_DESCRIPTION_EDITOR)),
+                "-r".to_string(),
+                "kmnopqrs".to_string(),
+                "src/read.rs".to_string(),
+                "src/write.rs".to_string(),
+            ]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_keeps_wrapped_description() {
+        let wrapper = fake_wrapper();
+        let description = "Refactor io\n\nMoves reads and writes apart so each\ncan be tested on its own.";
+        wrapper.describe_multiline(&description.lines().collect::<Vec<_>>()).await.unwrap();
+        let original = wrapper.resolve_change("@").await.unwrap();
+
+        wrapper.split(None, &["src/read.rs", "src/write.rs"]).await.unwrap();
+
+        let first = wrapper.resolve_change("@-").await.unwrap();
+        let second = wrapper.resolve_change("@").await.unwrap();
+        assert_eq!(first.message, description);
+        assert_eq!(second.message, description);
+        assert_eq!(second.change_id, original.change_id);
+        assert_ne!(first.change_id, original.change_id);
+    }
+
+    #[tokio::test]
+    async fn test_current_commit() {
+        let executor = Arc::new(MockExecutor::with_output("log", &commit_json("Add parser\n")));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let commit = wrapper.current_commit().await.unwrap();
+        assert_eq!(commit.id, "9f8e7d6c5b4a");
+        assert_eq!(commit.message, "Add parser");
+        assert_eq!(wrapper.current_change_id().await.unwrap(), "kmnopqrs");
+
+        assert_eq!(executor.calls.lock().unwrap()[0][..3], ["log", "-r", "@"]);
+    }
+
+    #[tokio::test]
+    async fn test_current_commit_in_empty_repo() {
+        // A new repository's working copy is an empty, undescribed commit
+        let output = r#"{"commit_id":"e3b0c44298fc","change_id":"qpvuntsm","description":"","author":"","author_email":"","timestamp":"2024-05-01T10:00:00+00:00","empty":true,"conflict":false}
+"#;
+        let executor = Arc::new(MockExecutor::with_output("log", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+
+        let commit = wrapper.current_commit().await.unwrap();
+        assert_eq!(commit.change_id, "qpvuntsm");
+        assert_eq!(commit.message, "");
+    }
+
+    #[tokio::test]
+    async fn test_new_on_single_parent() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.new_on(&["main"], Some("Start feature")).await.unwrap();
+        wrapper.new_on(&["release~1"], None).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["new", "main", "-m", "Start feature"]);
+        assert_eq!(calls[1], vec!["new", "release~1"]);
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::New.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_new_on_multiple_parents() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.new_on(&["feature-a", "feature-b"], Some("Integrate")).await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["new", "feature-a", "feature-b", "-m", "Integrate"]
+        );
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Merge.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_new_on_requires_parent() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let err = wrapper.new_on(&[], None).await.unwrap_err();
+
+        assert!(matches!(err, JJError::InvalidConfig(_)));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_two_parents() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.merge(&["feature-a", "feature-b"], Some("Merge features")).await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["new", "feature-a", "feature-b", "-m", "Merge features"]
+        );
+        let ops = wrapper.get_operations(1).unwrap();
+        assert_eq!(ops[0].operation_type, OperationType::Merge.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_merge_three_parents() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.merge(&["a", "b", "c"], None).await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["new", "a", "b", "c"]);
+        let ops = wrapper.get_operations(1).unwrap();
+        assert_eq!(ops[0].operation_type, OperationType::Merge.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_merge_requires_two_revisions() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let err = wrapper.merge(&["a"], None).await.unwrap_err();
+
+        assert!(matches!(err, JJError::InvalidConfig(_)));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_new_with_one_parent_is_not_a_merge() {
+        assert_eq!(
+            JJWrapper::detect_operation_type(&["new", "main", "-m", "Start work"]),
+            OperationType::New
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate() {
+        let executor = Arc::new(MockExecutor::with_output("log", &commit_json("Add parser\n")));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let commit_id = wrapper.duplicate("kmnopqrs", None).await.unwrap();
+
+        assert_eq!(commit_id, "9f8e7d6c5b4a");
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["duplicate", "kmnopqrs"]);
+        assert_eq!(calls[1][..3], ["log", "-r", "latest((kmnopqrs)-+ ~ (kmnopqrs))"]);
+        drop(calls);
+
+        let ops = wrapper.get_operations(10).unwrap();
+        assert_eq!(ops[1].operation_type, OperationType::Duplicate.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_onto_destination() {
+        let executor = Arc::new(MockExecutor::with_output("log", &commit_json("Add parser\n")));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.duplicate("kmnopqrs", Some("main")).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["duplicate", "kmnopqrs", "-d", "main"]);
+        assert_eq!(calls[1][..3], ["log", "-r", "latest((main)+ ~ (kmnopqrs))"]);
+    }
+
+    #[tokio::test]
+    async fn test_split_requires_paths() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let err = wrapper.split(None, &[]).await.unwrap_err();
+
+        assert!(matches!(err, JJError::InvalidConfig(_)));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_describe_revision() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let op = wrapper.describe_revision("main~2", "Add parser").await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec![
+                "describe",
+                "--config",
+                r#"ui.editor="false""#,
+                "-r",
+                "main~2",
+                "-m",
+                "Add parser"
+            ]
+        );
+        assert_eq!(op.operation_type, OperationType::Describe.as_string());
+        assert_eq!(op.command, "jj describe -r main~2 -m Add parser");
+    }
+
+    #[tokio::test]
+    async fn test_describe_multiline() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper
+            .describe_multiline(&["Fix parser panic", "", "Lexer errors are now propagated."])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["describe", "--config", r#"ui.editor="false""#, "--stdin"]
+        );
+        assert_eq!(
+            *executor.stdin.lock().unwrap(),
+            vec!["Fix parser panic\n\nLexer errors are now propagated."]
+        );
+        assert_eq!(wrapper.get_operations(1).unwrap()[0].command, "jj describe --stdin");
+        assert!(matches!(wrapper.describe_multiline(&[]).await, Err(JJError::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_describe_from_file() {
+        let (wrapper, executor) = mock_wrapper();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("message.txt");
+        let message = "Fix parser panic\n\nLexer errors used to be unwrapped, which\naborted the agent:\n\n- lex() now returns Result\n- parse() propagates it\n\n    let tokens = lex(input)?;\n";
+        fs::write(&path, message).unwrap();
+
+        wrapper.describe_from_file(&path).await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[0].last().unwrap(), "--stdin");
+        assert_eq!(*executor.stdin.lock().unwrap(), vec![message]);
+
+        let missing = wrapper.describe_from_file(&dir.path().join("missing.txt")).await;
+        assert!(matches!(missing, Err(JJError::InvalidConfig(msg)) if msg.contains("missing.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_describe_revision_rejects_bad_revset() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let empty = wrapper.describe_revision(" ", "Add parser").await.unwrap_err();
+        let injected = wrapper.describe_revision("@; rm -rf /", "Add parser").await.unwrap_err();
+
+        assert!(matches!(empty, JJError::InvalidConfig(_)));
+        assert!(matches!(injected, JJError::InvalidConfig(_)));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_commit_json_signatures() {
+        let line = |id: &str, signature: &str| {
+            format!(
+                r#"{{"commit_id":"{}","change_id":"kxqv","description":"","author":"","author_email":"","timestamp":"","empty":false,"conflict":false,"signature":"{}"}}"#,
+                id, signature
+            )
+        };
+        let output = [line("aaa", "good"), line("bbb", "none"), line("ccc", "bad"), line("ddd", "unknown")].join("\n");
+
+        let commits = JJWrapper::parse_commit_json(&output).unwrap();
+
+        let signed: Vec<_> = commits.into_iter().map(|c| c.signed).collect();
+        assert_eq!(
+            signed,
+            vec![
+                Some(SignatureStatus::Good),
+                Some(SignatureStatus::Unsigned),
+                Some(SignatureStatus::Bad),
+                Some(SignatureStatus::Unknown),
+            ]
+        );
+        // The plain template doesn't request signatures
+        assert_eq!(JJWrapper::parse_commit_json(&commit_json("Add parser")).unwrap()[0].signed, None);
+    }
+
+    #[tokio::test]
+    async fn test_log_graph() {
+        let commit = |id: &str| {
+            format!(
+                "{}\n",
+                serde_json::json!({"commit_id": id, "change_id": format!("change-{}", id), "description": format!("Commit {}\n", id)})
+            )
+        };
+        // d merges b and c, both children of a; e is a disconnected root.
+        // a and e have parents outside the revset.
+        let mut executor = MockExecutor::default();
+        executor.outputs.insert("main..@".into(), ["d", "c", "b", "a", "e"].map(commit).concat());
+        for (child, parents) in [("d", "bc"), ("c", "a"), ("b", "a"), ("a", "x"), ("e", "y")] {
+            let parents: String = parents.chars().map(|p| commit(&p.to_string())).collect();
+            executor.outputs.insert(format!("{}-", child), parents);
+        }
+        let executor = Arc::new(executor);
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let graph = wrapper.log_graph("main..@", Some(10)).await.unwrap();
+
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.edges.len(), 4);
+        assert!(graph.nodes[0].is_merge);
+        assert_eq!(graph.nodes[0].parents, vec!["b", "c"]);
+        assert_eq!(graph.roots(), vec!["a", "e"]);
+        assert_eq!(executor.calls.lock().unwrap()[0][..5], ["log", "-r", "main..@", "--limit", "10"]);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph commits {\n"));
+        assert!(dot.contains("  \"d\" [label=\"d\\nCommit d\"];\n"));
+        assert!(dot.contains("  \"d\" -> \"c\";\n"));
+        assert_eq!(dot.matches(" -> ").count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_verify_signatures() {
+        let output = concat!(
+            r#"{"commit_id":"aaa","change_id":"kxqv","description":"","author":"","author_email":"","timestamp":"","empty":false,"conflict":false,"signature":"good"}"#,
+            "\n",
+            r#"{"commit_id":"bbb","change_id":"zzyx","description":"","author":"","author_email":"","timestamp":"","empty":false,"conflict":false,"signature":"none"}"#,
+            "\n",
+        );
+        let executor = Arc::new(MockExecutor::with_output("log", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let statuses = wrapper.verify_signatures("main..@").await.unwrap();
+
+        assert_eq!(
+            statuses,
+            vec![
+                ("aaa".to_string(), SignatureStatus::Good),
+                ("bbb".to_string(), SignatureStatus::Unsigned),
+            ]
+        );
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0][5], SIGNED_COMMIT_JSON_TEMPLATE);
+        assert!(validate_command_args(&[SIGNED_COMMIT_JSON_TEMPLATE]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_restore_from() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper
+            .restore_from("main~3", &["src/lib.rs", "Cargo.toml"])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["restore", "--from", "main~3", "src/lib.rs", "Cargo.toml"]
+        );
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Restore.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_restore_from_requires_paths() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let err = wrapper.restore_from("main", &[]).await.unwrap_err();
+
+        assert!(matches!(err, JJError::InvalidConfig(_)));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_squash_paths_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.squash_paths("@", "@--", &["src/parser.rs", "tests/parser.rs"]).await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec![
+                "squash",
+                "--config",
+                r#"ui.editor="false""#,
+                "--from",
+                "@",
+                "--into",
+                "@--",
+                "--use-destination-message",
+                "src/parser.rs",
+                "tests/parser.rs",
+            ]
+        );
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Squash.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_squash_paths_requires_paths() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let err = wrapper.squash_paths("@", "@-", &[]).await.unwrap_err();
+
+        assert!(matches!(err, JJError::InvalidConfig(_)));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_absorb_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.absorb(&[]).await.unwrap();
+        wrapper.absorb(&["src/lib.rs"]).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["absorb"]);
+        assert_eq!(calls[1], vec!["absorb", "src/lib.rs"]);
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Absorb.as_string());
+    }
+
+    #[tokio::test]
+    async fn test_fix_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.fix(None).await.unwrap();
+        wrapper.fix(Some("main..@")).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["fix"]);
+        assert_eq!(calls[1], vec!["fix", "-s", "main..@"]);
+        let op = &wrapper.get_operations(1).unwrap()[0];
+        assert_eq!(op.operation_type, OperationType::Fix.as_string());
+        assert!(OperationType::Fix.modifies_history());
+    }
+
+    #[test]
+    fn test_fixed_commits() {
+        let result = JJResult::new(
+            String::new(),
+            "Fixed 2 commits of 5 checked.\nWorking copy now at: qpvuntsm 5d3c8a11 fmt\n".to_string(),
+            0,
+            12,
+        );
+
+        assert_eq!(JJWrapper::fixed_commits(&result), Some((2, 5)));
+        assert_eq!(
+            JJWrapper::fixed_commits(&JJResult::new("Nothing changed.\n".to_string(), String::new(), 0, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_absorbed_revisions() {
+        let result = JJResult::new(
+            String::new(),
+            "Absorbed changes into these revisions:\n  \
+             zsuskuln 3027ffe6 fix parser\n  \
+             kkmpptxz 8a1c44d0 add parser\n\
+             Rebased 1 descendant commits.\n\
+             Working copy now at: yqosqzyt 1e4f0a2b (empty) (no description set)\n"
+                .to_string(),
+            0,
+            5,
+        );
+
+        assert_eq!(
+            JJWrapper::absorbed_revisions(&result),
+            vec!["zsuskuln", "kkmpptxz"]
+        );
+        assert!(JJWrapper::absorbed_revisions(&JJResult::new(String::new(), String::new(), 0, 1))
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_abandoned_change() {
+        let (wrapper, _executor) = mock_wrapper();
+
+        let err = wrapper.resolve_change("zzzzzzzz").await.unwrap_err();
+
+        assert!(matches!(err, JJError::CommandFailed(ref msg) if msg.contains("abandoned")));
+    }
+
+    #[test]
+    fn test_args_builder() {
+        let args: Vec<String> = Args::new(["git", "push"])
+            .opt_flag("--remote", Some("origin"))
+            .opt_flag("-b", None::<&str>)
+            .switch("--allow-new", true)
+            .switch("--dry-run", false)
+            .flag("-r", String::from("main"))
+            .opt_arg(Some("extra"))
+            .opt_arg(None::<String>)
+            .args(["a.rs", "b.rs"])
+            .into();
+
+        assert_eq!(
+            args,
+            vec!["git", "push", "--remote", "origin", "--allow-new", "-r", "main", "extra", "a.rs", "b.rs"]
+        );
+        assert!(Vec::<String>::from(Args::default()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_commit_json_flags() {
+        let output = concat!(
+            r#"{"commit_id":"1e4f0a2b","change_id":"qpvuntsm","description":"","author":"Alice","author_email":"alice@example.com","timestamp":"2024-05-01T10:00:00+00:00","empty":true,"conflict":false,"working_copy":true}"#,
+            "\n",
+            r#"{"commit_id":"8a1c44d0","change_id":"kkmpptxz","description":"add parser\n","author":"Alice","author_email":"alice@example.com","timestamp":"2024-05-01T09:00:00+00:00","empty":false,"conflict":false,"working_copy":false}"#,
+            "\n",
+        );
+
+        let commits = JJWrapper::parse_commit_json(output).unwrap();
+
+        assert!(commits[0].is_empty && commits[0].is_working_copy);
+        assert!(!commits[1].is_empty && !commits[1].is_working_copy);
+        assert_eq!(commits[1].message, "add parser");
+    }
+
+    #[test]
+    fn test_commit_json_template_passes_validation() {
+        assert!(validate_command_args(&[COMMIT_JSON_TEMPLATE]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_commits_since() {
+        let mut executor = MockExecutor::default();
+        executor.outputs.insert("0123abcd".to_string(), "aaa111\nbbb222\n".to_string());
+        executor
+            .outputs
+            .insert("::visible_heads() ~ ::aaa111 ~ ::bbb222".to_string(), commit_json("review fixes"));
+        let executor = Arc::new(executor);
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let commits = wrapper.commits_since("0123abcd").await.unwrap();
+
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].message, "review fixes");
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0][..5], ["log", "--at-operation", "0123abcd", "-r", "visible_heads()"]);
+        assert_eq!(calls[1][..3], ["log", "-r", "::visible_heads() ~ ::aaa111 ~ ::bbb222"]);
+    }
+
+    #[tokio::test]
+    async fn test_view_injects_at_operation_for_reads() {
+        let (wrapper, executor) = mock_wrapper();
+        let view = wrapper.at_op("0123abcd");
+
+        view.log(Some("main"), Some(5)).await.unwrap();
+        view.status().await.unwrap();
+        view.diff("@-", "@").await.unwrap();
+        view.execute(vec!["op".to_string(), "log".to_string()]).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0][..7], ["log", "--at-operation", "0123abcd", "-r", "main", "--limit", "5"]);
+        assert_eq!(calls[1], vec!["status", "--at-operation", "0123abcd"]);
+        assert_eq!(calls[2][..4], ["diff", "--at-operation", "0123abcd", "--git"]);
+        assert_eq!(calls[3], vec!["op", "--at-operation", "0123abcd", "log"]);
+        assert_eq!(view.operation_id(), "0123abcd");
+    }
+
+    #[tokio::test]
+    async fn test_view_rejects_writes() {
+        let (wrapper, executor) = mock_wrapper();
+        let view = wrapper.at_op("0123abcd");
+
+        for args in [vec!["describe", "-m", "x"], vec!["new"], vec!["rebase", "-d", "main"]] {
+            let args: Vec<String> = args.into_iter().map(String::from).collect();
+            let err = view.execute(args).await.unwrap_err();
+            assert!(matches!(err, JJError::InvalidConfig(_)), "{}", err);
+        }
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_commits_since_missing_operation() {
+        let executor = Arc::new(MockExecutor {
+            error: Some("Error: No operation ID matching \"0123abcd\"\n".to_string()),
+            ..MockExecutor::default()
+        });
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+
+        let err = wrapper.commits_since("0123abcd").await.unwrap_err();
+
+        assert_eq!(err, JJError::OperationNotFound("0123abcd".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_evolog() {
+        let versions: String = [
+            ("a1a1a1a1a1a1", "fix parser after review", false),
+            ("b2b2b2b2b2b2", "fix parser", true),
+            ("c3c3c3c3c3c3", "", true),
+        ]
+        .iter()
+        .map(|(commit_id, description, hidden)| {
+            format!(
+                "{}\n",
+                serde_json::json!({
+                    "commit_id": commit_id,
+                    "change_id": "kmnopqrs",
+                    "description": description,
+                    "author": "Alice",
+                    "author_email": "alice@example.com",
+                    "timestamp": "2024-05-01T10:00:00+00:00",
+                    "empty": description.is_empty(),
+                    "conflict": false,
+                    "hidden": hidden,
+                })
+            )
+        })
+        .collect();
+        let mut executor = MockExecutor::with_version("jj 0.28.0");
+        executor.outputs.insert("evolog".to_string(), versions);
+        let executor = Arc::new(executor);
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let history = wrapper.evolog("kmnopqrs").await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[1][..3], ["evolog", "-r", "kmnopqrs"]);
+        assert_eq!(history.len(), 3);
+        assert!(history.iter().all(|commit| commit.change_id == "kmnopqrs"));
+        assert_eq!(
+            history.iter().map(|commit| commit.is_hidden).collect::<Vec<_>>(),
+            vec![false, true, true]
+        );
+        assert_eq!(history[1].message, "fix parser");
+        assert!(history[2].is_empty);
+    }
+
+    #[tokio::test]
+    async fn test_evolog_falls_back_to_obslog() {
+        let executor = Arc::new(MockExecutor::with_version("jj 0.18.0"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.evolog("kmnopqrs").await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[1][0], "obslog");
+        assert!(validate_command_args(&[EVOLOG_JSON_TEMPLATE]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_operations_tagged_with_repo_path() {
+        let shared = JJOperationLog::new(100);
+        let executor = Arc::new(MockExecutor::default());
+        let frontend_config = JJConfig::default().with_repo_path("/repos/frontend".to_string());
+        let frontend = JJWrapper::with_config_checked(frontend_config)
+            .unwrap()
+            .with_executor(executor.clone())
+            .with_operation_log(shared.clone());
+        let backend_config = JJConfig::default().with_repo_path("/repos/backend".to_string());
+        let backend = JJWrapper::with_config_checked(backend_config)
+            .unwrap()
+            .with_executor(executor)
+            .with_operation_log(shared.clone());
+
+        frontend.execute_checked(vec!["status".to_string()]).await.unwrap();
+        backend.execute_checked(vec!["log".to_string()]).await.unwrap();
+        frontend.execute_checked(vec!["diff".to_string()]).await.unwrap();
+
+        assert_eq!(shared.len(), 3);
+        let ops = shared.filter_by_repo("/repos/frontend");
+        assert_eq!(ops.len(), 2);
+        assert!(ops.iter().all(|op| op.command != "jj log"));
+        assert_eq!(shared.filter_by_repo("/repos/backend").len(), 1);
+        assert!(shared.filter_by_repo("/repos/other").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repo_path_passed_to_executor() {
+        let executor = Arc::new(MockExecutor::default());
+        let config = JJConfig::default().with_repo_path("/repos/app".to_string());
+        let wrapper = JJWrapper::with_config_checked(config)
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.status().await.unwrap();
+        let cloned = wrapper
+            .git_clone("https://example.com/lib.git", Path::new("/repos/lib"))
+            .await
+            .unwrap();
+        cloned.status().await.unwrap();
+
+        let repo_paths = executor.repo_paths.lock().unwrap();
+        assert_eq!(repo_paths[0].as_deref(), Some("/repos/app"));
+        // Clone takes its destination as an argument instead
+        assert_eq!(repo_paths[1], None);
+        assert_eq!(repo_paths[2].as_deref(), Some("/repos/lib"));
+    }
+
+    #[tokio::test]
+    async fn test_default_repo_path_uses_current_directory() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.status().await.unwrap();
+
+        assert_eq!(executor.repo_paths.lock().unwrap()[0], None);
+    }
+
+    #[tokio::test]
+    async fn test_tagging_rules_applied_in_execute() {
+        let executor = Arc::new(MockExecutor {
+            delay: Some(Duration::from_millis(30)),
+            fail_on: Some("abandon".to_string()),
+            ..MockExecutor::default()
+        });
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor);
+        wrapper.add_tagging_rule(TaggingRule::slow(10));
+        wrapper.add_tagging_rule(TaggingRule::failed());
+
+        wrapper.status().await.unwrap();
+        let _ = wrapper.execute_checked(vec!["abandon".to_string()]).await;
+
+        let ops = wrapper.get_operations(10).unwrap();
+        assert_eq!(ops[1].tags, vec!["slow"]);
+        // Failing mock commands return immediately
+        assert_eq!(ops[0].tags, vec!["error"]);
+    }
+
+    fn branch_status_wrapper(remote: &str, ahead: &str, behind: &str) -> JJWrapper {
+        let mut executor = MockExecutor::with_version("jj 0.28.0");
+        let remote_revset = r#"remote_bookmarks(exact:"feature")"#;
+        let local_revset = r#"bookmarks(exact:"feature")"#;
+        for (revset, output) in [
+            (remote_revset.to_string(), remote),
+            (format!("{}..{}", remote_revset, local_revset), ahead),
+            (format!("{}..{}", local_revset, remote_revset), behind),
+        ] {
+            executor.outputs.insert(revset, output.to_string());
+        }
+        JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(Arc::new(executor))
+    }
+
+    #[tokio::test]
+    async fn test_branch_status() {
+        use crate::types::BranchSyncState;
+
+        let ahead = branch_status_wrapper("aaa111\n", "bbb222\nccc333\n", "");
+        let status = ahead.branch_status("feature").await.unwrap();
+        assert_eq!(status.state, BranchSyncState::Ahead);
+        assert_eq!((status.ahead, status.behind), (2, 0));
+
+        let behind = branch_status_wrapper("aaa111\n", "", "ddd444\n");
+        let status = behind.branch_status("feature").await.unwrap();
+        assert_eq!(status.state, BranchSyncState::Behind);
+        assert_eq!((status.ahead, status.behind), (0, 1));
+
+        let diverged = branch_status_wrapper("aaa111\n", "bbb222\n", "ddd444\neee555\n");
+        let status = diverged.branch_status("feature").await.unwrap();
+        assert_eq!(status.state, BranchSyncState::Diverged);
+        assert_eq!((status.ahead, status.behind), (1, 2));
+
+        let synced = branch_status_wrapper("aaa111\n", "", "");
+        assert_eq!(
+            synced.branch_status("feature").await.unwrap().state,
+            BranchSyncState::UpToDate
+        );
+    }
+
+    #[tokio::test]
+    async fn test_branch_status_untracked() {
+        use crate::types::BranchSyncState;
+
+        let wrapper = branch_status_wrapper("", "", "");
+        let status = wrapper.branch_status("feature").await.unwrap();
+
+        assert_eq!(status.state, BranchSyncState::Untracked);
+        // Only the remote lookup runs
+        assert_eq!(wrapper.get_operations(10).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_abandon_policies() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.abandon("a".to_string(), None).await.unwrap();
+        wrapper
+            .abandon("b".to_string(), Some(AbandonPolicy::RebaseDescendants))
+            .await
+            .unwrap();
+        wrapper
+            .abandon("c".to_string(), Some(AbandonPolicy::KeepDescendants))
+            .await
+            .unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["abandon", "a"]);
+        assert_eq!(calls[1], vec!["abandon", "b"]);
+        assert_eq!(calls[2], vec!["abandon", "--restore-descendants", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_rebase_modes() {
+        let (wrapper, executor) = mock_wrapper();
+
+        for mode in [None, Some(RebaseMode::Source), Some(RebaseMode::Branch), Some(RebaseMode::Revision)] {
+            wrapper
+                .rebase("feature".to_string(), "main".to_string(), mode)
+                .await
+                .unwrap();
+        }
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["rebase", "-s", "feature", "-d", "main"]);
+        assert_eq!(calls[1], vec!["rebase", "-s", "feature", "-d", "main"]);
+        assert_eq!(calls[2], vec!["rebase", "-b", "feature", "-d", "main"]);
+        assert_eq!(calls[3], vec!["rebase", "-r", "feature", "-d", "main"]);
+    }
+
+    #[tokio::test]
+    async fn test_rebase_requires_revsets() {
+        let (wrapper, executor) = mock_wrapper();
+
+        assert!(wrapper.rebase("".to_string(), "main".to_string(), None).await.is_err());
+        assert!(wrapper
+            .rebase("feature".to_string(), " ".to_string(), Some(RebaseMode::Branch))
+            .await
+            .is_err());
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_config_get() {
+        let executor = Arc::new(MockExecutor::with_output("get", "alice@example.com\n"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let email = wrapper.config_get("user.email").await.unwrap();
+
+        assert_eq!(email, "alice@example.com");
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["config", "get", "user.email"]);
+    }
+
+    #[tokio::test]
+    async fn test_config_get_missing_key() {
+        let executor = MockExecutor {
+            error: Some("Config error: Value not found for user.emial\n".to_string()),
+            ..Default::default()
+        };
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(Arc::new(executor));
+
+        let err = wrapper.config_get("user.emial").await.unwrap_err();
+
+        assert_eq!(err, JJError::ConfigKeyNotFound("user.emial".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_config_set_scopes() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper
+            .config_set("user.email", "bot@example.com", ConfigScope::Repo)
+            .await
+            .unwrap();
+        wrapper.config_set("ui.color", "never", ConfigScope::User).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["config", "set", "--repo", "user.email", "bot@example.com"]);
+        assert_eq!(calls[1], vec!["config", "set", "--user", "ui.color", "never"]);
+    }
+
+    #[tokio::test]
+    async fn test_abandon_many() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.abandon_many("empty() ~ @").await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["abandon", "empty() ~ @"]);
+    }
+
+    #[tokio::test]
+    async fn test_squash_with_message() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper
+            .squash(Some("abc".to_string()), Some("@-".to_string()), Some("Combined".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec![
+                "squash",
+                "--config",
+                r#"ui.editor="false""#,
+                "-r",
+                "abc",
+                "--into",
+                "@-",
+                "-m",
+                "Combined"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_squash_never_opens_editor() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.squash(None, None, None).await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["squash", "--config", r#"ui.editor="false""#, "--use-destination-message"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_describe_without_message_cannot_open_editor() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.execute_checked(vec!["describe".to_string()]).await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["describe", "--config", r#"ui.editor="false""#]
+        );
+        // The override is an execution detail, not part of the logged command
+        assert_eq!(wrapper.get_operations(1).unwrap()[0].command, "jj describe");
+    }
+
+    #[tokio::test]
+    async fn test_editor_overrides_per_command() {
+        let (wrapper, executor) = mock_wrapper();
+
+        for command in ["split", "resolve", "status"] {
+            wrapper.execute_checked(vec![command.to_string()]).await.unwrap();
+        }
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(
+            calls[0],
+            vec!["split", "--config", r#"ui.editor="false""#, "--config", r#"ui.diff-editor="false""#]
+        );
+        assert_eq!(calls[1], vec!["resolve", "--config", r#"ui.merge-editor="false""#]);
+        assert_eq!(calls[2], vec!["status"]);
+    }
+
+    #[tokio::test]
+    async fn test_revset_aliases_injected() {
+        let config = JJConfig::default()
+            .with_revset_alias("mine".to_string(), r#"author(exact:"me@x")"#.to_string())
+            .with_revset_alias("wip".to_string(), "description(wip) ~ ::trunk()".to_string());
+        let executor = Arc::new(MockExecutor::default());
+        let wrapper = JJWrapper::with_config_checked(config)
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.abandon_many("mine ~ wip").await.unwrap();
+
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec![
+                "abandon",
+                "--config",
+                r#"revset-aliases."mine"="author(exact:\"me@x\")""#,
+                "--config",
+                r#"revset-aliases."wip"="description(wip) ~ ::trunk()""#,
+                "mine ~ wip"
+            ]
+        );
+        assert_eq!(wrapper.get_operations(1).unwrap()[0].command, "jj abandon mine ~ wip");
+    }
+
+    #[tokio::test]
+    async fn test_operation_log_persists_across_wrappers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ops.jsonl").to_string_lossy().to_string();
+        let config = JJConfig::default()
+            .with_log_persist_path(path.clone())
+            .with_max_log_entries(2);
+
+        let wrapper = JJWrapper::with_config_checked(config.clone())
+            .unwrap()
+            .with_executor(Arc::new(MockExecutor::default()));
+        for command in ["status", "new", "log"] {
+            wrapper.execute_checked(vec![command.to_string()]).await.unwrap();
+        }
+        // Memory is trimmed, the file is not
+        assert_eq!(wrapper.get_operations(10).unwrap().len(), 2);
+        drop(wrapper);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        let restored = JJWrapper::with_config_checked(config).unwrap();
+        let commands: Vec<String> = restored
+            .get_operations(10)
+            .unwrap()
+            .into_iter()
+            .map(|op| op.command)
+            .collect();
+        assert_eq!(commands, vec!["jj log", "jj new"]);
+    }
+
+    fn cached_wrapper(ttl_ms: u32) -> (JJWrapper, Arc<MockExecutor>) {
+        let executor = Arc::new(MockExecutor::with_output("status", "Working copy changes:\n"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default().with_cache_ttl(ttl_ms))
+            .unwrap()
+            .with_executor(executor.clone());
+        (wrapper, executor)
+    }
+
+    #[tokio::test]
+    async fn test_read_only_output_cached_within_ttl() {
+        let (wrapper, executor) = cached_wrapper(60_000);
+
+        let first = wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+        let second = wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+
+        assert_eq!(first.stdout, second.stdout);
+        assert_eq!(executor.calls.lock().unwrap().len(), 1);
+        // Cache hits are still logged, marked as such
+        let ops = wrapper.get_operations(2).unwrap();
+        assert_eq!(ops[0].get_metadata("cached"), Some("true".to_string()));
+        assert_eq!(ops[1].get_metadata("cached"), None);
+    }
+
+    #[tokio::test]
+    async fn test_output_cache_cleared_by_mutation() {
+        let (wrapper, executor) = cached_wrapper(60_000);
+
+        wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+        wrapper
+            .execute_checked(vec!["commit".to_string(), "-m".to_string(), "Save".to_string()])
+            .await
+            .unwrap();
+        wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[2], vec!["status"]);
+    }
+
+    #[tokio::test]
+    async fn test_output_cache_expires() {
+        let (wrapper, executor) = cached_wrapper(1);
+
+        wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_output_not_cached_by_default() {
+        let (wrapper, executor) = mock_wrapper();
+
+        for _ in 0..2 {
+            wrapper.execute_checked(vec!["status".to_string()]).await.unwrap();
+        }
+
+        assert_eq!(executor.calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_interactive_mode_leaves_args_untouched() {
+        let executor = Arc::new(MockExecutor::default());
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default().with_non_interactive(false))
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.execute_checked(vec!["describe".to_string()]).await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["describe"]);
+    }
+
+    #[tokio::test]
+    async fn test_conventional_commit_messages_enforced() {
+        let executor = Arc::new(MockExecutor::default());
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default().with_conventional_commits())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper
+            .execute_checked(vec!["describe".into(), "-m".into(), "docs: explain hooks".into()])
+            .await
+            .unwrap();
+
+        let err = wrapper
+            .execute_checked(vec!["describe".into(), "-m".into(), "explain hooks".into()])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, JJError::HookVetoed(ref msg) if msg.contains("explain hooks")));
+
+        // The rejected message never reached jj
+        assert_eq!(executor.calls.lock().unwrap().len(), 1);
+    }
+
+    fn batch_ops() -> Vec<BatchOp> {
+        vec![
+            BatchOp::Describe {
+                revision: "@-".to_string(),
+                message: "Fix parser".to_string(),
+            },
+            BatchOp::Edit("xyz".to_string()),
+            BatchOp::New(Some("Next".to_string())),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_batch_stops_on_error() {
+        let executor = Arc::new(MockExecutor::failing_on("edit"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let err = wrapper.batch(batch_ops()).await.unwrap_err();
+
+        assert!(matches!(err, JJError::CommandFailed(_)));
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0],
+            vec!["describe", "--config", r#"ui.editor="false""#, "@-", "-m", "Fix parser"]
+        );
+        drop(calls);
+        // Each attempted operation is logged individually
+        assert_eq!(wrapper.get_operations(10).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_continue_on_error() {
+        let executor = Arc::new(MockExecutor::failing_on("edit"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        let results = wrapper.batch_with_options(batch_ops(), true).await.unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].success());
+        assert!(!results[1].success());
+        assert!(results[1].stderr.contains("edit failed"));
+        assert!(results[2].success());
+        assert_eq!(executor.calls.lock().unwrap()[2], vec!["new", "-m", "Next"]);
+
+        let ops = wrapper.get_operations(10).unwrap();
+        assert_eq!(ops.len(), 3);
+        assert_eq!(ops.iter().filter(|op| !op.success).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_git_fetch_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.git_fetch(None).await.unwrap();
+        wrapper.git_fetch(Some("upstream")).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["git", "fetch"]);
+        assert_eq!(calls[1], vec!["git", "fetch", "--remote", "upstream"]);
+        drop(calls);
+
+        let ops = wrapper.get_operations(10).unwrap();
+        assert!(ops.iter().all(|op| op.operation_type == "GitFetch"));
+    }
+
+    #[tokio::test]
+    async fn test_git_push_args() {
+        let (wrapper, executor) = mock_wrapper();
+
+        wrapper.git_push(None, None, false).await.unwrap();
+        wrapper.git_push(Some("origin"), None, false).await.unwrap();
+        wrapper.git_push(None, Some("feature"), false).await.unwrap();
+        wrapper.git_push(None, Some("feature"), true).await.unwrap();
+        wrapper.git_push(Some("origin"), Some("feature"), true).await.unwrap();
+        wrapper.git_push(None, None, true).await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["git", "push"]);
+        assert_eq!(calls[1], vec!["git", "push", "--remote", "origin"]);
+        assert_eq!(calls[2], vec!["git", "push", "-b", "feature"]);
+        assert_eq!(calls[3], vec!["git", "push", "-b", "feature", "--allow-new"]);
+        assert_eq!(
+            calls[4],
+            vec!["git", "push", "--remote", "origin", "-b", "feature", "--allow-new"]
+        );
+        assert_eq!(calls[5], vec!["git", "push", "--allow-new"]);
+        drop(calls);
+
+        let ops = wrapper.get_operations(10).unwrap();
+        assert_eq!(ops.len(), 6);
+        assert!(ops.iter().all(|op| op.operation_type == "GitPush"));
+    }
+
+    #[tokio::test]
+    async fn test_git_clone() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let cloned = wrapper
+            .git_clone("https://example.com/repo.git", Path::new("/tmp/agent-clone"))
+            .await
+            .unwrap();
+
+        assert_eq!(cloned.get_config().repo_path, "/tmp/agent-clone");
+        assert_eq!(
+            executor.calls.lock().unwrap()[0],
+            vec!["git", "clone", "https://example.com/repo.git", "/tmp/agent-clone"]
+        );
+        assert_eq!(wrapper.get_operations(1).unwrap()[0].operation_type, "Clone");
+
+        // The new wrapper keeps using the same executor
+        cloned.status().await.unwrap();
+        assert_eq!(executor.calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_init() {
+        let (wrapper, executor) = mock_wrapper();
+
+        let git = wrapper.init(Path::new("/tmp/agent-git"), true).await.unwrap();
+        let native = wrapper.init(Path::new("/tmp/agent-native"), false).await.unwrap();
+
+        assert_eq!(git.get_config().repo_path, "/tmp/agent-git");
+        assert_eq!(native.get_config().repo_path, "/tmp/agent-native");
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[0], vec!["git", "init", "/tmp/agent-git"]);
+        assert_eq!(calls[1], vec!["init", "/tmp/agent-native"]);
+    }
+
+    #[tokio::test]
+    async fn test_init_rejects_existing_repo() {
+        let (wrapper, executor) = mock_wrapper();
+        let dir = std::env::temp_dir().join(format!("jj-init-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(".jj")).unwrap();
+
+        let result = wrapper.init(&dir, true).await;
+
+        assert!(matches!(result, Err(JJError::InvalidConfig(_))));
+        assert!(executor.calls.lock().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rewritten_args_are_validated() {
+        let (wrapper, executor) = mock_wrapper();
+        wrapper.register_pre_operation_hook(|event| {
+            event.context.args.push("; rm -rf /".to_string());
+            Ok(())
+        });
+
+        let result = wrapper.execute_checked(vec!["status".to_string()]).await;
+
+        assert!(matches!(result, Err(JJError::InvalidConfig(_))));
+        assert!(executor.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_task_correlation() {
+        let (wrapper, _executor) = mock_wrapper();
+        wrapper.execute_checked(vec!["status".into()]).await.unwrap();
+
+        wrapper.begin_task("fix-parser");
+        wrapper.execute_checked(vec!["new".into()]).await.unwrap();
+        wrapper.execute_checked(vec!["describe".into(), "-m".into(), "Fix parser".into()]).await.unwrap();
+        assert_eq!(wrapper.end_task(), Some("fix-parser".to_string()));
+        wrapper.execute_checked(vec!["log".into()]).await.unwrap();
+
+        let log = wrapper.operation_log.lock().unwrap().clone();
+        let task_ops = log.filter_by_task("fix-parser");
+        let types: Vec<&str> = task_ops.iter().map(|op| op.operation_type.as_str()).collect();
+        assert_eq!(types, vec!["New", "Describe"]);
+        assert!(log.filter_by_task("other").is_empty());
+        assert_eq!(log.get_recent(1)[0].get_metadata("task_id"), None);
+        assert_eq!(wrapper.end_task(), None);
+    }
+
+    #[tokio::test]
+    async fn test_logged_operations_stay_distinct() {
+        let (wrapper, _executor) = mock_wrapper();
+        wrapper.execute_checked(vec!["status".into()]).await.unwrap();
+        wrapper.execute_checked(vec!["status".into()]).await.unwrap();
+        wrapper.execute_checked(vec!["log".into()]).await.unwrap();
+
+        let ops = wrapper.get_operations(10).unwrap();
+        assert_eq!(ops.len(), 3);
+        assert!(ops.iter().all(|op| !op.has_jj_operation_id()));
+        let unique: std::collections::HashSet<JJOperation> = ops.iter().cloned().collect();
+        assert_eq!(unique.len(), 3);
+        assert_eq!(ops[0], ops[0].clone());
+    }
+
+    #[tokio::test]
+    async fn test_operation_log_disabled() {
+        let executor = Arc::new(MockExecutor::default());
+        let config = JJConfig::builder().enable_operation_log(false).build().unwrap();
+        let wrapper = JJWrapper::with_config_checked(config)
+            .unwrap()
+            .with_executor(executor.clone());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_hook = seen.clone();
+        wrapper.register_post_operation_hook(move |event| {
+            let op = event.operation.as_ref().map(|op| op.command.clone());
+            seen_by_hook.lock().unwrap().push(op);
+            Ok(())
+        });
+
+        for _ in 0..3 {
+            wrapper.execute_checked(vec!["status".into()]).await.unwrap();
+        }
+        let _ = wrapper.execute_checked(vec!["bogus".into()]).await;
+
+        assert_eq!(executor.calls.lock().unwrap().len(), 4);
+        assert!(wrapper.get_operations(10).unwrap().is_empty());
+        assert_eq!(wrapper.operation_log.lock().unwrap().len(), 0);
+        // Hooks still see the operation even though it isn't stored
+        assert_eq!(seen.lock().unwrap()[0].as_deref(), Some("jj status"));
+    }
+
+    fn snapshot_wrapper() -> (JJWrapper, Arc<MockExecutor>) {
+        let output = concat!(
+            r#"{"id":"c3d4e5f6a7b8","description":"new empty commit","user":"alice@laptop","start":"2024-05-01T10:01:00.000+00:00","end":"2024-05-01T10:01:00.020+00:00"}"#,
+            "\n",
+            r#"{"id":"b2c3d4e5f6a7","description":"snapshot working copy","user":"alice@laptop","start":"2024-05-01T10:00:30.000+00:00","end":"2024-05-01T10:00:30.010+00:00"}"#,
+            "\n",
+            r#"{"id":"a1b2c3d4e5f6","description":"snapshot working copy","user":"alice@laptop","start":"2024-05-01T09:59:00.000+00:00","end":"2024-05-01T09:59:00.010+00:00"}"#,
+            "\n",
+        );
+        let executor = Arc::new(MockExecutor::with_output("log", output));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+        (wrapper, executor)
+    }
+
+    #[tokio::test]
+    async fn test_list_snapshots() {
+        let (wrapper, _executor) = snapshot_wrapper();
+
+        let snapshots = wrapper.list_snapshots().await.unwrap();
+
+        let ids: Vec<&str> = snapshots.iter().map(|op| op.operation_id.as_str()).collect();
+        assert_eq!(ids, vec!["b2c3d4e5f6a7", "a1b2c3d4e5f6"]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_snapshot_args() {
+        let (wrapper, executor) = snapshot_wrapper();
+
+        wrapper.restore_snapshot("a1b2").await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[1], vec!["op", "restore", "a1b2c3d4e5f6"]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_snapshot_validates_id() {
+        let (wrapper, executor) = snapshot_wrapper();
+
+        let err = wrapper.restore_snapshot("c3d4").await.unwrap_err();
+        assert!(matches!(err, JJError::InvalidConfig(msg) if msg.contains("not a snapshot")));
+        let err = wrapper.restore_snapshot("ffff").await.unwrap_err();
+        assert_eq!(err, JJError::OperationNotFound("ffff".to_string()));
+        assert!(matches!(wrapper.restore_snapshot("").await, Err(JJError::InvalidConfig(_))));
+
+        let calls = executor.calls.lock().unwrap();
+        assert!(calls.iter().all(|call| call[1] != "restore"));
+    }
+
+    #[tokio::test]
+    async fn test_undo_safe_after_commit() {
+        let (wrapper, executor) = mock_wrapper();
+        wrapper
+            .operation_log
+            .lock()
+            .unwrap()
+            .add_operation(JJOperation::builder().operation_type(OperationType::Commit).build());
+
+        wrapper.undo_safe().await.unwrap();
+
+        assert_eq!(executor.calls.lock().unwrap()[0], vec!["undo"]);
+    }
+
+    #[tokio::test]
+    async fn test_undo_safe_refuses_push() {
+        let (wrapper, executor) = mock_wrapper();
+        wrapper.execute_checked(vec!["git".into(), "push".into()]).await.unwrap();
+        // Reads in between don't hide the push
+        wrapper.execute_checked(vec!["status".into()]).await.unwrap();
+        wrapper.branch_list().await.unwrap();
+        wrapper.execute_checked(vec!["root".into()]).await.unwrap();
+
+        let err = wrapper.undo_safe().await.unwrap_err();
+
+        assert!(matches!(err, JJError::NotUndoable(msg) if msg.contains("GitPush")));
+        assert!(!executor.calls.lock().unwrap().iter().any(|call| call == &["undo"]));
+
+        wrapper.undo().await.unwrap();
+        assert_eq!(executor.calls.lock().unwrap().last().unwrap(), &["undo"]);
+    }
+
+    #[test]
+    fn test_parse_status_changes() {
+        let output = "\
+Working copy changes:
+M src/lib.rs
+A src/new.rs
+D src/old.rs
+R src/{parser.rs => parse/mod.rs}
+R docs/a.md => docs/b.md
+M src/merge.rs
+Working copy  (@) : kkmpptxz 3d4e5f6a (conflict) Fix parser
+Parent commit (@-): qpvuntsm 1a2b3c4d Add parser
+There are unresolved conflicts at these paths:
+src/merge.rs    2-sided conflict
+Cargo.lock    3-sided conflict including 1 deletion
+";
+        let changes = JJWrapper::parse_status_changes(output);
+
+        let summary: Vec<(&str, &ChangeStatus, bool)> =
+            changes.iter().map(|c| (c.path.as_str(), &c.kind, c.conflicted)).collect();
+        assert!(matches!(
+            summary.as_slice(),
+            [
+                ("src/lib.rs", ChangeStatus::Modified, false),
+                ("src/new.rs", ChangeStatus::Added, false),
+                ("src/old.rs", ChangeStatus::Deleted, false),
+                ("src/parse/mod.rs", ChangeStatus::Renamed, false),
+                ("docs/b.md", ChangeStatus::Renamed, false),
+                ("src/merge.rs", ChangeStatus::Modified, true),
+                ("Cargo.lock", ChangeStatus::Conflicted, true),
+            ]
+        ));
+        assert_eq!(changes[3].old_path.as_deref(), Some("src/parser.rs"));
+        assert_eq!(changes[4].old_path.as_deref(), Some("docs/a.md"));
+        assert_eq!(changes[0].old_path, None);
+    }
+
+    #[test]
+    fn test_parse_status_changes_clean() {
+        let output = "\
+The working copy has no changes.
+Working copy  (@) : kkmpptxz 3d4e5f6a (empty) (no description set)
+Parent commit (@-): qpvuntsm 1a2b3c4d Add parser
+";
+        assert!(JJWrapper::parse_status_changes(output).is_empty());
+    }
+
+    fn fake_wrapper() -> JJWrapper {
+        JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(Arc::new(crate::native::FakeExecutor::new()))
+    }
+
+    #[tokio::test]
+    async fn test_fake_repo_new_describe_log() {
+        let wrapper = fake_wrapper();
+
+        wrapper.describe("Add parser".to_string()).await.unwrap();
+        wrapper.new_commit(Some("Add tests".to_string())).await.unwrap();
+        wrapper.new_commit(None).await.unwrap();
+        wrapper.describe("Wire up CLI".to_string()).await.unwrap();
+
+        let graph = wrapper.log_graph("all()", None).await.unwrap();
+        let descriptions: Vec<&str> = graph.nodes.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(descriptions, vec!["Wire up CLI", "Add tests", "Add parser", ""]);
+        assert!(graph.nodes[0].is_working_copy);
+        assert!(graph.nodes[1..].iter().all(|c| !c.is_working_copy));
+        assert_eq!(graph.nodes[0].parents, vec![graph.nodes[1].id.clone()]);
+        assert_eq!(graph.nodes[2].parents, vec![graph.nodes[3].id.clone()]);
+        assert_eq!(graph.nodes[0].author_email, "test.user@example.com");
+
+        let current = wrapper.current_commit().await.unwrap();
+        assert_eq!(current.id, graph.nodes[0].id);
+        assert_eq!(wrapper.resolve_change(&current.change_id).await.unwrap().id, current.id);
+    }
+
+    #[tokio::test]
+    async fn test_fake_repo_text_log() {
+        let wrapper = fake_wrapper();
+        wrapper.new_commit(Some("second".to_string())).await.unwrap();
+
+        let commits = wrapper.log(Some(2)).await.unwrap();
+
+        let current = wrapper.current_commit().await.unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].id, current.id);
+        assert_eq!(commits[0].change_id, current.change_id);
+        assert_eq!(commits[1].author, "Test User");
+    }
+
+    #[tokio::test]
+    async fn test_fake_repo_describe_stdin_round_trip() {
+        let wrapper = fake_wrapper();
+        let message = "Fix parser panic\n\n- lex() now returns Result\n- parse() propagates it\n\n    let tokens = lex(input)?;";
+
+        wrapper.describe_multiline(&message.lines().collect::<Vec<_>>()).await.unwrap();
+
+        assert_eq!(wrapper.resolve_change("@").await.unwrap().message, message);
+    }
+
+    #[tokio::test]
+    async fn test_editor_override_fails_as_editor_error() {
+        let wrapper = fake_wrapper();
+
+        let err = wrapper.execute_checked(vec!["describe".to_string()]).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Editor 'false' exited with exit status: 1"),
+            "{}",
+            err
+        );
+
+        // Unquoted, the value is a TOML boolean and jj rejects the config instead
+        let err = wrapper
+            .execute_checked(vec!["describe".into(), "--config".into(), "ui.editor=false".into()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid type or value for ui.editor"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_fake_repo_unknown_revision() {
+        let wrapper = fake_wrapper();
+
+        let err = wrapper.resolve_change("nonexistent").await.unwrap_err();
+
+        assert_eq!(err, JJError::RevisionNotFound("nonexistent".to_string()));
+    }
+
+    #[test]
+    fn test_parse_bookmark_json() {
+        let output = concat!(
+            r#"{"name":"main","remote":"","present":true,"conflict":false,"target":"aaa111","tracked":false,"remote_ahead":0,"remote_behind":0}"#, "\n",
+            r#"{"name":"main","remote":"git","present":true,"conflict":false,"target":"aaa111","tracked":true,"remote_ahead":0,"remote_behind":0}"#, "\n",
+            r#"{"name":"main","remote":"origin","present":true,"conflict":false,"target":"bbb222","tracked":true,"remote_ahead":1,"remote_behind":2}"#, "\n",
+            r#"{"name":"spike","remote":"","present":true,"conflict":false,"target":"ccc333","tracked":false,"remote_ahead":0,"remote_behind":0}"#, "\n",
+            r#"{"name":"spike","remote":"origin","present":true,"conflict":false,"target":"ddd444","tracked":false,"remote_ahead":0,"remote_behind":0}"#, "\n",
+            r#"{"name":"topic","remote":"","present":true,"conflict":true,"target":"","tracked":false,"remote_ahead":0,"remote_behind":0}"#, "\n",
+        );
+
+        let branches = JJWrapper::parse_bookmark_json(output).unwrap();
+
+        let names: Vec<String> = branches.iter().map(JJBranch::full_name).collect();
+        assert_eq!(names, vec!["main", "origin/main", "spike", "origin/spike", "topic"]);
+
+        // Tracked: local main is 2 ahead of and 1 behind main@origin
+        let (main, origin_main) = (&branches[0], &branches[1]);
+        assert!(main.is_tracking && !main.is_remote);
+        assert_eq!(main.tracking_remote.as_deref(), Some("origin"));
+        assert_eq!((main.ahead, main.behind), (Some(2), Some(1)));
+        assert!(origin_main.is_remote && origin_main.is_tracking);
+        assert_eq!(origin_main.target, "bbb222");
+        assert_eq!((origin_main.ahead, origin_main.behind), (Some(2), Some(1)));
+
+        // Untracked: no tracking state or counts on either side
+        let (spike, origin_spike) = (&branches[2], &branches[3]);
+        assert!(!spike.is_tracking && spike.tracking_remote.is_none());
+        assert!(!origin_spike.is_tracking);
+        assert_eq!((spike.ahead, origin_spike.behind), (None, None));
+
+        // Conflicted: flagged, with no single target
+        assert!(branches[4].conflicted);
+        assert_eq!(branches[4].target, "");
+        assert!(branches[..4].iter().all(|b| !b.conflicted));
+    }
+
+    #[tokio::test]
+    async fn test_bookmark_list_all_args() {
+        let executor = Arc::new(MockExecutor::with_version("jj 0.25.0"));
+        let wrapper = JJWrapper::with_config_checked(JJConfig::default())
+            .unwrap()
+            .with_executor(executor.clone());
+
+        wrapper.bookmark_list_all().await.unwrap();
+
+        let calls = executor.calls.lock().unwrap();
+        assert_eq!(calls[1][..5], ["bookmark", "list", "-a", "-T", BOOKMARK_JSON_TEMPLATE]);
+        drop(calls);
+        JJWrapper::validate_args(&[BOOKMARK_JSON_TEMPLATE.to_string()]).unwrap();
+    }
+
+    #[test]
+    fn test_parse_log_skips_partial_records() {
+        let output = "\
+Commit ID: 0123abcd
+Change ID: qpvuntsm
+Author: Alice <alice@example.com>
+
+Change ID: zzzzzzzz
+Author: Bob <bob@example.com>
+
+garbage that is not a record
+
+Commit ID: not-a-hash
+Change ID: kkmpptxz
+
+Commit ID: 4567ef01
+Change ID: rlvkpnrz
+
+";
+        let (commits, parse_warnings) = JJWrapper::parse_log(output);
+
+        let ids: Vec<&str> = commits.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["0123abcd", "4567ef01"]);
+        assert_eq!(commits[0].author, "Alice");
+        assert_eq!(commits[0].author_email, "alice@example.com");
+        assert_eq!(commits[1].change_id, "rlvkpnrz");
+        assert_eq!(parse_warnings, 3);
+
+        assert_eq!(JJWrapper::parse_log("").1, 0);
     }
 
     #[test]